@@ -0,0 +1,22 @@
+use linux_embedded_hal::I2cdev;
+use mb85rc::{Builder, FramDevice, UsbBridge};
+
+fn main() {
+    // usage: ch341 <bus> [address]
+    // the CH341A shows up as an i2c-dev bus once the out-of-tree
+    // i2c-ch341-usb driver is loaded; `i2cdetect -l` says which one
+    let mut args = std::env::args().skip(1);
+    let path = args.next().expect("usage: ch341 <bus> [address]");
+    let address = args.next().map(|a| a.parse().unwrap()).unwrap_or(0u32);
+
+    let i2c = I2cdev::new(&path).unwrap();
+    let mut fram = Builder::new().with_usb_bridge(UsbBridge::Ch341).connect_i2c(i2c);
+    println!("{} byte FRAM, {:?}", fram.fram_size(), fram.device_id().unwrap());
+
+    // 256 bytes goes out as sixteen 16-byte transactions
+    let data: Vec<u8> = (0..=255).collect();
+    fram.write_at(address, &data).unwrap();
+    let mut back = vec![0u8; data.len()];
+    fram.read_at(address, &mut back).unwrap();
+    println!("read back {}", if back == data { "OK" } else { "MISMATCH" });
+}
@@ -0,0 +1,39 @@
+use linux_embedded_hal::I2cdev;
+use mb85rc::{Builder, CrashDump, DumpSection};
+
+fn main() {
+    // usage: crash-dump [bus] [region start] [region end] [--clear]
+    let args: Vec<String> = std::env::args().skip(1).filter(|a| a != "--clear").collect();
+    let clear = std::env::args().any(|a| a == "--clear");
+    let path = args.first().cloned().unwrap_or_else(|| "/dev/i2c-1".to_string());
+    let start = args.get(1).map(|a| a.parse().unwrap()).unwrap_or(0);
+    let end = args.get(2).map(|a| a.parse().unwrap()).unwrap_or(1024);
+
+    let i2c = I2cdev::new(&path).unwrap();
+    let mut fram = Builder::new().try_connect_i2c(i2c).unwrap();
+    let dump = CrashDump::new(start..end).unwrap();
+
+    let mut sections = [DumpSection::default(); 32];
+    let count = match dump.sections(&mut fram, &mut sections) {
+        Ok(count) => count,
+        Err(_) => {
+            println!("no crash dump stored");
+            return;
+        },
+    };
+
+    let mut buf = [0u8; u16::MAX as usize];
+    for section in &sections[..count] {
+        let len = dump.read_section(&mut fram, section, &mut buf).unwrap();
+        println!("section {} ({} bytes):", section.tag, len);
+        for line in buf[..len].chunks(16) {
+            let hex: Vec<String> = line.iter().map(|b| format!("{:02X}", b)).collect();
+            println!("  {}", hex.join(" "));
+        }
+    }
+
+    if clear {
+        dump.clear(&mut fram).unwrap();
+        println!("cleared");
+    }
+}
@@ -0,0 +1,79 @@
+use embassy_executor::Spawner;
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_time::{Duration, Ticker};
+use mb85rc::{Builder, Mb85rcError, SharedBusFram, SimFram};
+
+/// Where the state lives in FRAM
+const STATE_ADDR: u32 = 0x40;
+
+/// What the firmware wants to keep across resets
+#[derive(Debug, Default)]
+struct State {
+    boots: u32,
+    uptime_secs: u32,
+}
+
+impl State {
+    const SIZE: usize = 8;
+
+    fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..4].copy_from_slice(&self.boots.to_le_bytes());
+        bytes[4..].copy_from_slice(&self.uptime_secs.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        Self {
+            boots: u32::from_le_bytes(bytes[..4].try_into().unwrap()),
+            uptime_secs: u32::from_le_bytes(bytes[4..].try_into().unwrap()),
+        }
+    }
+}
+
+type Fram = SharedBusFram<'static, NoopRawMutex, SimFram>;
+
+/// Load the state, count a boot, and write it back every second
+#[embassy_executor::task]
+async fn persist(mut fram: Fram, periods: u32) {
+    let result: Result<(), Mb85rcError> = async {
+        let mut raw = [0u8; State::SIZE];
+        fram.read_at(STATE_ADDR, &mut raw).await?;
+        let mut state = State::from_bytes(raw);
+        state.boots += 1;
+        println!("loaded {:?}", state);
+
+        let mut ticker = Ticker::every(Duration::from_secs(1));
+        for _ in 0..periods {
+            fram.write_at(STATE_ADDR, &state.to_bytes()).await?;
+            ticker.next().await;
+            state.uptime_secs += 1;
+        }
+        fram.write_at(STATE_ADDR, &state.to_bytes()).await?;
+        println!("saved {:?}", state);
+        Ok(())
+    }.await;
+
+    if let Err(e) = result {
+        eprintln!("FRAM error: {}", e);
+    }
+    std::process::exit(0);
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    // usage: embassy-persist [file] [seconds]
+    // on hardware the bus is the HAL's async I2C peripheral; here a simulated
+    // device backed by a file stands in, so the state survives between runs
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "embassy-persist.bin".to_string());
+    let periods = args.next().map(|n| n.parse().unwrap()).unwrap_or(3);
+
+    let bus = SimFram::open(&path, 32 * 1024).unwrap();
+    let bus: &'static Mutex<NoopRawMutex, SimFram> = Box::leak(Box::new(Mutex::new(bus)));
+
+    let fram = Builder::new().connect_shared(bus).await.unwrap();
+    println!("{} byte FRAM on the shared bus", fram.fram_size());
+    spawner.spawn(persist(fram, periods).unwrap());
+}
@@ -0,0 +1,27 @@
+use ftdi_embedded_hal::FtHal;
+use mb85rc::{Builder, FramDevice, UsbBridge};
+
+fn main() {
+    // usage: ft232h [address]
+    // an Adafruit FT232H or similar breakout, with D1 and D2 tied together as
+    // SDA and D0 as SCL, running the bus at 400 kHz. Links against the
+    // system libftdi1 (libftdi1-dev on Debian)
+    let address = std::env::args().nth(1).map(|a| a.parse().unwrap()).unwrap_or(0u32);
+
+    let device = ftdi::find_by_vid_pid(0x0403, 0x6014)
+        .interface(ftdi::Interface::A)
+        .open()
+        .unwrap();
+    let hal = FtHal::init_freq(device, 400_000).unwrap();
+    let i2c = hal.i2c().unwrap();
+
+    let mut fram = Builder::new().with_usb_bridge(UsbBridge::Ft232h).connect_i2c(i2c);
+    println!("{} byte FRAM, {:?}", fram.fram_size(), fram.device_id().unwrap());
+
+    // 256 bytes goes out as four 64-byte transactions
+    let data: Vec<u8> = (0..=255).collect();
+    fram.write_at(address, &data).unwrap();
+    let mut back = vec![0u8; data.len()];
+    fram.read_at(address, &mut back).unwrap();
+    println!("read back {}", if back == data { "OK" } else { "MISMATCH" });
+}
@@ -0,0 +1,16 @@
+use linux_embedded_hal::I2cdev;
+use mb85rc::{scan, Probe};
+
+fn main() {
+    // bus can be given on the command line, defaulting to the raspi's main i2c bus
+    let path = std::env::args().nth(1).unwrap_or_else(|| "/dev/i2c-1".to_string());
+    let mut i2c = I2cdev::new(&path).unwrap();
+
+    for entry in scan(&mut i2c) {
+        match entry.probe {
+            Probe::Empty => {},
+            Probe::Fram(id) => println!("0x{:02X}: FRAM, {} bytes", entry.address, id.size()),
+            Probe::Unknown => println!("0x{:02X}: something answered but it failed the FRAM device-ID check", entry.address),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use linux_embedded_hal::I2cdev;
+use mb85rc::{Builder, RegionTable};
+
+fn main() {
+    // usage: regions [bus] [table address] [max entries]
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "/dev/i2c-1".to_string());
+    let addr = args.next().map(|a| a.parse().unwrap()).unwrap_or(0);
+    let max_entries = args.next().map(|n| n.parse().unwrap()).unwrap_or(16);
+
+    let i2c = I2cdev::new(&path).unwrap();
+    let mut fram = Builder::new().try_connect_i2c(i2c).unwrap();
+    let table = RegionTable::new(addr, max_entries);
+
+    let count = table.len(&mut fram).unwrap();
+    for index in 0..count {
+        let region = table.get(&mut fram, index).unwrap();
+        println!("{:<16} 0x{:05X}..0x{:05X} ({} bytes) flags 0x{:X}",
+            region.name(), region.offset, region.offset + region.len, region.len, region.flags);
+    }
+}
@@ -0,0 +1,34 @@
+#ifndef MB85RC_H
+#define MB85RC_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* error codes returned by mb85rc_read / mb85rc_write */
+#define MB85RC_OK      0
+#define MB85RC_EINVAL (-1) /* bad argument */
+#define MB85RC_ERANGE (-2) /* access past the end of device memory */
+#define MB85RC_EIO    (-3) /* I2C error */
+
+typedef struct Mb85rcHandle mb85rc_t;
+
+/* open the FRAM at `address` on the i2c-dev bus at `path`; size 0 = auto-detect. NULL on failure */
+mb85rc_t *mb85rc_open(const char *path, uint8_t address, uint32_t size);
+
+int mb85rc_read(mb85rc_t *handle, uint32_t addr, uint8_t *buf, size_t len);
+int mb85rc_write(mb85rc_t *handle, uint32_t addr, const uint8_t *buf, size_t len);
+
+/* device size in bytes */
+uint32_t mb85rc_size(const mb85rc_t *handle);
+
+void mb85rc_close(mb85rc_t *handle);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MB85RC_H */
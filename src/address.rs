@@ -0,0 +1,71 @@
+use core::fmt;
+
+/// A byte position inside an FRAM device
+///
+/// Addresses remember the capacity of the device they were made for, so the
+/// arithmetic methods can refuse to wrap around or walk off the end of memory.
+/// Like a slice index, an address may point one past the last byte (the end of
+/// the device); accesses starting there must have zero length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FramAddress {
+    addr: u32,
+    capacity: u32,
+}
+
+impl FramAddress {
+    /// Create an address, or `None` if `addr` lies past the end of a device of `capacity` bytes
+    pub fn new(addr: u32, capacity: u32) -> Option<Self> {
+        if addr <= capacity {
+            Some(Self { addr, capacity })
+        } else {
+            None
+        }
+    }
+
+    /// The address one past the last byte of a device of `capacity` bytes
+    pub fn end(capacity: u32) -> Self {
+        Self { addr: capacity, capacity }
+    }
+
+    /// The raw byte offset from the start of the device
+    pub fn get(self) -> u32 {
+        self.addr
+    }
+
+    /// Size in bytes of the device this address belongs to
+    pub fn capacity(self) -> u32 {
+        self.capacity
+    }
+
+    /// Number of bytes between this address and the end of the device
+    pub fn remaining(self) -> u32 {
+        self.capacity - self.addr
+    }
+
+    /// Whether `len` bytes starting at this address fit inside the device
+    pub fn fits(self, len: usize) -> bool {
+        len as u64 <= self.remaining() as u64
+    }
+
+    /// Move forward by `count` bytes, or `None` if that would pass the end of the device
+    pub fn checked_add(self, count: u32) -> Option<Self> {
+        Self::new(self.addr.checked_add(count)?, self.capacity)
+    }
+
+    /// Move back by `count` bytes, or `None` if that would go before the start of the device
+    pub fn checked_sub(self, count: u32) -> Option<Self> {
+        Self::new(self.addr.checked_sub(count)?, self.capacity)
+    }
+
+    /// Move by a signed number of bytes, or `None` if that would leave the device
+    pub fn checked_offset(self, offset: i64) -> Option<Self> {
+        let target = (self.addr as i64).checked_add(offset)?;
+        Self::new(u32::try_from(target).ok()?, self.capacity)
+    }
+}
+
+impl fmt::Display for FramAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:04X}", self.addr)
+    }
+}
@@ -0,0 +1,104 @@
+/// Most memory-address bytes an [`Addressing`] ever sends
+pub const MAX_ADDRESS_BYTES: usize = 4;
+
+/// How a memory address is put on the wire
+///
+/// Most parts take a two-byte address after the slave address, but the
+/// smallest take one byte and the largest carry the top address bit in the
+/// slave address, so one chip answers at two slave addresses. Implement this
+/// to support another family member or a compatible part, along with
+/// [`SingleAddress`] or [`Banked`] to say which builder method takes it.
+pub trait Addressing: Sync {
+    /// The slave address for a transaction starting at `addr`
+    fn slave_address(&self, device_addr: u8, addr: u32) -> u8 {
+        let _ = addr;
+        device_addr
+    }
+
+    /// Write the memory address bytes for `addr` into `out`, returning how many there are
+    fn address_bytes(&self, addr: u32, out: &mut [u8; MAX_ADDRESS_BYTES]) -> usize;
+
+    /// How many bytes from `addr` one transaction can cover before the framing changes
+    fn span(&self, addr: u32) -> u32 {
+        let _ = addr;
+        u32::MAX
+    }
+}
+
+/// Addressing that keeps the whole part at one slave address
+///
+/// Pass it to [`Builder::with_addressing`](crate::Builder::with_addressing).
+pub trait SingleAddress: Addressing {}
+
+/// Addressing that spreads the part over several slave addresses
+///
+/// How many slave addresses the part takes up depends on its size, so
+/// [`Builder::with_banked_addressing`](crate::Builder::with_banked_addressing)
+/// makes the size a required step rather than trusting detection.
+pub trait Banked: Addressing {}
+
+/// One address byte, for parts of up to 256 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OneByte;
+
+impl SingleAddress for OneByte {}
+
+impl Addressing for OneByte {
+    fn address_bytes(&self, addr: u32, out: &mut [u8; MAX_ADDRESS_BYTES]) -> usize {
+        out[0] = addr as u8;
+        1
+    }
+}
+
+/// Two address bytes, high first, for parts of up to 64 KiB; the default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TwoByte;
+
+impl SingleAddress for TwoByte {}
+
+impl Addressing for TwoByte {
+    fn address_bytes(&self, addr: u32, out: &mut [u8; MAX_ADDRESS_BYTES]) -> usize {
+        out[..2].copy_from_slice(&(addr as u16).to_be_bytes());
+        2
+    }
+}
+
+/// One address byte with the bits above it in the slave address, as on the MB85RC04V
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OneByteBanked;
+
+impl Banked for OneByteBanked {}
+
+impl Addressing for OneByteBanked {
+    fn slave_address(&self, device_addr: u8, addr: u32) -> u8 {
+        device_addr | (addr >> 8) as u8
+    }
+
+    fn address_bytes(&self, addr: u32, out: &mut [u8; MAX_ADDRESS_BYTES]) -> usize {
+        OneByte.address_bytes(addr, out)
+    }
+
+    fn span(&self, addr: u32) -> u32 {
+        0x100 - (addr & 0xFF)
+    }
+}
+
+/// Two address bytes with the bits above them in the slave address, as on the MB85RC1MT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TwoByteBanked;
+
+impl Banked for TwoByteBanked {}
+
+impl Addressing for TwoByteBanked {
+    fn slave_address(&self, device_addr: u8, addr: u32) -> u8 {
+        device_addr | (addr >> 16) as u8
+    }
+
+    fn address_bytes(&self, addr: u32, out: &mut [u8; MAX_ADDRESS_BYTES]) -> usize {
+        TwoByte.address_bytes(addr, out)
+    }
+
+    fn span(&self, addr: u32) -> u32 {
+        0x10000 - (addr & 0xFFFF)
+    }
+}
@@ -0,0 +1,310 @@
+use core::ops::Range;
+
+use crate::{Clock, FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::crc32;
+
+const ENTRY: u32 = 24;
+
+/// One write as recorded by an [`AuditLog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditRecord {
+    /// Sequence number, counting every write recorded since the log was cleared
+    pub seq: u32,
+    /// Where the write started
+    pub addr: u32,
+    /// How many bytes were written
+    pub len: u32,
+    /// When it happened, in the clock's ticks
+    pub time: u32,
+    /// The tag the caller attached to it
+    pub tag: u32,
+}
+
+impl AuditRecord {
+    fn encode(&self) -> [u8; ENTRY as usize] {
+        let mut buf = [0u8; ENTRY as usize];
+        for (i, v) in [self.seq, self.addr, self.len, self.time, self.tag].into_iter().enumerate() {
+            buf[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        let crc = crc32(&buf[..20]);
+        buf[20..].copy_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    pub(crate) fn decode(buf: &[u8; ENTRY as usize]) -> Option<Self> {
+        let word = |i: usize| u32::from_le_bytes(buf[i * 4..i * 4 + 4].try_into().unwrap());
+        match crc32(&buf[..20]) == word(5) {
+            true => Some(Self { seq: word(0), addr: word(1), len: word(2), time: word(3), tag: word(4) }),
+            false => None,
+        }
+    }
+}
+
+/// Ring of fixed-size, CRC-checked records of writes, kept in a dedicated region
+///
+/// Each record holds a write's address, length, timestamp and a tag chosen
+/// by the caller (a user ID, a command code, whatever the product needs to
+/// show an auditor). Once the region is full the oldest record is
+/// overwritten; records go round the region in turn, and
+/// [`load`](AuditLog::load) finds the newest by sequence number, so
+/// sequence numbers can wrap past `u32::MAX`. Put an [`Audited`] around the
+/// device to have every write recorded, or call [`record`](Self::record)
+/// directly. The device is passed to each call that touches it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLog {
+    region: Range<u32>,
+    next: u32,
+    count: u32,
+    slot: u32,
+}
+
+impl AuditLog {
+    /// Bytes of FRAM each record takes up
+    pub const RECORD_SIZE: u32 = ENTRY;
+
+    /// Find the records already stored in `region`
+    pub fn load<D: FramDevice + ?Sized>(dev: &mut D, region: Range<u32>) -> Result<Self, Mb85rcError> {
+        if region.start > region.end || region.end > dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        let mut log = Self { region, next: 0, count: 0, slot: 0 };
+        let capacity = log.capacity();
+        if capacity == 0 {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+
+        let mut newest: Option<(u32, u32)> = None;
+        for slot in 0..capacity {
+            if let Some(record) = log.read_slot(dev, slot)? {
+                if newest.is_none_or(|(_, seq)| (record.seq.wrapping_sub(seq) as i32) > 0) {
+                    newest = Some((slot, record.seq));
+                }
+            }
+        }
+        let Some((slot, seq)) = newest else {
+            return Ok(log);
+        };
+
+        // the log is the unbroken run of records leading up to the newest
+        let mut count = 1;
+        while count < capacity {
+            match log.read_slot(dev, (slot + capacity - count) % capacity)? {
+                Some(record) if record.seq == seq.wrapping_sub(count) => count += 1,
+                _ => break,
+            }
+        }
+        log.next = seq.wrapping_add(1);
+        log.count = count;
+        log.slot = (slot + 1) % capacity;
+        Ok(log)
+    }
+
+    /// The region the log is kept in
+    pub fn region(&self) -> Range<u32> {
+        self.region.clone()
+    }
+
+    /// How many records fit before the oldest start being overwritten
+    pub fn capacity(&self) -> u32 {
+        (self.region.end - self.region.start) / ENTRY
+    }
+
+    /// How many records are stored
+    pub fn len(&self) -> u32 {
+        self.count
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Sequence numbers of the stored records, oldest first
+    pub fn range(&self) -> Range<u32> {
+        self.next.wrapping_sub(self.count)..self.next
+    }
+
+    /// Append a record of a write of `len` bytes at `addr`, returning its sequence number
+    pub fn record<D: FramDevice + ?Sized>(&mut self, dev: &mut D, addr: u32, len: u32, time: u32, tag: u32) -> Result<u32, Mb85rcError> {
+        let seq = self.next;
+        let record = AuditRecord { seq, addr, len, time, tag };
+        dev.write_at(self.slot_addr(self.slot), &record.encode())?;
+        self.next = seq.wrapping_add(1);
+        self.slot = (self.slot + 1) % self.capacity();
+        self.count = (self.count + 1).min(self.capacity());
+        Ok(seq)
+    }
+
+    /// The `n`th stored record, oldest first
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if the record is corrupt.
+    pub fn get<D: FramDevice + ?Sized>(&self, dev: &mut D, n: u32) -> Result<Option<AuditRecord>, Mb85rcError> {
+        if n >= self.count {
+            return Ok(None);
+        }
+        let seq = self.next.wrapping_sub(self.count - n);
+        match self.read_slot(dev, self.slot_of(seq))? {
+            Some(record) if record.seq == seq => Ok(Some(record)),
+            _ => Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        }
+    }
+
+    /// Read the stored records with sequence numbers in `seqs` into `out`, returning which were read
+    ///
+    /// A record's slot follows from its sequence number, so this goes
+    /// straight to the first one asked for: a sync protocol can ask for
+    /// everything since the last sequence it saw with `last + 1..u32::MAX`
+    /// without replaying the whole log. Records already overwritten are
+    /// skipped, and reading stops when `out` is full; ask again from the end
+    /// of the returned range for the rest. Fails with
+    /// [`ErrorKind::InvalidData`] if a record is corrupt.
+    pub fn read_range<D: FramDevice + ?Sized>(&self, dev: &mut D, seqs: Range<u32>, out: &mut [AuditRecord]) -> Result<Range<u32>, Mb85rcError> {
+        let stored = self.range();
+        let start = seqs.start.max(stored.start).min(stored.end);
+        let end = seqs.end.max(start).min(stored.end).min(start.saturating_add(out.len() as u32));
+
+        for (record, seq) in out.iter_mut().zip(start..end) {
+            *record = match self.read_slot(dev, self.slot_of(seq))? {
+                Some(found) if found.seq == seq => found,
+                _ => return Err(Mb85rcError::new(ErrorKind::InvalidData)),
+            };
+        }
+        Ok(start..end)
+    }
+
+    /// The most recent record
+    pub fn latest<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<AuditRecord>, Mb85rcError> {
+        match self.count {
+            0 => Ok(None),
+            n => self.get(dev, n - 1),
+        }
+    }
+
+    /// Erase every record
+    pub fn clear<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        let zeros = [0u8; ENTRY as usize];
+        for slot in 0..self.capacity() {
+            dev.write_at(self.slot_addr(slot), &zeros)?;
+        }
+        self.next = 0;
+        self.count = 0;
+        self.slot = 0;
+        Ok(())
+    }
+
+    /// Slot stored record `seq` is in, counting back from the next slot to write
+    fn slot_of(&self, seq: u32) -> u32 {
+        let capacity = self.capacity() as u64;
+        ((self.slot as u64 + capacity - self.next.wrapping_sub(seq) as u64) % capacity) as u32
+    }
+
+    /// Where stored record `seq` is
+    #[cfg(feature = "embassy")]
+    pub(crate) fn record_addr(&self, seq: u32) -> u32 {
+        self.slot_addr(self.slot_of(seq))
+    }
+
+    fn slot_addr(&self, slot: u32) -> u32 {
+        self.region.start + slot * ENTRY
+    }
+
+    fn read_slot<D: FramDevice + ?Sized>(&self, dev: &mut D, slot: u32) -> Result<Option<AuditRecord>, Mb85rcError> {
+        let mut buf = [0u8; ENTRY as usize];
+        dev.read_at(self.slot_addr(slot), &mut buf)?;
+        Ok(AuditRecord::decode(&buf))
+    }
+}
+
+/// Device wrapper that records every write in an [`AuditLog`]
+///
+/// `clock` gives the timestamp (any [`Clock`], a closure will do) and
+/// `tag` picks the tag for each write from its address and data, e.g.
+/// `|_, _| current_user()`. The record goes in
+/// before the data does, so nothing can change without leaving a trace
+/// (though a write that then fails is still recorded). Writes into the
+/// log's own region are refused with [`ErrorKind::Overlap`].
+pub struct Audited<D: FramDevice, C: Clock, T: FnMut(u32, &[u8]) -> u32> {
+    dev: D,
+    log: AuditLog,
+    clock: C,
+    tag: T,
+}
+
+impl<D: FramDevice, C: Clock, T: FnMut(u32, &[u8]) -> u32> Audited<D, C, T> {
+    /// Record writes to `dev` in `log`
+    pub fn new(dev: D, log: AuditLog, clock: C, tag: T) -> Self {
+        Self { dev, log, clock, tag }
+    }
+
+    /// The log
+    pub fn log(&self) -> &AuditLog {
+        &self.log
+    }
+
+    /// The `n`th stored record, oldest first
+    pub fn record(&mut self, n: u32) -> Result<Option<AuditRecord>, Mb85rcError> {
+        self.log.get(&mut self.dev, n)
+    }
+
+    /// Give back the device and the log
+    pub fn into_inner(self) -> (D, AuditLog) {
+        (self.dev, self.log)
+    }
+}
+
+impl<D: FramDevice, C: Clock, T: FnMut(u32, &[u8]) -> u32> FramDevice for Audited<D, C, T> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.dev.read_at(addr, buf)
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        let end = addr as u64 + buf.len() as u64;
+        if end > self.dev.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        if !buf.is_empty() && (addr as u64) < self.log.region.end as u64 && end > self.log.region.start as u64 {
+            return Err(Mb85rcError::new(ErrorKind::Overlap));
+        }
+
+        let time = self.clock.now();
+        let tag = (self.tag)(addr, buf);
+        self.log.record(&mut self.dev, addr, buf.len() as u32, time, tag)?;
+        self.dev.write_at(addr, buf)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{AuditLog, ENTRY};
+    use crate::{Builder, SimFram};
+
+    #[test]
+    fn sequence_numbers_wrap() {
+        let mut fram = Builder::new().with_size(1024).connect_i2c(SimFram::new(1024));
+        let region = 0..3 * ENTRY;
+
+        // two records just short of the wrap: the count comes from what's stored
+        let mut log = AuditLog::load(&mut fram, region.clone()).unwrap();
+        log.next = u32::MAX - 1;
+        log.record(&mut fram, 0, 1, 0, 0).unwrap();
+        log.record(&mut fram, 0, 1, 0, 0).unwrap();
+        let log = AuditLog::load(&mut fram, region.clone()).unwrap();
+        assert_eq!((log.len(), log.next), (2, 0));
+        assert_eq!(log.latest(&mut fram).unwrap().unwrap().seq, u32::MAX);
+
+        // 3 doesn't divide 2^32, so `seq % capacity` would put u32::MAX and 0 in one slot
+        let mut log = log;
+        for addr in 0..2 {
+            log.record(&mut fram, addr, 1, 0, 0).unwrap();
+        }
+        let log = AuditLog::load(&mut fram, region).unwrap();
+        assert_eq!(log.len(), 3);
+        let seqs: Vec<u32> = (0..3).map(|n| log.get(&mut fram, n).unwrap().unwrap().seq).collect();
+        assert_eq!(seqs, [u32::MAX, 0, 1]);
+    }
+}
@@ -0,0 +1,96 @@
+use core::ops::Range;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Bytes of HMAC-SHA256 tag stored at the end of the region
+const TAG: u32 = 32;
+
+/// A region whose contents carry an HMAC-SHA256 tag
+///
+/// The last 32 bytes of the region hold a tag over the region's start
+/// address and the rest of its contents, computed with a key the caller
+/// supplies. Reads check the tag first, so edits made with a programmer on a
+/// socketed or exposed chip are caught even though the data isn't
+/// encrypted. Including the address means a valid region copied somewhere
+/// else doesn't verify either. The handle only records where the region
+/// is; pass the device and key to each call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthRegion {
+    region: Range<u32>,
+}
+
+impl AuthRegion {
+    /// Authenticate `region`, which must be longer than the 32-byte tag
+    pub fn new(region: Range<u32>) -> Result<Self, Mb85rcError> {
+        if region.end < region.start || region.end - region.start <= TAG {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok(Self { region })
+    }
+
+    /// Bytes of data the region holds, not counting the tag
+    pub fn capacity(&self) -> u32 {
+        self.region.end - self.region.start - TAG
+    }
+
+    fn tag_addr(&self) -> u32 {
+        self.region.end - TAG
+    }
+
+    fn check_access(&self, offset: u32, len: usize) -> Result<(), Mb85rcError> {
+        match offset as u64 + len as u64 <= self.capacity() as u64 {
+            true => Ok(()),
+            false => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    fn compute<D: FramDevice + ?Sized>(&self, dev: &mut D, key: &[u8]) -> Result<Hmac<Sha256>, Mb85rcError> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+        mac.update(&self.region.start.to_le_bytes());
+
+        let mut buf = [0u8; 64];
+        let mut done = 0;
+        while done < self.capacity() {
+            let n = (self.capacity() - done).min(buf.len() as u32) as usize;
+            dev.read_at(self.region.start + done, &mut buf[..n])?;
+            mac.update(&buf[..n]);
+            done += n as u32;
+        }
+        Ok(mac)
+    }
+
+    /// Check the stored tag against the contents
+    pub fn verify<D: FramDevice + ?Sized>(&self, dev: &mut D, key: &[u8]) -> Result<bool, Mb85rcError> {
+        let mut tag = [0u8; TAG as usize];
+        dev.read_at(self.tag_addr(), &mut tag)?;
+        Ok(self.compute(dev, key)?.verify_slice(&tag).is_ok())
+    }
+
+    /// Recompute and store the tag for the current contents
+    pub fn seal<D: FramDevice + ?Sized>(&self, dev: &mut D, key: &[u8]) -> Result<(), Mb85rcError> {
+        let tag = self.compute(dev, key)?.finalize().into_bytes();
+        dev.write_at(self.tag_addr(), &tag)
+    }
+
+    /// Read `buf.len()` bytes at `offset` into the region
+    ///
+    /// Fails with [`ErrorKind::AuthFailed`] if the region doesn't verify.
+    pub fn read<D: FramDevice + ?Sized>(&self, dev: &mut D, key: &[u8], offset: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.check_access(offset, buf.len())?;
+        if !self.verify(dev, key)? {
+            return Err(Mb85rcError::new(ErrorKind::AuthFailed));
+        }
+        dev.read_at(self.region.start + offset, buf)
+    }
+
+    /// Write `data` at `offset` into the region and update the tag
+    ///
+    /// A reset between the two leaves the region failing verification.
+    pub fn write<D: FramDevice + ?Sized>(&self, dev: &mut D, key: &[u8], offset: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        self.check_access(offset, data.len())?;
+        dev.write_at(self.region.start + offset, data)?;
+        self.seal(dev, key)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Clock, FramDevice, Codec, ConfigHistory, Mb85rcError};
+
+/// Keeps a value in RAM and saves it to a [`ConfigHistory`] now and then
+///
+/// Changes made through [`get_mut`](Self::get_mut) or [`set`](Self::set)
+/// mark the value dirty. [`poll`](Self::poll), called from the main loop
+/// with the current time, saves a dirty value once `interval` ticks have
+/// passed since the last save or `max_changes` changes have piled up,
+/// whichever comes first. That bounds both the write rate and how much can
+/// be lost at a reset. `N` is the size of the buffer the value is encoded
+/// into.
+pub struct Autosave<T, C: Codec<T>, D: FramDevice, const N: usize> {
+    history: ConfigHistory<D>,
+    codec: C,
+    value: T,
+    dirty: bool,
+    changes: u32,
+    last_save: u32,
+    interval: u32,
+    max_changes: u32,
+}
+
+impl<T, C: Codec<T>, D: FramDevice, const N: usize> Autosave<T, C, D, N> {
+    /// Load the newest saved value from `history`, or start from `default` if there isn't one
+    ///
+    /// `now` counts as the time of the last save. Saves happen on every
+    /// change until [`with_interval`](Self::with_interval) or
+    /// [`with_max_changes`](Self::with_max_changes) say otherwise.
+    pub fn load(mut history: ConfigHistory<D>, codec: C, default: T, now: u32) -> Result<Self, Mb85rcError> {
+        let mut scratch = [0u8; N];
+        let (value, dirty) = match history.load_latest_value(&codec, &mut scratch)? {
+            Some(value) => (value, false),
+            None => (default, true),
+        };
+        Ok(Self { history, codec, value, dirty, changes: 0, last_save: now, interval: 0, max_changes: 1 })
+    }
+
+    /// Wait at least `ticks` between saves
+    pub fn with_interval(mut self, ticks: u32) -> Self {
+        self.interval = ticks;
+        self
+    }
+
+    /// Save regardless of the interval once `changes` changes are waiting, 0 for never
+    pub fn with_max_changes(mut self, changes: u32) -> Self {
+        self.max_changes = changes;
+        self
+    }
+
+    /// The working copy
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// The working copy, counting as one change
+    pub fn get_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        self.changes = self.changes.saturating_add(1);
+        &mut self.value
+    }
+
+    /// Replace the working copy, counting as one change
+    pub fn set(&mut self, value: T) {
+        *self.get_mut() = value;
+    }
+
+    /// Whether there are changes that haven't been saved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Save the value if it's dirty and due, returning whether it was saved
+    pub fn poll(&mut self, now: u32) -> Result<bool, Mb85rcError> {
+        if !self.dirty {
+            return Ok(false);
+        }
+        let elapsed = now.wrapping_sub(self.last_save) >= self.interval;
+        let busy = self.max_changes != 0 && self.changes >= self.max_changes;
+        if !elapsed && !busy {
+            return Ok(false);
+        }
+        self.save(now)?;
+        Ok(true)
+    }
+
+    /// [`poll`](Self::poll) with the time from `clock`
+    pub fn poll_with<K: Clock + ?Sized>(&mut self, clock: &mut K) -> Result<bool, Mb85rcError> {
+        self.poll(clock.now())
+    }
+
+    /// Save the value now, dirty or not
+    pub fn save(&mut self, now: u32) -> Result<(), Mb85rcError> {
+        let mut scratch = [0u8; N];
+        self.history.save_value(&self.codec, &self.value, &mut scratch, now)?;
+        self.dirty = false;
+        self.changes = 0;
+        self.last_save = now;
+        Ok(())
+    }
+
+    /// Save any pending changes and give back the history and the value
+    pub fn into_inner(mut self, now: u32) -> Result<(ConfigHistory<D>, T), Mb85rcError> {
+        if self.dirty {
+            self.save(now)?;
+        }
+        Ok((self.history, self.value))
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{FramDevice, Mb85rcError};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Keeps timestamped dumps of a device in a directory on the host
+///
+/// Each [`backup`](Self::backup) reads the whole device into a file named
+/// like `fram-20240131T235959Z.bin` (UTC), written to a temporary name first
+/// and renamed into place so a crash never leaves a half-written dump behind.
+/// Old dumps are then pruned: the newest [`keep_last`](Self::keep_last)
+/// are always kept, plus the newest dump from each of the last
+/// [`keep_daily`](Self::keep_daily) days that have one. Files that don't
+/// match the naming scheme are left alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupManager {
+    dir: PathBuf,
+    prefix: String,
+    keep_last: usize,
+    keep_daily: usize,
+}
+
+impl BackupManager {
+    /// Keep dumps in `dir`, which is created on the first backup if needed
+    ///
+    /// By default the last 10 dumps and one a day for 7 days are kept.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into(), prefix: "fram".into(), keep_last: 10, keep_daily: 7 }
+    }
+
+    /// Start file names with `prefix` instead of `fram`, to keep several devices in one directory
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Always keep the newest `n` dumps
+    pub fn keep_last(mut self, n: usize) -> Self {
+        self.keep_last = n;
+        self
+    }
+
+    /// Also keep the newest dump from each of the last `days` days that have one
+    pub fn keep_daily(mut self, days: usize) -> Self {
+        self.keep_daily = days;
+        self
+    }
+
+    /// The directory dumps are kept in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Dump `dev` timestamped with the current time, prune old dumps and return the new file's path
+    pub fn backup<D: FramDevice + ?Sized>(&self, dev: &mut D) -> io::Result<PathBuf> {
+        self.backup_at(dev, SystemTime::now())
+    }
+
+    /// Dump `dev` timestamped with `time`, prune old dumps and return the new file's path
+    pub fn backup_at<D: FramDevice + ?Sized>(&self, dev: &mut D, time: SystemTime) -> io::Result<PathBuf> {
+        let mut image = vec![0u8; dev.capacity() as usize];
+        dev.read_at(0, &mut image).map_err(io::Error::other)?;
+
+        fs::create_dir_all(&self.dir)?;
+        let secs = time.duration_since(UNIX_EPOCH).map_err(io::Error::other)?.as_secs();
+        let path = self.dir.join(format!("{}-{}.bin", self.prefix, format_timestamp(secs)));
+        let tmp = path.with_extension("bin.tmp");
+        fs::write(&tmp, &image)?;
+        fs::rename(&tmp, &path)?;
+
+        self.prune()?;
+        Ok(path)
+    }
+
+    /// Every dump in the directory with its timestamp, oldest first
+    pub fn backups(&self) -> io::Result<Vec<(SystemTime, PathBuf)>> {
+        let mut found = Vec::new();
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(found),
+            Err(e) => return Err(e),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            let secs = path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(self.prefix.as_str()))
+                .and_then(|rest| rest.strip_prefix('-'))
+                .and_then(|rest| rest.strip_suffix(".bin"))
+                .and_then(parse_timestamp);
+            if let Some(secs) = secs {
+                found.push((UNIX_EPOCH + Duration::from_secs(secs), path));
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+
+    /// Delete dumps the retention rules don't cover, returning their paths
+    pub fn prune(&self) -> io::Result<Vec<PathBuf>> {
+        let mut backups = self.backups()?;
+        backups.reverse();
+
+        let mut days_kept = Vec::new();
+        let mut removed = Vec::new();
+        for (i, (time, path)) in backups.into_iter().enumerate() {
+            let day = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / SECS_PER_DAY;
+            let daily = days_kept.len() < self.keep_daily && !days_kept.contains(&day);
+            if daily {
+                days_kept.push(day);
+            }
+            if i >= self.keep_last && !daily {
+                fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Dump several devices at once, one thread each, and join the dumps into one image
+///
+/// Meant for banks of chips on separate buses (for example one Linux
+/// `/dev/i2c-N` adapter per chip), where reading them in parallel cuts the
+/// time roughly by the number of buses. Device `i`'s contents start right
+/// after device `i - 1`'s in the returned image. Devices sharing one bus gain
+/// nothing from this, since their transactions still take turns.
+pub fn dump_parallel<D: FramDevice + Send>(devices: &mut [D]) -> Result<Vec<u8>, Mb85rcError> {
+    let mut image = vec![0u8; devices.iter().map(|d| d.capacity() as usize).sum()];
+    thread::scope(|scope| {
+        let mut rest = image.as_mut_slice();
+        let workers: Vec<_> = devices.iter_mut().map(|dev| {
+            let (part, tail) = std::mem::take(&mut rest).split_at_mut(dev.capacity() as usize);
+            rest = tail;
+            scope.spawn(move || dev.read_at(0, part))
+        }).collect();
+
+        workers.into_iter()
+            .try_for_each(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+    })?;
+    Ok(image)
+}
+
+/// Civil date for a count of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + (month <= 2) as i64, month, day)
+}
+
+/// Days since 1970-01-01 for a civil date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// `YYYYMMDDTHHMMSSZ` for seconds since the Unix epoch
+fn format_timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / SECS_PER_DAY) as i64);
+    let time = secs % SECS_PER_DAY;
+    format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+/// Seconds since the Unix epoch for a `YYYYMMDDTHHMMSSZ` timestamp
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = s.split_once('T')?;
+    if date.len() != 8 || time.len() != 6 || !date.bytes().chain(time.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let num = |s: &str| s.parse::<u32>().ok();
+    let (year, month, day) = (num(&date[0..4])?, num(&date[4..6])?, num(&date[6..8])?);
+    let (hour, minute, second) = (num(&time[0..2])?, num(&time[2..4])?, num(&time[4..6])?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year as i64, month, day)).ok()?;
+    Some(days * SECS_PER_DAY + (hour * 3600 + minute * 60 + second) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_timestamp, parse_timestamp};
+
+    #[test]
+    fn timestamp_round_trip() {
+        for secs in [0, 951_782_400, 1_706_745_599, 4_102_444_800, 253_402_300_799] {
+            assert_eq!(parse_timestamp(&format_timestamp(secs)), Some(secs), "{}", format_timestamp(secs));
+        }
+        assert_eq!(format_timestamp(0), "19700101T000000Z");
+        // 2024 is a leap year
+        assert_eq!(format_timestamp(1_709_164_800), "20240229T000000Z");
+        assert_eq!(format_timestamp(1_706_745_599), "20240131T235959Z");
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps() {
+        for s in ["", "20240131T235959", "20240131 235959Z", "2024013T235959Z", "20241301T000000Z", "20240100T000000Z", "20240131T240000Z", "20240131T23595aZ", "1969x231T235959Z"] {
+            assert_eq!(parse_timestamp(s), None, "{}", s);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{FramDevice, BufferedFram, Mb85rcError, ErrorKind};
+
+/// Size of the header kept in front of each recorded write
+const HEADER: usize = 6;
+
+/// A set of writes to scattered addresses, applied together
+///
+/// Writes are recorded in an `N`-byte buffer (each one costs its length plus
+/// six bytes of bookkeeping) and nothing reaches the device until
+/// [`commit`](Self::commit). Commit sends them in address order with adjacent
+/// writes merged into single transactions. If recorded writes overlap, they
+/// are sent in the order they were made instead, so later writes still win.
+pub struct Batch<'a, D: FramDevice + ?Sized, const N: usize> {
+    dev: &'a mut D,
+    buf: [u8; N],
+    used: usize,
+    count: usize,
+}
+
+impl<'a, D: FramDevice + ?Sized, const N: usize> Batch<'a, D, N> {
+    /// Start an empty batch for `dev`
+    pub fn new(dev: &'a mut D) -> Self {
+        Self { dev, buf: [0u8; N], used: 0, count: 0 }
+    }
+
+    /// Record a write of `data` at `addr`
+    ///
+    /// Fails with [`ErrorKind::BatchFull`] if the batch has no room left
+    pub fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        if addr as u64 + data.len() as u64 > self.dev.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        if data.len() > u16::MAX as usize || HEADER + data.len() > N - self.used {
+            return Err(Mb85rcError::new(ErrorKind::BatchFull));
+        }
+
+        let entry = &mut self.buf[self.used..self.used + HEADER + data.len()];
+        entry[..4].copy_from_slice(&addr.to_le_bytes());
+        entry[4..6].copy_from_slice(&(data.len() as u16).to_le_bytes());
+        entry[HEADER..].copy_from_slice(data);
+        self.used += entry.len();
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of writes recorded
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether no writes have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Apply every recorded write
+    pub fn commit(self) -> Result<(), Mb85rcError> {
+        apply(self.dev, &self.buf[..self.used])
+    }
+
+    /// Number of bytes of undo space [`commit_with_rollback`](Self::commit_with_rollback) needs
+    pub fn undo_size(&self) -> usize {
+        self.used - HEADER * self.count
+    }
+
+    /// Apply every recorded write, putting the old contents back if any write fails
+    ///
+    /// The current contents of every range the batch touches are first read
+    /// into `undo`, which must hold at least [`undo_size`](Self::undo_size)
+    /// bytes. If the commit then fails part way, the old contents are written
+    /// back and [`ErrorKind::RolledBack`] is returned. If restoring fails too,
+    /// the error is [`ErrorKind::RollbackFailed`] and the touched ranges are
+    /// in an unknown state.
+    ///
+    /// The undo copy lives in RAM, so this covers bus errors, not power loss.
+    pub fn commit_with_rollback(self, undo: &mut [u8]) -> Result<(), Mb85rcError> {
+        let log = &self.buf[..self.used];
+        if undo.len() < self.undo_size() {
+            return Err(Mb85rcError::new(ErrorKind::BufferTooSmall));
+        }
+
+        let mut pos = 0;
+        for (_, addr, data) in entries(log) {
+            self.dev.read_at(addr, &mut undo[pos..pos + data.len()])?;
+            pos += data.len();
+        }
+
+        if apply(self.dev, log).is_ok() {
+            return Ok(());
+        }
+
+        // restore newest first, so where writes overlap the oldest contents end up on top
+        let mut end = pos;
+        let mut failed = false;
+        for i in (0..self.count).rev() {
+            let (_, addr, data) = entries(log).nth(i).unwrap();
+            let start = end - data.len();
+            failed |= self.dev.write_at(addr, &undo[start..end]).is_err();
+            end = start;
+        }
+
+        match failed {
+            false => Err(Mb85rcError::new(ErrorKind::RolledBack)),
+            true => Err(Mb85rcError::new(ErrorKind::RollbackFailed)),
+        }
+    }
+
+    /// Throw the recorded writes away
+    pub fn discard(self) {}
+}
+
+/// Iterate over the writes recorded in `log` as `(start of entry, addr, data)`, in recording order
+fn entries(log: &[u8]) -> impl Iterator<Item = (usize, u32, &[u8])> {
+    let mut pos = 0;
+    core::iter::from_fn(move || {
+        if pos >= log.len() {
+            return None;
+        }
+        let addr = u32::from_le_bytes(log[pos..pos + 4].try_into().unwrap());
+        let len = u16::from_le_bytes(log[pos + 4..pos + 6].try_into().unwrap()) as usize;
+        let entry = (pos, addr, &log[pos + HEADER..pos + HEADER + len]);
+        pos += HEADER + len;
+        Some(entry)
+    })
+}
+
+fn overlapping(log: &[u8]) -> bool {
+    entries(log).any(|(pa, a, da)| {
+        entries(log).any(|(pb, b, db)| {
+            pa != pb && (a as u64) < b as u64 + db.len() as u64 && (b as u64) < a as u64 + da.len() as u64
+        })
+    })
+}
+
+/// The next entry to send after `prev` (the address and entry start of the last one sent)
+fn next_sorted(log: &[u8], prev: Option<(u32, usize)>) -> Option<(usize, u32, &[u8])> {
+    entries(log)
+        .filter(|&(pos, addr, _)| prev.is_none_or(|p| (addr, pos) > p))
+        .min_by_key(|&(pos, addr, _)| (addr, pos))
+}
+
+/// Send the writes in `log` to `dev`, sorted and merged where that's safe
+fn apply<D: FramDevice + ?Sized>(dev: &mut D, log: &[u8]) -> Result<(), Mb85rcError> {
+    let mut out: BufferedFram<&mut D, 64> = BufferedFram::new(dev);
+
+    if overlapping(log) {
+        for (_, addr, data) in entries(log) {
+            out.write_at(addr, data)?;
+        }
+    } else {
+        let mut prev = None;
+        while let Some((pos, addr, data)) = next_sorted(log, prev) {
+            out.write_at(addr, data)?;
+            prev = Some((addr, pos));
+        }
+    }
+
+    out.flush()
+}
@@ -0,0 +1,119 @@
+//! Emulate an MB85RC as an I2C slave, for hardware-in-the-loop tests without a real chip
+//!
+//! Uses the Linux `i2c-slave-eeprom` backend, which answers reads and writes
+//! with two-byte addressing just like the FRAM does, on adapters that
+//! support slave mode (on a Pi, the BSC slave or `i2c-gpio`; see the
+//! kernel's `Documentation/i2c/slave-interface.rst`). The memory is loaded
+//! from an image file at start and written back to it whenever it changes,
+//! so contents survive restarts like FRAM would.
+//!
+//! The backend can't answer the device-ID command, so the host has to give
+//! the size with `Builder::with_size`. Parts over 64 KiB, which need a
+//! second slave address, aren't supported.
+//!
+//! usage: mb85rc-slave [bus number] [--address 0x50] [--size 32768]
+//!                     [--image mb85rc-slave.bin] [--sync 1] [--seconds N]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct Options {
+    bus: u32,
+    address: u8,
+    size: usize,
+    image: PathBuf,
+    sync: u64,
+    seconds: Option<u64>,
+}
+
+fn parse_number(s: &str) -> u64 {
+    let parsed = match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.unwrap_or_else(|_| panic!("not a number: {}", s))
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        bus: 1,
+        address: 0x50,
+        size: 32 * 1024,
+        image: PathBuf::from("mb85rc-slave.bin"),
+        sync: 1,
+        seconds: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| panic!("{} needs a value", arg));
+        match arg.as_str() {
+            "--address" => options.address = parse_number(&value()) as u8,
+            "--size" => options.size = parse_number(&value()) as usize,
+            "--image" => options.image = PathBuf::from(value()),
+            "--sync" => options.sync = parse_number(&value()).max(1),
+            "--seconds" => options.seconds = Some(parse_number(&value())),
+            _ => options.bus = parse_number(&arg) as u32,
+        }
+    }
+    options
+}
+
+/// The smallest `i2c-slave-eeprom` backend with two-byte addressing that holds `size` bytes
+fn backend(size: usize) -> &'static str {
+    match size {
+        0..=4096 => "slave-24c32",
+        4097..=8192 => "slave-24c64",
+        8193..=65536 => "slave-24c512",
+        _ => panic!("parts over 64 KiB need two slave addresses, which the eeprom backend can't do"),
+    }
+}
+
+/// Write `data` to `path` via a temporary file, so a crash never leaves half an image
+fn save(path: &Path, data: &[u8]) {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).unwrap();
+    fs::rename(&tmp, path).unwrap();
+}
+
+fn main() {
+    let options = parse_options();
+    let adapter = PathBuf::from(format!("/sys/bus/i2c/devices/i2c-{}", options.bus));
+    // 0x1000 marks the address as one we answer on, rather than a device we talk to
+    let slave = 0x1000 | options.address as u16;
+    let device = PathBuf::from(format!("/sys/bus/i2c/devices/{}-{:04x}", options.bus, slave));
+
+    // a previous run that was killed leaves its slave behind
+    if device.exists() {
+        fs::write(adapter.join("delete_device"), format!("0x{:04x}", slave)).unwrap();
+    }
+    fs::write(adapter.join("new_device"), format!("{} 0x{:04x}", backend(options.size), slave))
+        .unwrap_or_else(|e| panic!("couldn't register a slave on i2c-{}: {} (does the adapter support slave mode?)", options.bus, e));
+    let memory = device.join("slave-eeprom");
+
+    let mut image = fs::read(&options.image).unwrap_or_default();
+    image.resize(options.size, 0);
+    fs::write(&memory, &image).unwrap();
+    save(&options.image, &image);
+
+    println!(
+        "emulating a {} byte FRAM at 0x{:02X} on i2c-{}, image {}",
+        options.size, options.address, options.bus, options.image.display(),
+    );
+
+    let started = Instant::now();
+    let mut saved = image;
+    while options.seconds.is_none_or(|s| started.elapsed().as_secs() < s) {
+        thread::sleep(Duration::from_secs(options.sync));
+        let mut current = fs::read(&memory).unwrap();
+        current.truncate(options.size);
+        if current != saved {
+            save(&options.image, &current);
+            saved = current;
+        }
+    }
+
+    fs::write(adapter.join("delete_device"), format!("0x{:04x}", slave)).unwrap();
+}
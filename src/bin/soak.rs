@@ -0,0 +1,154 @@
+//! Long-running soak test for a real FRAM
+//!
+//! Hammers the device with random-length writes at random addresses,
+//! reads every one back to check it, and prints running totals until the
+//! time runs out.
+//!
+//! usage: mb85rc-soak [bus] [--address 0x50] [--seconds 3600] [--seed N]
+//!                    [--max-len 256] [--start 0] [--end <size>] [--report 60]
+
+use std::time::{Duration, Instant};
+
+use linux_embedded_hal::I2cdev;
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+
+use mb85rc::{Builder, FramDevice, Mb85rcError};
+
+struct Options {
+    bus: String,
+    address: u8,
+    seconds: u64,
+    seed: u64,
+    max_len: usize,
+    start: u32,
+    end: Option<u32>,
+    report: u64,
+}
+
+fn parse_number(s: &str) -> u64 {
+    let parsed = match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.unwrap_or_else(|_| panic!("not a number: {}", s))
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        bus: "/dev/i2c-1".to_string(),
+        address: 0x50,
+        seconds: 3600,
+        seed: rand::thread_rng().next_u64(),
+        max_len: 256,
+        start: 0,
+        end: None,
+        report: 60,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || parse_number(&args.next().unwrap_or_else(|| panic!("{} needs a value", arg)));
+        match arg.as_str() {
+            "--address" => options.address = value() as u8,
+            "--seconds" => options.seconds = value(),
+            "--seed" => options.seed = value(),
+            "--max-len" => options.max_len = value().max(1) as usize,
+            "--start" => options.start = value() as u32,
+            "--end" => options.end = Some(value() as u32),
+            "--report" => options.report = value().max(1),
+            _ => options.bus = arg,
+        }
+    }
+    options
+}
+
+#[derive(Default)]
+struct Stats {
+    transfers: u64,
+    bytes: u64,
+    write_errors: u64,
+    read_errors: u64,
+    mismatched_transfers: u64,
+    mismatched_bytes: u64,
+}
+
+impl Stats {
+    fn failures(&self) -> u64 {
+        self.write_errors + self.read_errors + self.mismatched_transfers
+    }
+
+    fn print(&self, elapsed: Duration) {
+        println!(
+            "[{:>7.0}s] {} transfers, {} bytes, {} write errors, {} read errors, {} mismatched transfers ({} bytes)",
+            elapsed.as_secs_f64(), self.transfers, self.bytes, self.write_errors,
+            self.read_errors, self.mismatched_transfers, self.mismatched_bytes,
+        );
+    }
+}
+
+fn report_error(what: &str, addr: u32, len: usize, e: Mb85rcError) {
+    eprintln!("{} of {} bytes at 0x{:05X} failed: {}", what, len, addr, e);
+}
+
+fn main() {
+    let options = parse_options();
+    let i2c = I2cdev::new(&options.bus).unwrap();
+    let mut fram = Builder::new().with_address(options.address).try_connect_i2c(i2c).unwrap();
+
+    let end = options.end.unwrap_or(fram.capacity()).min(fram.capacity());
+    assert!(options.start < end, "empty test region");
+    let max_len = options.max_len.min((end - options.start) as usize);
+
+    println!(
+        "soaking 0x{:05X}..0x{:05X} of a {} byte FRAM for {}s, seed {}",
+        options.start, end, fram.capacity(), options.seconds, options.seed,
+    );
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut stats = Stats::default();
+    let mut written = vec![0u8; max_len];
+    let mut read = vec![0u8; max_len];
+
+    let began = Instant::now();
+    let mut last_report = began;
+    let duration = Duration::from_secs(options.seconds);
+
+    while began.elapsed() < duration {
+        let len = rng.gen_range(1..=max_len);
+        let addr = rng.gen_range(options.start..=end - len as u32);
+        let (written, read) = (&mut written[..len], &mut read[..len]);
+        rng.fill_bytes(written);
+
+        stats.transfers += 1;
+        stats.bytes += len as u64;
+        if let Err(e) = fram.write_at(addr, written) {
+            stats.write_errors += 1;
+            report_error("write", addr, len, e);
+            continue;
+        }
+        if let Err(e) = fram.read_at(addr, read) {
+            stats.read_errors += 1;
+            report_error("read", addr, len, e);
+            continue;
+        }
+
+        let bad = written.iter().zip(read.iter()).filter(|(w, r)| w != r).count();
+        if bad > 0 {
+            stats.mismatched_transfers += 1;
+            stats.mismatched_bytes += bad as u64;
+            eprintln!("{} of {} bytes at 0x{:05X} read back wrong", bad, len, addr);
+        }
+
+        if last_report.elapsed() >= Duration::from_secs(options.report) {
+            stats.print(began.elapsed());
+            last_report = Instant::now();
+        }
+    }
+
+    println!("done");
+    stats.print(began.elapsed());
+    if stats.failures() > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// A persistent array of bits
+///
+/// Bit `i` is bit `i % 8` of byte `i / 8`, starting at `addr`. Changing a
+/// bit reads and rewrites only the byte holding it. The handle only records
+/// where the bitmap lives; pass the device to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FramBitmap {
+    addr: u32,
+    bits: u32,
+}
+
+impl FramBitmap {
+    /// A bitmap of `bits` bits stored at `addr`
+    pub const fn new(addr: u32, bits: u32) -> Self {
+        Self { addr, bits }
+    }
+
+    /// Number of bits
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Bytes of FRAM the bitmap occupies
+    pub fn size(&self) -> u32 {
+        self.bits.div_ceil(8)
+    }
+
+    fn locate(&self, bit: u32) -> Result<(u32, u8), Mb85rcError> {
+        if bit >= self.bits {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok((self.addr + bit / 8, 1 << (bit % 8)))
+    }
+
+    fn update<D: FramDevice + ?Sized>(&self, dev: &mut D, bit: u32, value: bool) -> Result<(), Mb85rcError> {
+        let (addr, mask) = self.locate(bit)?;
+        let mut byte = [0u8];
+        dev.read_at(addr, &mut byte)?;
+
+        let new = if value { byte[0] | mask } else { byte[0] & !mask };
+        if new != byte[0] {
+            dev.write_at(addr, &[new])?;
+        }
+        Ok(())
+    }
+
+    /// Set bit `bit`
+    pub fn set<D: FramDevice + ?Sized>(&self, dev: &mut D, bit: u32) -> Result<(), Mb85rcError> {
+        self.update(dev, bit, true)
+    }
+
+    /// Clear bit `bit`
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D, bit: u32) -> Result<(), Mb85rcError> {
+        self.update(dev, bit, false)
+    }
+
+    /// Whether bit `bit` is set
+    pub fn test<D: FramDevice + ?Sized>(&self, dev: &mut D, bit: u32) -> Result<bool, Mb85rcError> {
+        let (addr, mask) = self.locate(bit)?;
+        let mut byte = [0u8];
+        dev.read_at(addr, &mut byte)?;
+        Ok(byte[0] & mask != 0)
+    }
+
+    /// Clear every bit
+    pub fn clear_all<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        let zeros = [0u8; 64];
+        let mut done = 0;
+        while done < self.size() {
+            let n = (self.size() - done).min(zeros.len() as u32);
+            dev.write_at(self.addr + done, &zeros[..n as usize])?;
+            done += n;
+        }
+        Ok(())
+    }
+
+    /// Call `f` with each chunk of the bitmap and the index of its first bit
+    ///
+    /// Bits past the end in the last byte are masked off
+    fn scan<D: FramDevice + ?Sized, R>(
+        &self,
+        dev: &mut D,
+        mut f: impl FnMut(u32, &[u8]) -> Option<R>,
+    ) -> Result<Option<R>, Mb85rcError> {
+        let mut buf = [0u8; 64];
+        let mut done = 0;
+        while done < self.size() {
+            let n = (self.size() - done).min(buf.len() as u32);
+            let chunk = &mut buf[..n as usize];
+            dev.read_at(self.addr + done, chunk)?;
+            if done + n == self.size() && !self.bits.is_multiple_of(8) {
+                chunk[n as usize - 1] &= (1 << (self.bits % 8)) - 1;
+            }
+            if let Some(r) = f(done * 8, chunk) {
+                return Ok(Some(r));
+            }
+            done += n;
+        }
+        Ok(None)
+    }
+
+    /// Number of set bits
+    pub fn count_ones<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<u32, Mb85rcError> {
+        let mut count = 0;
+        self.scan(dev, |_, chunk| {
+            count += chunk.iter().map(|b| b.count_ones()).sum::<u32>();
+            None::<()>
+        })?;
+        Ok(count)
+    }
+
+    /// Index of the first clear bit, or `None` if every bit is set
+    pub fn find_first_zero<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<u32>, Mb85rcError> {
+        let bits = self.bits;
+        self.scan(dev, |first, chunk| {
+            chunk.iter().enumerate()
+                .find(|(_, b)| **b != 0xFF)
+                .map(|(i, b)| first + i as u32 * 8 + b.trailing_ones())
+                .filter(|&bit| bit < bits)
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::mirror::Mirrored;
+
+/// Bytes in an encoded [`BootState`]
+const RECORD: usize = 4;
+
+/// One of the two firmware slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BootSlot {
+    /// The first slot
+    A,
+    /// The second slot
+    B,
+}
+
+impl BootSlot {
+    /// The slot that isn't this one
+    pub fn other(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::A,
+        }
+    }
+}
+
+/// What [`BootFlags`] records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootState {
+    /// The slot to boot
+    pub active: BootSlot,
+    /// Trial boots left before falling back to the other slot
+    pub attempts_left: u8,
+    /// Whether the active slot's firmware has confirmed it works
+    pub confirmed: bool,
+}
+
+impl BootState {
+    const DEFAULT: Self = Self { active: BootSlot::A, attempts_left: 0, confirmed: true };
+
+    fn to_bytes(self) -> [u8; RECORD] {
+        [self.active as u8, self.attempts_left, self.confirmed as u8, 0]
+    }
+
+    fn from_bytes(raw: &[u8; RECORD]) -> Result<Self, Mb85rcError> {
+        let active = match raw[0] {
+            0 => BootSlot::A,
+            1 => BootSlot::B,
+            _ => return Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        };
+        Ok(Self { active, attempts_left: raw[1], confirmed: raw[2] != 0 })
+    }
+}
+
+/// A/B boot bookkeeping in the style of embassy-boot and MCUboot
+///
+/// After installing new firmware the application calls
+/// [`switch`](Self::switch). The bootloader calls [`boot`](Self::boot) on
+/// every reset to pick a slot: an unconfirmed slot gets a limited number of
+/// trial boots, and once they run out the flags revert to the other slot.
+/// New firmware that comes up fine calls [`confirm`](Self::confirm) to stop
+/// the countdown. Each update is a single write to one of two alternating,
+/// CRC-checked copies, so a reset at any point leaves either the old or the
+/// new flags. The handle only records where the flags live; pass the device
+/// to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootFlags {
+    record: Mirrored<RECORD>,
+}
+
+impl BootFlags {
+    /// Bytes of FRAM the flags take up
+    pub const SIZE: u32 = Mirrored::<RECORD>::SIZE;
+
+    /// Flags stored at `addr`
+    pub const fn new(addr: u32) -> Self {
+        Self { record: Mirrored::new(addr) }
+    }
+
+    /// The current flags, slot A and confirmed if nothing valid has been stored yet
+    pub fn state<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<BootState, Mb85rcError> {
+        match self.record.read(dev)? {
+            Some((_, raw)) => BootState::from_bytes(&raw),
+            None => Ok(BootState::DEFAULT),
+        }
+    }
+
+    fn store<D: FramDevice + ?Sized>(&self, dev: &mut D, state: BootState) -> Result<(), Mb85rcError> {
+        self.record.write(dev, &state.to_bytes())
+    }
+
+    /// Make `slot` active on trial, with `attempts` boots to confirm it
+    pub fn switch<D: FramDevice + ?Sized>(&self, dev: &mut D, slot: BootSlot, attempts: u8) -> Result<(), Mb85rcError> {
+        self.store(dev, BootState { active: slot, attempts_left: attempts, confirmed: false })
+    }
+
+    /// Mark the active slot as good
+    pub fn confirm<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        let state = self.state(dev)?;
+        if !state.confirmed {
+            self.store(dev, BootState { confirmed: true, ..state })?;
+        }
+        Ok(())
+    }
+
+    /// Pick the slot to boot, using up a trial boot or reverting as needed
+    pub fn boot<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<BootSlot, Mb85rcError> {
+        let state = self.state(dev)?;
+        if state.confirmed {
+            return Ok(state.active);
+        }
+
+        let next = match state.attempts_left {
+            0 => BootState { active: state.active.other(), attempts_left: 0, confirmed: true },
+            n => BootState { attempts_left: n - 1, ..state },
+        };
+        self.store(dev, next)?;
+        Ok(next.active)
+    }
+}
@@ -0,0 +1,42 @@
+use embedded_hal::blocking::i2c;
+
+/// A mutably borrowed I2C bus
+///
+/// Lets the driver use a bus owned by someone else (an RTIC resource, a
+/// shared bus manager, ...) for as long as the borrow lasts. Create one
+/// with [`Builder::borrow_i2c`](crate::Builder::borrow_i2c).
+pub struct BorrowedI2c<'a, I2C>(&'a mut I2C);
+
+impl<'a, I2C> BorrowedI2c<'a, I2C> {
+    /// Wrap a mutable reference to a bus
+    pub fn new(i2c: &'a mut I2C) -> Self {
+        Self(i2c)
+    }
+
+    /// Give the borrow back
+    pub fn into_inner(self) -> &'a mut I2C {
+        self.0
+    }
+}
+
+impl<I2C> i2c::Write for BorrowedI2c<'_, I2C>
+where
+    I2C: i2c::Write,
+{
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, bytes)
+    }
+}
+
+impl<I2C> i2c::WriteRead for BorrowedI2c<'_, I2C>
+where
+    I2C: i2c::WriteRead,
+{
+    type Error = I2C::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.write_read(address, bytes, buffer)
+    }
+}
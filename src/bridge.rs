@@ -0,0 +1,38 @@
+/// USB-to-I2C bridges with transfer-size limits the driver needs to respect
+///
+/// Pass one to [`Builder::with_usb_bridge`](crate::Builder::with_usb_bridge)
+/// when the bus goes through one: `ftdi-embedded-hal` for the FT232H, or the
+/// i2c-dev bus the i2c-ch341-usb driver makes for the CH341. Both bridges
+/// fail or silently truncate long transactions, so the driver splits them up
+/// front. The `ft232h` and `ch341` examples show the whole setup for each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UsbBridge {
+    /// FTDI FT232H (and FT2232H/FT4232H) in MPSSE mode
+    ///
+    /// `ftdi-embedded-hal` queues a whole transaction as one MPSSE command
+    /// list, 11 command bytes for every I2C byte (two pin updates, eight bits
+    /// out and the ACK bit in) plus the start and stop conditions. That has to
+    /// fit the chip's 1 KiB transmit buffer (FT232H datasheet, DS_FT232H),
+    /// which caps a transaction at about 90 bytes; 64 leaves room for the
+    /// slave and memory address bytes.
+    Ft232h,
+    /// WCH CH341A in I2C mode
+    ///
+    /// Transactions go out as 32-byte USB bulk packets (CH341 datasheet,
+    /// CH341DS1, and the bulk size in the i2c-ch341-usb driver) that also
+    /// carry the stream command, start, length, address and stop bytes. What's
+    /// left for data depends on how the driver packs reads, so 16 is the
+    /// largest power of two that fits a write or a read with room to spare.
+    Ch341,
+}
+
+impl UsbBridge {
+    /// Largest number of data bytes to put in one transaction through this bridge
+    pub fn max_transfer(&self) -> usize {
+        match self {
+            UsbBridge::Ft232h => 64,
+            UsbBridge::Ch341 => 16,
+        }
+    }
+}
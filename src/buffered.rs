@@ -0,0 +1,99 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Write buffer that merges sequential writes into larger transactions
+///
+/// Writes that continue exactly where the previous one ended are collected in
+/// an `N`-byte buffer and sent as one write, so serializing a struct field by
+/// field doesn't cost one bus transaction per field. The buffer goes out when
+/// a non-adjacent write arrives, when it fills up, or on [`flush`](Self::flush).
+/// Reads see pending data without flushing it.
+///
+/// Pending data is lost if the wrapper is dropped without flushing. With
+/// `N` of 0 nothing is buffered and writes go straight through.
+pub struct BufferedFram<D: FramDevice, const N: usize> {
+    dev: D,
+    buf: [u8; N],
+    start: u32,
+    len: usize,
+}
+
+impl<D: FramDevice, const N: usize> BufferedFram<D, N> {
+    /// Put a write buffer in front of `dev`
+    pub fn new(dev: D) -> Self {
+        Self { dev, buf: [0u8; N], start: 0, len: 0 }
+    }
+
+    /// Send any buffered data to the device
+    pub fn flush(&mut self) -> Result<(), Mb85rcError> {
+        if self.len > 0 {
+            self.dev.write_at(self.start, &self.buf[..self.len])?;
+            self.len = 0;
+        }
+        Ok(())
+    }
+
+    /// Number of bytes waiting to be written
+    pub fn pending(&self) -> usize {
+        self.len
+    }
+
+    /// Flush and hand back the device
+    pub fn into_inner(mut self) -> Result<D, Mb85rcError> {
+        self.flush()?;
+        Ok(self.dev)
+    }
+
+    fn pending_end(&self) -> u32 {
+        self.start + self.len as u32
+    }
+}
+
+impl<D: FramDevice, const N: usize> FramDevice for BufferedFram<D, N> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.dev.read_at(addr, buf)?;
+
+        // lay pending bytes over what the device returned
+        let lo = addr.max(self.start);
+        let hi = (addr as u64 + buf.len() as u64).min(self.pending_end() as u64) as u32;
+        if self.len > 0 && lo < hi {
+            let (dst, src) = ((lo - addr) as usize, (lo - self.start) as usize);
+            let n = (hi - lo) as usize;
+            buf[dst..dst + n].copy_from_slice(&self.buf[src..src + n]);
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, addr: u32, mut data: &[u8]) -> Result<(), Mb85rcError> {
+        if addr as u64 + data.len() as u64 > self.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        if N == 0 {
+            return self.dev.write_at(addr, data);
+        }
+
+        let mut addr = addr;
+        if self.len > 0 && addr != self.pending_end() {
+            self.flush()?;
+        }
+
+        while !data.is_empty() {
+            if self.len == 0 {
+                self.start = addr;
+            }
+            let n = data.len().min(N - self.len);
+            self.buf[self.len..self.len + n].copy_from_slice(&data[..n]);
+            self.len += n;
+            addr += n as u32;
+            data = &data[n..];
+
+            if self.len == N {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Marks a [`CacheLine`] that holds nothing
+const EMPTY: u32 = u32::MAX;
+
+/// Bookkeeping for one block held by a [`CachedFram`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheLine {
+    block: u32,
+    used: u32,
+}
+
+impl CacheLine {
+    /// A line holding nothing, for initializing the array given to [`CachedFram::new`]
+    pub const EMPTY: Self = Self { block: EMPTY, used: 0 };
+}
+
+/// Read cache that keeps recently used blocks in RAM
+///
+/// The device is split into `block_size`-byte blocks. Reads load whole
+/// blocks into the caller's buffers and evict the least recently used block
+/// when they run out; writes go straight to the device and update any copy
+/// in the cache, so nothing is ever lost by dropping the wrapper.
+pub struct CachedFram<'a, D: FramDevice> {
+    dev: D,
+    block_size: u32,
+    lines: &'a mut [CacheLine],
+    data: &'a mut [u8],
+    clock: u32,
+    hits: u32,
+    misses: u32,
+}
+
+impl<'a, D: FramDevice> CachedFram<'a, D> {
+    /// Cache `dev` in `lines.len()` blocks of `block_size` bytes, stored in `data`
+    ///
+    /// Fails with [`ErrorKind::BufferTooSmall`] unless `data` has room for
+    /// every line.
+    pub fn new(dev: D, block_size: u32, lines: &'a mut [CacheLine], data: &'a mut [u8]) -> Result<Self, Mb85rcError> {
+        if block_size == 0 || lines.is_empty() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        if (data.len() as u64) < block_size as u64 * lines.len() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::BufferTooSmall));
+        }
+        lines.fill(CacheLine::EMPTY);
+        Ok(Self { dev, block_size, lines, data, clock: 0, hits: 0, misses: 0 })
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Reads of a block that was already cached
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+
+    /// Reads that had to load a block from the device
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+
+    /// Drop any cached blocks overlapping `range`, so the next read goes to the device
+    ///
+    /// Use this after something else (another bus master, a debugger) has
+    /// written to the FRAM behind the cache's back.
+    pub fn invalidate(&mut self, range: Range<u32>) {
+        for line in self.lines.iter_mut() {
+            if line.block == EMPTY {
+                continue;
+            }
+            let start = line.block as u64 * self.block_size as u64;
+            if start < range.end as u64 && (range.start as u64) < start + self.block_size as u64 {
+                *line = CacheLine::EMPTY;
+            }
+        }
+    }
+
+    /// Drop every cached block
+    pub fn invalidate_all(&mut self) {
+        self.lines.fill(CacheLine::EMPTY);
+    }
+
+    /// Read straight from the device, leaving the cache as it was
+    pub fn read_uncached(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.dev.read_at(addr, buf)
+    }
+
+    fn line_data(&mut self, line: usize) -> &mut [u8] {
+        let size = self.block_size as usize;
+        &mut self.data[line * size..(line + 1) * size]
+    }
+
+    /// Index of the line holding `block`, loading it if needed
+    fn lookup(&mut self, block: u32) -> Result<usize, Mb85rcError> {
+        self.clock = self.clock.wrapping_add(1);
+        if let Some(line) = self.lines.iter().position(|l| l.block == block) {
+            self.hits = self.hits.wrapping_add(1);
+            self.lines[line].used = self.clock;
+            return Ok(line);
+        }
+        self.misses = self.misses.wrapping_add(1);
+
+        // empty lines first, then the least recently used
+        let line = (0..self.lines.len())
+            .min_by_key(|&i| (self.lines[i].block != EMPTY, self.lines[i].used))
+            .unwrap();
+        self.lines[line] = CacheLine::EMPTY;
+
+        let start = block * self.block_size;
+        let len = self.block_size.min(self.dev.capacity() - start) as usize;
+        let size = self.block_size as usize;
+        self.dev.read_at(start, &mut self.data[line * size..line * size + len])?;
+
+        self.lines[line] = CacheLine { block, used: self.clock };
+        Ok(line)
+    }
+}
+
+impl<D: FramDevice> FramDevice for CachedFram<'_, D> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        if addr as u64 + buf.len() as u64 > self.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+
+        let mut done = 0;
+        while done < buf.len() {
+            let pos = addr + done as u32;
+            let (block, offset) = (pos / self.block_size, (pos % self.block_size) as usize);
+            let line = self.lookup(block)?;
+            let n = (buf.len() - done).min(self.block_size as usize - offset);
+            buf[done..done + n].copy_from_slice(&self.line_data(line)[offset..offset + n]);
+            done += n;
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, addr: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        self.dev.write_at(addr, data)?;
+
+        // keep cached copies in step, without loading anything new
+        let end = addr as u64 + data.len() as u64;
+        for line in 0..self.lines.len() {
+            let block = self.lines[line].block;
+            if block == EMPTY {
+                continue;
+            }
+            let start = block as u64 * self.block_size as u64;
+            let lo = start.max(addr as u64);
+            let hi = (start + self.block_size as u64).min(end);
+            if lo < hi {
+                let (dst, src, n) = ((lo - start) as usize, (lo - addr as u64) as usize, (hi - lo) as usize);
+                self.line_data(line)[dst..dst + n].copy_from_slice(&data[src..src + n]);
+            }
+        }
+        Ok(())
+    }
+}
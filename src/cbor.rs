@@ -0,0 +1,38 @@
+//! CBOR encoding for stored records, via `minicbor`
+//!
+//! CBOR is self-describing, so external tools can read the stored records
+//! without the firmware's type definitions.
+
+use minicbor::{Encode, Decode};
+use minicbor::encode::write::Cursor;
+
+use crate::{Codec, Mb85rcError, ErrorKind};
+
+/// Encode `value` into `buf`, returning the encoded length
+pub fn encode<T: Encode<()>>(value: &T, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+    let mut cursor = Cursor::new(buf);
+    minicbor::encode(value, &mut cursor).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+    Ok(cursor.position())
+}
+
+/// Decode a value from `bytes`
+pub fn decode<'b, T: Decode<'b, ()>>(bytes: &'b [u8]) -> Result<T, Mb85rcError> {
+    minicbor::decode(bytes).map_err(|_| Mb85rcError::new(ErrorKind::InvalidData))
+}
+
+/// [`Codec`] storing values as CBOR
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cbor;
+
+impl<T> Codec<T> for Cbor
+where
+    T: Encode<()> + for<'b> Decode<'b, ()>,
+{
+    fn encode(&self, value: &T, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        encode(value, buf)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, Mb85rcError> {
+        decode(bytes)
+    }
+}
@@ -0,0 +1,142 @@
+use embedded_hal::blocking::i2c;
+use core::error::Error;
+
+use crate::{MB85RC, FramAddress, Mb85rcError, WriteProtect};
+
+/// A write that is carried out one transaction at a time
+///
+/// Each call to [`step`](Self::step) moves at most
+/// [`max_transfer`](MB85RC::max_transfer) bytes, so the driver only needs to
+/// be locked for a single short transaction. This fits RTIC-style shared
+/// resources, where a task should take the lock per step rather than holding
+/// it for a whole multi-kilobyte transfer.
+///
+/// # Example
+///
+/// Two RTIC tasks sharing the driver as a `#[shared]` resource. The task
+/// bodies are written against `rtic::Mutex`, so the app calls them as
+/// `log_samples(&mut cx.shared.fram, &samples)` and
+/// `count_event(&mut cx.shared.fram)`. The logger only takes the lock for one
+/// chunk at a time, so the higher-priority counter gets in between chunks.
+///
+/// ```
+/// use mb85rc::{ChunkedWrite, Mb85rcError, MB85RC};
+/// use rtic_core::Mutex;
+/// # type I2c = mb85rc::SimFram;
+///
+/// /// The driver on the board's I2C peripheral
+/// type Fram = MB85RC<I2c>;
+///
+/// const LOG: u32 = 0x100;
+/// const EVENTS: u32 = 0x0;
+///
+/// /// Low priority: persist a block of samples without hogging the bus
+/// fn log_samples(fram: &mut impl Mutex<T = Fram>, samples: &[u8]) -> Result<(), Mb85rcError> {
+///     let addr = fram.lock(|fram| fram.address(LOG))?;
+///     let mut write = ChunkedWrite::new(addr, samples);
+///     while !fram.lock(|fram| write.step(fram))? {}
+///     Ok(())
+/// }
+///
+/// /// High priority: count an event with one short read and write
+/// fn count_event(fram: &mut impl Mutex<T = Fram>) -> Result<u32, Mb85rcError> {
+///     fram.lock(|fram| {
+///         let addr = fram.address(EVENTS)?;
+///         let mut count = [0u8; 4];
+///         fram.fram_read(addr, &mut count)?;
+///         let count = u32::from_le_bytes(count) + 1;
+///         fram.fram_write(addr, &count.to_le_bytes())?;
+///         Ok(count)
+///     })
+/// }
+/// #
+/// # struct Shared<'a>(&'a core::cell::RefCell<Fram>);
+/// # impl Mutex for Shared<'_> {
+/// #     type T = Fram;
+/// #     fn lock<R>(&mut self, f: impl FnOnce(&mut Fram) -> R) -> R {
+/// #         f(&mut self.0.borrow_mut())
+/// #     }
+/// # }
+/// # let fram = core::cell::RefCell::new(mb85rc::Builder::new().with_size(1024).connect_i2c(I2c::new(1024)));
+/// # log_samples(&mut Shared(&fram), &[0xAB; 300])?;
+/// # assert_eq!(count_event(&mut Shared(&fram))?, 1);
+/// # Ok::<(), Mb85rcError>(())
+/// ```
+pub struct ChunkedWrite<'a> {
+    addr: FramAddress,
+    data: &'a [u8],
+}
+
+impl<'a> ChunkedWrite<'a> {
+    /// Prepare to write `data` starting at `addr`
+    pub fn new(addr: FramAddress, data: &'a [u8]) -> Self {
+        Self { addr, data }
+    }
+
+    /// Write the next chunk, returning `true` once everything has been written
+    pub fn step<I2C, WP: WriteProtect>(&mut self, fram: &mut MB85RC<I2C, WP>) -> Result<bool, Mb85rcError>
+    where
+        I2C: i2c::WriteRead + i2c::Write,
+        <I2C as i2c::WriteRead>::Error: Error,
+        <I2C as i2c::Write>::Error: Error,
+    {
+        if self.data.is_empty() {
+            return Ok(true);
+        }
+
+        let len = self.data.len().min(fram.max_transfer());
+        let n = fram.fram_write(self.addr, &self.data[..len])?;
+        self.addr = self.addr.checked_add(n as u32).unwrap();
+        self.data = &self.data[n..];
+        Ok(self.data.is_empty())
+    }
+
+    /// Number of bytes still to be written
+    pub fn remaining(&self) -> usize {
+        self.data.len()
+    }
+}
+
+/// A read that is carried out one transaction at a time
+///
+/// The read counterpart of [`ChunkedWrite`]
+pub struct ChunkedRead<'a> {
+    addr: FramAddress,
+    buf: &'a mut [u8],
+    done: usize,
+}
+
+impl<'a> ChunkedRead<'a> {
+    /// Prepare to fill `buf` with bytes starting at `addr`
+    pub fn new(addr: FramAddress, buf: &'a mut [u8]) -> Self {
+        Self { addr, buf, done: 0 }
+    }
+
+    /// Read the next chunk, returning `true` once the buffer is full
+    pub fn step<I2C, WP: WriteProtect>(&mut self, fram: &mut MB85RC<I2C, WP>) -> Result<bool, Mb85rcError>
+    where
+        I2C: i2c::WriteRead + i2c::Write,
+        <I2C as i2c::WriteRead>::Error: Error,
+        <I2C as i2c::Write>::Error: Error,
+    {
+        if self.done == self.buf.len() {
+            return Ok(true);
+        }
+
+        let len = self.remaining().min(fram.max_transfer());
+        let n = fram.fram_read(self.addr, &mut self.buf[self.done..self.done + len])?;
+        self.addr = self.addr.checked_add(n as u32).unwrap();
+        self.done += n;
+        Ok(self.done == self.buf.len())
+    }
+
+    /// Number of bytes still to be read
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.done
+    }
+
+    /// Give back the buffer once reading is finished (or abandoned)
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.buf
+    }
+}
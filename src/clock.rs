@@ -0,0 +1,80 @@
+/// Where the timestamping layers get the current time
+///
+/// The time is a `u32` of whatever unit suits the product: epoch seconds
+/// from an RTC, milliseconds since boot, timer ticks. Intervals given to
+/// the same layer are in that unit too, and times are compared with
+/// wrapping arithmetic, so a tick counter may roll over. Any
+/// `FnMut() -> u32` is a clock, e.g. `|| rtc.unix_time()`.
+pub trait Clock {
+    /// The current time
+    fn now(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> Clock for F {
+    fn now(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// Seconds since the Unix epoch from the host's clock
+///
+/// Reads 0 if the clock is set before 1970, and wraps in 2106.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&mut self) -> u32 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as u32)
+    }
+}
+
+/// Adapts a function returning a [`fugit`] instant, such as an RTIC monotonic's `now`
+///
+/// The time is the instant's raw ticks, so intervals are in ticks of the
+/// timer's rate.
+#[cfg(feature = "fugit")]
+pub struct FugitClock<F>(pub F);
+
+#[cfg(feature = "fugit")]
+impl<F: FnMut() -> fugit::Instant<u32, NOM, DENOM>, const NOM: u32, const DENOM: u32> Clock for FugitClock<F> {
+    fn now(&mut self) -> u32 {
+        (self.0)().ticks()
+    }
+}
+
+/// Adapts an [`embedded_time::Clock`] with a 32-bit tick count
+///
+/// The time is ticks since the clock's epoch. If the clock fails to read,
+/// the last time read is repeated.
+#[cfg(feature = "embedded-time")]
+pub struct EmbeddedTimeClock<C> {
+    clock: C,
+    last: u32,
+}
+
+#[cfg(feature = "embedded-time")]
+impl<C: embedded_time::Clock<T = u32>> EmbeddedTimeClock<C> {
+    /// Read the time from `clock`
+    pub fn new(clock: C) -> Self {
+        Self { clock, last: 0 }
+    }
+
+    /// Give back the clock
+    pub fn into_inner(self) -> C {
+        self.clock
+    }
+}
+
+#[cfg(feature = "embedded-time")]
+impl<C: embedded_time::Clock<T = u32>> Clock for EmbeddedTimeClock<C> {
+    fn now(&mut self) -> u32 {
+        if let Ok(instant) = self.clock.try_now() {
+            self.last = instant.duration_since_epoch().integer();
+        }
+        self.last
+    }
+}
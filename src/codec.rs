@@ -0,0 +1,60 @@
+use crate::{Mb85rcError, ErrorKind};
+
+/// Turns values into bytes for storage and back
+///
+/// Typed storage helpers take a codec instead of hardcoding a format, so the
+/// same helpers work with postcard, CBOR, bincode or a hand-written fixed
+/// layout. Implement it on a unit struct and pass that in.
+pub trait Codec<T> {
+    /// Encode `value` into `buf`, returning the number of bytes used
+    ///
+    /// Fail with [`ErrorKind::TooLarge`] if `buf` is too small
+    fn encode(&self, value: &T, buf: &mut [u8]) -> Result<usize, Mb85rcError>;
+
+    /// Decode a value from `bytes`
+    ///
+    /// Fail with [`ErrorKind::InvalidData`] if the bytes don't hold a valid value
+    fn decode(&self, bytes: &[u8]) -> Result<T, Mb85rcError>;
+}
+
+/// Fixed-size little-endian layout for integers and byte arrays
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LittleEndian;
+
+macro_rules! le_codec {
+    ($($t:ty),*) => {$(
+        impl Codec<$t> for LittleEndian {
+            fn encode(&self, value: &$t, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+                let bytes = value.to_le_bytes();
+                buf.get_mut(..bytes.len())
+                    .ok_or(Mb85rcError::new(ErrorKind::TooLarge))?
+                    .copy_from_slice(&bytes);
+                Ok(bytes.len())
+            }
+
+            fn decode(&self, bytes: &[u8]) -> Result<$t, Mb85rcError> {
+                bytes.get(..size_of::<$t>())
+                    .and_then(|b| b.try_into().ok())
+                    .map(<$t>::from_le_bytes)
+                    .ok_or(Mb85rcError::new(ErrorKind::InvalidData))
+            }
+        }
+    )*};
+}
+
+le_codec!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl<const N: usize> Codec<[u8; N]> for LittleEndian {
+    fn encode(&self, value: &[u8; N], buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        buf.get_mut(..N)
+            .ok_or(Mb85rcError::new(ErrorKind::TooLarge))?
+            .copy_from_slice(value);
+        Ok(N)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<[u8; N], Mb85rcError> {
+        bytes.get(..N)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(Mb85rcError::new(ErrorKind::InvalidData))
+    }
+}
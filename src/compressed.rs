@@ -0,0 +1,121 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::{self, Crc32};
+
+/// Bytes of uncompressed length, stored length and CRC in front of the data
+const HEADER: u32 = 12;
+
+/// A region holding one LZ4-compressed blob
+///
+/// Good for large text configs, JSON or log snapshots that would otherwise
+/// need a bigger part. The region starts with the uncompressed length, the
+/// stored length and a CRC-32 over both and the stored bytes. Data that
+/// doesn't get any smaller is stored as is. The handle only records where
+/// the blob lives; pass the device to each call.
+///
+/// Writing replaces the blob in place, so a reset partway through leaves it
+/// failing its CRC rather than holding the old contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedRegion {
+    region: Range<u32>,
+}
+
+impl CompressedRegion {
+    /// A compressed blob stored in `region`
+    pub fn new(region: Range<u32>) -> Self {
+        Self { region }
+    }
+
+    /// Most bytes of compressed data the region can hold
+    pub fn capacity(&self) -> u32 {
+        self.region.len().saturating_sub(HEADER as usize) as u32
+    }
+
+    /// Scratch space [`write`](Self::write) needs to be sure of compressing `len` bytes
+    pub fn scratch_size(len: usize) -> usize {
+        lz4_flex::block::get_maximum_output_size(len)
+    }
+
+    /// Compress `data` through `scratch` and store it, returning the bytes it took up
+    ///
+    /// If `scratch` can't hold the compressed form, `data` is stored
+    /// uncompressed. Fails with [`ErrorKind::TooLarge`] if the result
+    /// doesn't fit in the region.
+    pub fn write<D: FramDevice + ?Sized>(&self, dev: &mut D, data: &[u8], scratch: &mut [u8]) -> Result<u32, Mb85rcError> {
+        let len = u32::try_from(data.len()).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+        let stored = match lz4_flex::block::compress_into(data, scratch) {
+            Ok(n) if n < data.len() => &scratch[..n],
+            _ => data,
+        };
+        if self.region.len() < HEADER as usize {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        if stored.len() > self.capacity() as usize {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+
+        let mut header = [0u8; HEADER as usize];
+        header[0..4].copy_from_slice(&len.to_le_bytes());
+        header[4..8].copy_from_slice(&(stored.len() as u32).to_le_bytes());
+        let mut crc = Crc32::new();
+        crc.update(&header[..8]);
+        crc.update(stored);
+        header[8..12].copy_from_slice(&crc.finish().to_le_bytes());
+
+        dev.write_at(self.region.start + HEADER, stored)?;
+        dev.write_at(self.region.start, &header)?;
+        Ok(HEADER + stored.len() as u32)
+    }
+
+    /// Read and check the header, returning the uncompressed and stored lengths
+    fn header<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(usize, usize), Mb85rcError> {
+        if self.region.len() < HEADER as usize {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        let mut header = [0u8; HEADER as usize];
+        dev.read_at(self.region.start, &mut header)?;
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let stored = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if stored > self.capacity() || stored > len {
+            return Err(Mb85rcError::new(ErrorKind::InvalidData));
+        }
+
+        let mut crc = Crc32::new();
+        crc.update(&header[..8]);
+        crc::update_from_device(&mut crc, dev, self.region.start + HEADER, stored)?;
+        match crc.finish() == u32::from_le_bytes(header[8..12].try_into().unwrap()) {
+            true => Ok((len as usize, stored as usize)),
+            false => Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        }
+    }
+
+    /// Uncompressed length of the stored blob
+    pub fn len<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<usize, Mb85rcError> {
+        self.header(dev).map(|(len, _)| len)
+    }
+
+    /// Decompress the blob into `buf` through `scratch`, returning its length
+    ///
+    /// `scratch` must hold the compressed form, which is never larger than
+    /// [`capacity`](Self::capacity).
+    pub fn read<D: FramDevice + ?Sized>(&self, dev: &mut D, buf: &mut [u8], scratch: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let (len, stored) = self.header(dev)?;
+        let buf = buf.get_mut(..len).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        if stored == len {
+            dev.read_at(self.region.start + HEADER, buf)?;
+            return Ok(len);
+        }
+
+        let scratch = scratch.get_mut(..stored).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        dev.read_at(self.region.start + HEADER, scratch)?;
+        match lz4_flex::block::decompress_into(scratch, buf) {
+            Ok(n) if n == len => Ok(len),
+            _ => Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        }
+    }
+
+    /// Replace the stored blob with an empty one
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        self.write(dev, &[], &mut []).map(|_| ())
+    }
+}
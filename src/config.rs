@@ -0,0 +1,173 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::Builder;
+
+/// Where the FRAM is and how to talk to it, read at runtime instead of compiled in
+///
+/// Lets a daemon or test script be pointed at other hardware by changing
+/// an environment variable or a config file. [`from_env`](Self::from_env)
+/// reads `MB85RC_BUS`, `MB85RC_ADDRESS`, `MB85RC_SIZE`,
+/// `MB85RC_MAX_TRANSFER`, `MB85RC_ADDRESS_DISCOVERY` and `MB85RC_ID_CHECK`;
+/// [`load`](Self::load) reads the same settings, lowercase and without the
+/// prefix, from a flat TOML file:
+///
+/// ```toml
+/// bus = "/dev/i2c-3"
+/// address = 0x51
+/// size = 32768
+/// id_check = true
+/// ```
+///
+/// Anything left out keeps its default, which is the same as [`Builder::new`]
+/// on `/dev/i2c-1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramConfig {
+    /// Path of the I2C bus device
+    pub bus: String,
+    /// The I2C address
+    pub address: u8,
+    /// The size in bytes, or `None` to read it from the device ID
+    pub size: Option<u32>,
+    /// Largest number of data bytes in one transaction
+    pub max_transfer: Option<usize>,
+    /// See [`Builder::with_address_discovery`]
+    pub address_discovery: bool,
+    /// See [`Builder::with_id_check`]
+    pub id_check: bool,
+}
+
+impl FramConfig {
+    /// Read the settings from `MB85RC_*` environment variables
+    pub fn from_env() -> io::Result<Self> {
+        Self::from_env_with_prefix("MB85RC_")
+    }
+
+    /// Read the settings from environment variables starting with `prefix`, to configure more than one device
+    pub fn from_env_with_prefix(prefix: &str) -> io::Result<Self> {
+        let mut config = Self::default();
+        for key in ["bus", "address", "size", "max_transfer", "address_discovery", "id_check"] {
+            let var = format!("{}{}", prefix, key.to_uppercase());
+            match env::var(&var) {
+                Ok(value) => config.set(key, value.trim()).map_err(|e| invalid(format!("{}: {}", var, e)))?,
+                Err(env::VarError::NotPresent) => {},
+                Err(e) => return Err(invalid(format!("{}: {}", var, e))),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read the settings from the TOML file at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Read the settings from TOML text
+    ///
+    /// Only `key = value` lines with strings, integers (decimal or `0x` hex)
+    /// and booleans are understood, which is all the settings need.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut config = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |e: String| invalid(format!("line {}: {}", i + 1, e));
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected `key = value`".into()))?;
+            let value = value.trim();
+            let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                Some(s) => s,
+                None => value,
+            };
+            config.set(key.trim(), value).map_err(err)?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let bad = || format!("bad value {:?} for {}", value, key);
+        match key {
+            "bus" => self.bus = value.into(),
+            "address" => self.address = parse_int(value).and_then(|v| u8::try_from(v).ok()).ok_or_else(bad)?,
+            "size" => self.size = Some(parse_int(value).and_then(|v| u32::try_from(v).ok()).ok_or_else(bad)?),
+            "max_transfer" => self.max_transfer = Some(parse_int(value).and_then(|v| usize::try_from(v).ok()).ok_or_else(bad)?),
+            "address_discovery" => self.address_discovery = parse_bool(value).ok_or_else(bad)?,
+            "id_check" => self.id_check = parse_bool(value).ok_or_else(bad)?,
+            _ => return Err(format!("unknown setting {:?}", key)),
+        }
+        Ok(())
+    }
+
+    /// A [`Builder`] with these settings, ready to connect to the bus at [`bus`](Self::bus)
+    pub fn builder(&self) -> Builder {
+        let mut builder = Builder::new()
+            .with_address(self.address)
+            .with_address_discovery(self.address_discovery)
+            .with_id_check(self.id_check);
+        if let Some(size) = self.size {
+            builder = builder.with_size(size);
+        }
+        if let Some(bytes) = self.max_transfer {
+            builder = builder.with_max_transfer(bytes);
+        }
+        builder
+    }
+}
+
+impl Default for FramConfig {
+    fn default() -> Self {
+        Self {
+            bus: "/dev/i2c-1".into(),
+            address: 0x50,
+            size: None,
+            max_transfer: None,
+            address_discovery: false,
+            id_check: false,
+        }
+    }
+}
+
+impl Builder {
+    /// A builder set up from `MB85RC_*` environment variables
+    ///
+    /// See [`FramConfig`]; use [`FramConfig::from_env`] to get the bus path too.
+    pub fn from_env() -> io::Result<Self> {
+        FramConfig::from_env().map(|config| config.builder())
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// `line` without a trailing `#` comment, leaving `#` inside quotes alone
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {},
+        }
+    }
+    line
+}
+
+fn parse_int(s: &str) -> Option<u64> {
+    let s = s.replace('_', "");
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
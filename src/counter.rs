@@ -0,0 +1,88 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::mirror::Mirrored;
+
+/// A 64-bit counter that never goes backwards
+///
+/// The value lives in two alternating, CRC-checked copies at the start of
+/// the device (usually a [`FramSlice`](crate::FramSlice)), so a reset during
+/// an update leaves the previous value, and an update only returns once the
+/// new value is stored. A device without a valid value starts at zero.
+pub struct MonotonicCounter<D: FramDevice> {
+    dev: D,
+    value: u64,
+}
+
+impl<D: FramDevice> MonotonicCounter<D> {
+    /// Bytes of FRAM the counter takes up
+    pub const SIZE: u32 = Mirrored::<8>::SIZE;
+
+    /// Open the counter stored on `dev`
+    pub fn new(mut dev: D) -> Result<Self, Mb85rcError> {
+        if dev.capacity() < Self::SIZE {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        let value = match Mirrored::<8>::new(0).read(&mut dev)? {
+            Some((_, raw)) => u64::from_le_bytes(raw),
+            None => 0,
+        };
+        Ok(Self { dev, value })
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// The current value
+    pub fn get(&self) -> u64 {
+        self.value
+    }
+
+    /// Add `n` and store the result, failing with [`ErrorKind::Full`] instead of wrapping
+    pub fn advance(&mut self, n: u64) -> Result<u64, Mb85rcError> {
+        let value = self.value.checked_add(n).ok_or(Mb85rcError::new(ErrorKind::Full))?;
+        Mirrored::<8>::new(0).write(&mut self.dev, &value.to_le_bytes())?;
+        self.value = value;
+        Ok(value)
+    }
+
+    /// Add one and store the result
+    pub fn increment(&mut self) -> Result<u64, Mb85rcError> {
+        self.advance(1)
+    }
+}
+
+/// Hands out nonces that are never reused, even across resets
+///
+/// Nonces are reserved from a [`MonotonicCounter`] in blocks of `block`, so
+/// only one FRAM write is needed per block. After a reset the rest of the
+/// last reserved block is skipped rather than risk handing out a nonce
+/// twice. Keep the counter somewhere that isn't wiped along with the data
+/// it protects.
+pub struct NonceCounter<D: FramDevice> {
+    counter: MonotonicCounter<D>,
+    next: u64,
+    block: u64,
+}
+
+impl<D: FramDevice> NonceCounter<D> {
+    /// Hand out nonces from `counter`, reserving `block` at a time
+    pub fn new(counter: MonotonicCounter<D>, block: u64) -> Self {
+        Self { next: counter.get(), counter, block: block.max(1) }
+    }
+
+    /// Give back the counter
+    pub fn into_inner(self) -> MonotonicCounter<D> {
+        self.counter
+    }
+
+    /// The next unused nonce
+    pub fn next_nonce(&mut self) -> Result<u64, Mb85rcError> {
+        if self.next == self.counter.get() {
+            self.counter.advance(self.block)?;
+        }
+        let nonce = self.next;
+        self.next += 1;
+        Ok(nonce)
+    }
+}
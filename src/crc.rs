@@ -0,0 +1,61 @@
+//! CRC-32 (IEEE 802.3, as used by zlib and Ethernet) for integrity checks
+
+/// Incremental CRC-32 calculator
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Start a new checksum
+    pub const fn new() -> Self {
+        Self { state: 0xFFFF_FFFF }
+    }
+
+    /// Feed more bytes into the checksum
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.state ^= b as u32;
+            for _ in 0..8 {
+                let mask = (self.state & 1).wrapping_neg();
+                self.state = (self.state >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    /// The checksum of everything fed in so far
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC-32 of a byte slice in one go
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
+
+/// CRC-32 of `len` bytes of device memory at `addr`, continuing from `crc`
+pub(crate) fn update_from_device<D: crate::FramDevice + ?Sized>(
+    crc: &mut Crc32,
+    dev: &mut D,
+    addr: u32,
+    len: u32,
+) -> Result<(), crate::Mb85rcError> {
+    let mut buf = [0u8; 64];
+    let mut done = 0;
+    while done < len {
+        let n = (len - done).min(buf.len() as u32);
+        dev.read_at(addr + done, &mut buf[..n as usize])?;
+        crc.update(&buf[..n as usize]);
+        done += n;
+    }
+    Ok(())
+}
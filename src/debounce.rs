@@ -0,0 +1,84 @@
+use crate::{Clock, FramDevice, Codec, Mb85rcError};
+
+/// A frequently updated value that is only written once it settles
+///
+/// Updates land in RAM. [`poll`](Self::poll) writes the latest value once
+/// `quiet` ticks have passed without an update, so a counter bumped in a
+/// tight loop costs one write when the loop ends rather than one per bump.
+/// [`flush`](Self::flush) always writes the current value, so call it
+/// before powering down. The value is encoded with `codec` into an `N`-byte
+/// buffer and stored at `addr`. The handle only records where the value
+/// lives; pass the device to each call that touches it.
+pub struct Debounced<T, C: Codec<T>, const N: usize> {
+    addr: u32,
+    codec: C,
+    value: T,
+    pending: bool,
+    last_change: u32,
+    quiet: u32,
+}
+
+impl<T, C: Codec<T>, const N: usize> Debounced<T, C, N> {
+    /// Start from `value` without reading the device
+    pub fn new(addr: u32, codec: C, value: T, quiet: u32) -> Self {
+        Self { addr, codec, value, pending: false, last_change: 0, quiet }
+    }
+
+    /// Start from the value stored at `addr`
+    pub fn load<D: FramDevice + ?Sized>(dev: &mut D, addr: u32, codec: C, quiet: u32) -> Result<Self, Mb85rcError> {
+        let mut buf = [0u8; N];
+        dev.read_at(addr, &mut buf)?;
+        let value = codec.decode(&buf)?;
+        Ok(Self::new(addr, codec, value, quiet))
+    }
+
+    /// The latest value, written or not
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Replace the value, restarting the quiet period at `now`
+    pub fn set(&mut self, value: T, now: u32) {
+        self.value = value;
+        self.touch(now);
+    }
+
+    /// Change the value in place, restarting the quiet period at `now`
+    pub fn update(&mut self, now: u32, f: impl FnOnce(&mut T)) {
+        f(&mut self.value);
+        self.touch(now);
+    }
+
+    fn touch(&mut self, now: u32) {
+        self.pending = true;
+        self.last_change = now;
+    }
+
+    /// Whether the latest value hasn't been written yet
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Write the value if it has been quiet long enough, returning whether it was written
+    pub fn poll<D: FramDevice + ?Sized>(&mut self, dev: &mut D, now: u32) -> Result<bool, Mb85rcError> {
+        if !self.pending || now.wrapping_sub(self.last_change) < self.quiet {
+            return Ok(false);
+        }
+        self.flush(dev)?;
+        Ok(true)
+    }
+
+    /// [`poll`](Self::poll) with the time from `clock`
+    pub fn poll_with<D: FramDevice + ?Sized, K: Clock + ?Sized>(&mut self, dev: &mut D, clock: &mut K) -> Result<bool, Mb85rcError> {
+        self.poll(dev, clock.now())
+    }
+
+    /// Write the current value now
+    pub fn flush<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        let mut buf = [0u8; N];
+        let len = self.codec.encode(&self.value, &mut buf)?;
+        dev.write_at(self.addr, &buf[..len])?;
+        self.pending = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Bytes of address and length in front of each run in a delta
+const RUN_HEADER: usize = 6;
+
+/// Work out the delta that turns `old` into `new`, writing it to `out` and returning its length
+///
+/// A delta is a list of runs, each a little-endian `u32` address, a `u16`
+/// length and that many bytes of new data. Changed bytes separated by fewer
+/// unchanged bytes than a run header costs are merged into one run. Bytes of
+/// `new` past the end of `old` always count as changed. Fails with
+/// [`ErrorKind::BufferTooSmall`] if `out` can't hold the delta.
+pub fn make_delta(old: &[u8], new: &[u8], out: &mut [u8]) -> Result<usize, Mb85rcError> {
+    let changed = |i: usize| old.get(i) != Some(&new[i]);
+    let mut len = 0;
+    let mut i = 0;
+    while i < new.len() {
+        if !changed(i) {
+            i += 1;
+            continue;
+        }
+
+        // extend the run while the next change is close enough to be worth merging
+        let start = i;
+        let mut end = i + 1;
+        let limit = new.len().min(start + u16::MAX as usize);
+        let mut j = end;
+        while j < limit && j - end < RUN_HEADER {
+            if changed(j) {
+                end = j + 1;
+            }
+            j += 1;
+        }
+
+        let run = &new[start..end];
+        let dst = out.get_mut(len..len + RUN_HEADER + run.len()).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        let addr = u32::try_from(start).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+        dst[0..4].copy_from_slice(&addr.to_le_bytes());
+        dst[4..6].copy_from_slice(&(run.len() as u16).to_le_bytes());
+        dst[RUN_HEADER..].copy_from_slice(run);
+        len += dst.len();
+        i = end;
+    }
+    Ok(len)
+}
+
+/// Split the run at the front of `delta` into its address, data and whatever follows
+fn next_run(delta: &[u8]) -> Result<(u32, &[u8], &[u8]), Mb85rcError> {
+    let invalid = || Mb85rcError::new(ErrorKind::InvalidData);
+    let header = delta.get(..RUN_HEADER).ok_or_else(invalid)?;
+    let addr = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    let len = u16::from_le_bytes([header[4], header[5]]) as usize;
+    let data = delta.get(RUN_HEADER..RUN_HEADER + len).ok_or_else(invalid)?;
+    Ok((addr, data, &delta[RUN_HEADER + len..]))
+}
+
+/// Apply a delta from [`make_delta`] to `dev`, returning the number of bytes written
+///
+/// Only the changed runs go over the bus, which is much quicker than writing
+/// a whole image when provisioning from a golden image that's nearly
+/// identical. The whole delta is checked first; a malformed one fails with
+/// [`ErrorKind::InvalidData`], or [`ErrorKind::OutOfRange`] if it reaches
+/// past the end of `dev`, without writing anything.
+pub fn apply_delta<D: FramDevice + ?Sized>(dev: &mut D, delta: &[u8]) -> Result<u32, Mb85rcError> {
+    let mut rest = delta;
+    while !rest.is_empty() {
+        let (addr, data, next) = next_run(rest)?;
+        if addr as u64 + data.len() as u64 > dev.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        rest = next;
+    }
+
+    let mut written = 0;
+    let mut rest = delta;
+    while !rest.is_empty() {
+        let (addr, data, next) = next_run(rest)?;
+        dev.write_at(addr, data)?;
+        written += data.len() as u32;
+        rest = next;
+    }
+    Ok(written)
+}
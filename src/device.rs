@@ -0,0 +1,70 @@
+use embedded_hal::blocking::i2c;
+use core::error::Error;
+
+use crate::{MB85RC, Mb85rcError, WriteProtect};
+
+/// Byte-addressed FRAM storage
+///
+/// The trait is object safe, so different parts, simulators and wrappers can
+/// sit behind a `&mut dyn FramDevice` (or `Box<dyn FramDevice>` on `std`).
+/// Storage helpers in this crate work on any `FramDevice`.
+pub trait FramDevice {
+    /// Size of the device in bytes
+    fn capacity(&self) -> u32;
+
+    /// Fill `buf` with the bytes starting at `addr`
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError>;
+
+    /// Store `buf` starting at `addr`
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError>;
+}
+
+impl<I2C, WP: WriteProtect> FramDevice for MB85RC<I2C, WP>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    fn capacity(&self) -> u32 {
+        self.fram_size()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        let addr = self.address(addr)?;
+        self.fram_read(addr, buf).map(|_| ())
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        let addr = self.address(addr)?;
+        self.fram_write(addr, buf).map(|_| ())
+    }
+}
+
+impl<D: FramDevice + ?Sized> FramDevice for &mut D {
+    fn capacity(&self) -> u32 {
+        (**self).capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        (**self).read_at(addr, buf)
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        (**self).write_at(addr, buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: FramDevice + ?Sized> FramDevice for std::boxed::Box<D> {
+    fn capacity(&self) -> u32 {
+        (**self).capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        (**self).read_at(addr, buf)
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        (**self).write_at(addr, buf)
+    }
+}
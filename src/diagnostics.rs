@@ -0,0 +1,32 @@
+use crate::{DeviceId, ErrorKind, Health};
+
+/// Everything worth knowing about a device when something goes wrong, in one place
+///
+/// Get one from [`MB85RC::diagnostics`](crate::MB85RC::diagnostics), or from
+/// [`HealthMonitor::diagnostics`](crate::HealthMonitor::diagnostics) to have
+/// the health fields filled in as well. With the `serde` feature it can be
+/// serialized straight into a support bundle or bug report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// The device ID, or `None` if the device didn't give one
+    pub device_id: Option<DeviceId>,
+    /// The I2C address in use
+    pub address: u8,
+    /// The detected or configured size in bytes
+    pub size: u32,
+    /// The configured max transfer size in bytes
+    pub max_transfer: usize,
+    /// How many times a transaction is retried after losing arbitration, 0 if disabled
+    pub arbitration_retries: u8,
+    /// How healthy the device is, if it's being monitored
+    pub health: Option<Health>,
+    /// Failed accesses since the last success
+    pub consecutive_failures: u32,
+    /// Failed accesses in total
+    pub failures: u32,
+    /// CRC errors reported
+    pub crc_errors: u32,
+    /// The most recent error
+    pub last_error: Option<ErrorKind>,
+}
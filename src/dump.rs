@@ -0,0 +1,159 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::{self, Crc32};
+
+/// Identifies a complete dump, "DUMP"
+const MAGIC: u32 = u32::from_le_bytes(*b"DUMP");
+/// Bytes of magic, length and CRC at the start of the region
+const HEADER: u32 = 12;
+/// Bytes of tag and length in front of each section
+const SECTION_HEADER: u32 = 4;
+
+/// One section of a stored crash dump
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DumpSection {
+    /// What the section holds, see the constants on [`CrashDump`]
+    pub tag: u16,
+    /// Length of the section's data in bytes
+    pub len: u16,
+    addr: u32,
+}
+
+/// A region reserved for post-mortem data
+///
+/// A fault handler calls [`begin`](Self::begin) and streams tagged sections
+/// (registers, a slice of stack, a heap summary) into the region. Nothing is
+/// allocated, and sections that don't fit are cut short instead of running
+/// past the region. The header is written last by
+/// [`CrashWriter::finish`], so a dump cut short by a second fault or a reset
+/// never reads as valid. On the next boot [`read`](Self::read) and
+/// [`sections`](Self::sections) get it back out. The handle only records
+/// where the region is; pass the device to each call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashDump {
+    region: Range<u32>,
+}
+
+impl CrashDump {
+    /// Section tag for saved registers
+    pub const REGISTERS: u16 = 1;
+    /// Section tag for a copy of the stack
+    pub const STACK: u16 = 2;
+    /// Section tag for a heap summary
+    pub const HEAP: u16 = 3;
+
+    /// Use `region` for dumps
+    pub fn new(region: Range<u32>) -> Result<Self, Mb85rcError> {
+        if region.end < region.start || region.end - region.start < HEADER {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok(Self { region })
+    }
+
+    /// Start a new dump, throwing away the old one
+    pub fn begin<'a, D: FramDevice + ?Sized>(&self, dev: &'a mut D) -> Result<CrashWriter<'a, D>, Mb85rcError> {
+        self.clear(dev)?;
+        Ok(CrashWriter { dev, start: self.region.start, pos: self.region.start + HEADER, end: self.region.end, crc: Crc32::new() })
+    }
+
+    /// Length of the stored dump, if there is a complete one
+    pub fn read<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<u32>, Mb85rcError> {
+        let mut header = [0u8; HEADER as usize];
+        dev.read_at(self.region.start, &mut header)?;
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let len = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if magic != MAGIC || len > self.region.end - self.region.start - HEADER {
+            return Ok(None);
+        }
+
+        let mut crc = Crc32::new();
+        crc::update_from_device(&mut crc, dev, self.region.start + HEADER, len)?;
+        Ok((crc.finish() == u32::from_le_bytes(header[8..12].try_into().unwrap())).then_some(len))
+    }
+
+    /// The sections of the stored dump, in the order they were written
+    ///
+    /// Fails with [`ErrorKind::NotFound`] if there isn't a complete dump.
+    pub fn sections<D: FramDevice + ?Sized>(&self, dev: &mut D, out: &mut [DumpSection]) -> Result<usize, Mb85rcError> {
+        let len = self.read(dev)?.ok_or(Mb85rcError::new(ErrorKind::NotFound))?;
+        let end = self.region.start + HEADER + len;
+        let mut pos = self.region.start + HEADER;
+        let mut n = 0;
+        while pos + SECTION_HEADER <= end && n < out.len() {
+            let mut header = [0u8; SECTION_HEADER as usize];
+            dev.read_at(pos, &mut header)?;
+            let section = DumpSection {
+                tag: u16::from_le_bytes([header[0], header[1]]),
+                len: u16::from_le_bytes([header[2], header[3]]),
+                addr: pos + SECTION_HEADER,
+            };
+            out[n] = section;
+            n += 1;
+            pos = section.addr + section.len as u32;
+        }
+        Ok(n)
+    }
+
+    /// Read a section's data into `buf`, returning its length
+    pub fn read_section<D: FramDevice + ?Sized>(&self, dev: &mut D, section: &DumpSection, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let buf = buf.get_mut(..section.len as usize).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        dev.read_at(section.addr, buf)?;
+        Ok(buf.len())
+    }
+
+    /// Forget the stored dump
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        dev.write_at(self.region.start, &[0u8; 4])
+    }
+}
+
+/// Streams a dump into a [`CrashDump`] region, from [`CrashDump::begin`]
+pub struct CrashWriter<'a, D: FramDevice + ?Sized> {
+    dev: &'a mut D,
+    start: u32,
+    pos: u32,
+    end: u32,
+    crc: Crc32,
+}
+
+impl<D: FramDevice + ?Sized> CrashWriter<'_, D> {
+    /// Bytes left for sections, including their headers
+    pub fn remaining(&self) -> u32 {
+        self.end - self.pos
+    }
+
+    /// Add a section, cutting `data` short if it doesn't fit
+    ///
+    /// Returns how many bytes of `data` were stored.
+    pub fn section(&mut self, tag: u16, data: &[u8]) -> Result<usize, Mb85rcError> {
+        if self.remaining() <= SECTION_HEADER {
+            return Ok(0);
+        }
+        let len = data.len().min((self.remaining() - SECTION_HEADER) as usize).min(u16::MAX as usize);
+
+        let mut header = [0u8; SECTION_HEADER as usize];
+        header[0..2].copy_from_slice(&tag.to_le_bytes());
+        header[2..4].copy_from_slice(&(len as u16).to_le_bytes());
+        self.write(&header)?;
+        self.write(&data[..len])?;
+        Ok(len)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Mb85rcError> {
+        self.dev.write_at(self.pos, data)?;
+        self.crc.update(data);
+        self.pos += data.len() as u32;
+        Ok(())
+    }
+
+    /// Seal the dump so it reads back as complete
+    pub fn finish(self) -> Result<(), Mb85rcError> {
+        let mut header = [0u8; HEADER as usize];
+        header[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[4..8].copy_from_slice(&(self.pos - self.start - HEADER).to_le_bytes());
+        header[8..12].copy_from_slice(&self.crc.finish().to_le_bytes());
+        // magic last, so a torn header never looks valid
+        self.dev.write_at(self.start + 4, &header[4..])?;
+        self.dev.write_at(self.start, &header[..4])
+    }
+}
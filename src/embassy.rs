@@ -0,0 +1,348 @@
+use embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice;
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal_async::i2c::{self, Error as _};
+use futures_util::stream::{self, Stream};
+
+use crate::{Builder, Ready, FramAddress, Mb85rcError, ErrorKind, ConnectError, DeviceId, MAX_TRANSFER};
+use crate::addressing::{Addressing, MAX_ADDRESS_BYTES};
+use crate::scan::FRAM_ADDRESSES;
+use crate::{WriteProtect, NoWriteProtect};
+use crate::{SampleLog, AuditLog, AuditRecord};
+
+/// Slave address the device-ID command is sent to
+const DEVICE_ID_ADDR: u8 = 0xF8 >> 1;
+
+/// The async driver on one device of an Embassy shared I2C bus
+///
+/// Made by [`Builder::connect_shared`]. Every transaction locks the bus
+/// mutex by itself, so other drivers on the same bus get a turn in between.
+pub type SharedBusFram<'a, M, BUS, WP = NoWriteProtect> = AsyncMB85RC<I2cDevice<'a, M, BUS>, WP>;
+
+/// Async interface for the FRAM module over an embedded-hal-async I2C bus
+///
+/// The async counterpart of [`MB85RC`](crate::MB85RC), for Embassy and
+/// other async executors. Construct it with
+/// [`Builder::connect_async`] or [`Builder::connect_shared`]; the builder
+/// settings mean the same as for the blocking driver. Transfers are split
+/// the same way, so a long write yields to other tasks between transactions.
+pub struct AsyncMB85RC<I2C, WP = NoWriteProtect> {
+    i2c: I2C,
+    device_addr: u8,
+    device_size: u32,
+    max_transfer: usize,
+    arbitration_retries: u8,
+    addressing: &'static dyn Addressing,
+    write_protect: WP,
+    protected: bool,
+    auto_unprotect: bool,
+}
+
+async fn read_device_id<I2C: i2c::I2c>(i2c: &mut I2C, address: u8) -> Result<[u8; 3], I2C::Error> {
+    let mut id = [0u8; 3];
+    i2c.write_read(DEVICE_ID_ADDR, &[address << 1], &mut id).await?;
+    Ok(id)
+}
+
+impl<I2C: i2c::I2c, WP: WriteProtect> AsyncMB85RC<I2C, WP> {
+    async fn new(mut i2c: I2C, mut config: Builder<Ready, WP>) -> Result<Self, ConnectError<I2C>> {
+        // before anything else touches the bus, so the device is never writable by accident
+        if let Err(e) = config.write_protect.set_protected(config.protected) {
+            return Err(ConnectError::new(e, i2c));
+        }
+
+        let device_addr = if config.discover_address {
+            match Self::discover_address(&mut i2c, config.device_addr, config.addressing).await {
+                Ok(a) => a,
+                Err(e) => return Err(ConnectError::new(e, i2c)),
+            }
+        } else {
+            config.device_addr
+        };
+
+        let id = if config.device_size.is_none() || config.check_id {
+            match read_device_id(&mut i2c, device_addr).await {
+                Ok(v) => Some(DeviceId::from_bytes(v)),
+                Err(_) if config.check_id => return Err(ConnectError::new(Mb85rcError::new(ErrorKind::NoDeviceId), i2c)),
+                Err(_) => return Err(ConnectError::new(Mb85rcError::new(ErrorKind::UnknownSize), i2c)),
+            }
+        } else {
+            None
+        };
+
+        if let Some(id) = id.filter(|id| config.check_id && !id.is_fujitsu()) {
+            return Err(ConnectError::new(Mb85rcError::new(ErrorKind::WrongDevice { found: id.manufacturer() }), i2c));
+        }
+
+        // the ID is always read when no size is given
+        let device_size = config.device_size.unwrap_or_else(|| id.unwrap().size());
+
+        Ok(Self {
+            i2c,
+            device_addr,
+            device_size,
+            max_transfer: config.max_transfer.clamp(1, MAX_TRANSFER),
+            arbitration_retries: config.arbitration_retry.map_or(0, |r| r.retries),
+            addressing: config.addressing,
+            write_protect: config.write_protect,
+            protected: config.protected,
+            auto_unprotect: config.auto_unprotect,
+        })
+    }
+
+    /// Find the FRAM, preferring `preferred` and falling back to the one other address that answers
+    async fn discover_address(i2c: &mut I2C, preferred: u8, addressing: &dyn Addressing) -> Result<u8, Mb85rcError> {
+        // parts without a device ID still answer a plain read, framed the way the
+        // part expects so no address byte is taken for data
+        let mut addr_buf = [0u8; MAX_ADDRESS_BYTES];
+        let n = addressing.address_bytes(0, &mut addr_buf);
+        if read_device_id(i2c, preferred).await.is_ok() || i2c.write_read(preferred, &addr_buf[..n], &mut [0u8]).await.is_ok() {
+            return Ok(preferred);
+        }
+
+        // only the device-ID command is safe to send to whatever else is out there
+        let mut candidates = 0u8;
+        for (i, addr) in FRAM_ADDRESSES.enumerate() {
+            if addr != preferred && read_device_id(i2c, addr).await.is_ok() {
+                candidates |= 1 << i;
+            }
+        }
+
+        if candidates.count_ones() == 1 {
+            Ok(FRAM_ADDRESSES.start() + candidates.trailing_zeros() as u8)
+        } else {
+            Err(Mb85rcError::new(ErrorKind::AddressNotFound { candidates }))
+        }
+    }
+
+    /// Whether a failed transaction should be sent again
+    ///
+    /// embedded-hal 1.0 errors say what went wrong, so lost arbitration is
+    /// recognised without the `is_loss` function the blocking driver needs.
+    fn should_retry(&self, error: &I2C::Error, attempts: u8) -> bool {
+        attempts < self.arbitration_retries && error.kind() == i2c::ErrorKind::ArbitrationLoss
+    }
+
+    fn check_access(&self, addr: FramAddress, len: usize) -> Result<(), Mb85rcError> {
+        match FramAddress::new(addr.get(), self.device_size) {
+            Some(a) if a.fits(len) => Ok(()),
+            _ => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    /// Bytes from `pos` the next transaction can move, at most `remaining`
+    fn transaction_len(&self, pos: u32, remaining: usize) -> usize {
+        remaining.min(self.max_transfer).min(self.addressing.span(pos) as usize)
+    }
+
+    /// Read bytes at `addr` into the provided buffer
+    ///
+    /// Long reads are split into transactions of at most the configured max transfer size
+    pub async fn fram_read(&mut self, addr: FramAddress, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        self.check_access(addr, buf.len())?;
+
+        let mut addr_buf = [0u8; MAX_ADDRESS_BYTES];
+        let mut pos = addr.get();
+        let mut rest = &mut buf[..];
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at_mut(self.transaction_len(pos, rest.len()));
+            let slave = self.addressing.slave_address(self.device_addr, pos);
+            let n = self.addressing.address_bytes(pos, &mut addr_buf);
+            let mut attempts = 0;
+            while let Err(e) = self.i2c.write_read(slave, &addr_buf[..n], chunk).await {
+                if !self.should_retry(&e, attempts) {
+                    return Err(Mb85rcError::i2c(&e));
+                }
+                attempts += 1;
+            }
+            pos += chunk.len() as u32;
+            rest = tail;
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Write bytes at `addr` from the provided buffer
+    ///
+    /// Long writes are split into transactions of at most the configured max
+    /// transfer size. Write protection works the same as for
+    /// [`MB85RC::fram_write`](crate::MB85RC::fram_write).
+    pub async fn fram_write(&mut self, addr: FramAddress, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        self.check_access(addr, buf.len())?;
+        if !self.protected {
+            return self.write_transactions(addr, buf).await;
+        }
+        if !self.auto_unprotect {
+            return Err(Mb85rcError::new(ErrorKind::Locked));
+        }
+
+        self.write_protect.set_protected(false)?;
+        let written = self.write_transactions(addr, buf).await;
+        // protect again even if the write failed
+        let relocked = self.write_protect.set_protected(true);
+        written.and_then(|n| relocked.map(|_| n))
+    }
+
+    async fn write_transactions(&mut self, addr: FramAddress, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        let mut write_buf = [0u8; MAX_ADDRESS_BYTES + MAX_TRANSFER];
+        let mut addr_buf = [0u8; MAX_ADDRESS_BYTES];
+        let mut pos = addr.get();
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at(self.transaction_len(pos, rest.len()));
+            let slave = self.addressing.slave_address(self.device_addr, pos);
+            let n = self.addressing.address_bytes(pos, &mut addr_buf);
+            let len = n + chunk.len();
+            write_buf[..n].copy_from_slice(&addr_buf[..n]);
+            write_buf[n..len].copy_from_slice(chunk);
+            let mut attempts = 0;
+            while let Err(e) = self.i2c.write(slave, &write_buf[..len]).await {
+                if !self.should_retry(&e, attempts) {
+                    return Err(Mb85rcError::i2c(&e));
+                }
+                attempts += 1;
+            }
+            pos += chunk.len() as u32;
+            rest = tail;
+        }
+
+        Ok(buf.len())
+    }
+
+    /// [`fram_read`](Self::fram_read) with a plain address, like [`FramDevice::read_at`](crate::FramDevice::read_at)
+    pub async fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        let addr = self.address(addr)?;
+        self.fram_read(addr, buf).await.map(|_| ())
+    }
+
+    /// [`fram_write`](Self::fram_write) with a plain address, like [`FramDevice::write_at`](crate::FramDevice::write_at)
+    pub async fn write_at(&mut self, addr: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        let addr = self.address(addr)?;
+        self.fram_write(addr, data).await.map(|_| ())
+    }
+
+    /// Raise WP so the device ignores writes
+    ///
+    /// Fails with [`ErrorKind::WrongState`] if there's no WP pin.
+    pub fn protect(&mut self) -> Result<(), Mb85rcError> {
+        self.write_protect.set_protected(true)?;
+        self.protected = true;
+        Ok(())
+    }
+
+    /// Lower WP so the device can be written
+    pub fn unprotect(&mut self) -> Result<(), Mb85rcError> {
+        self.write_protect.set_protected(false)?;
+        self.protected = false;
+        Ok(())
+    }
+
+    /// Read the device ID
+    pub async fn device_id(&mut self) -> Result<DeviceId, Mb85rcError> {
+        read_device_id(&mut self.i2c, self.device_addr).await
+            .map(DeviceId::from_bytes)
+            .map_err(|e| Mb85rcError::i2c(&e))
+    }
+}
+
+impl<I2C: i2c::I2c, WP: WriteProtect> AsyncMB85RC<I2C, WP> {
+    /// Stream the samples in `log` numbered from `from` on, oldest first
+    ///
+    /// Each sample is read from the device as the stream is polled, one
+    /// transaction apiece, so a gateway can feed buffered telemetry straight
+    /// into an async pipeline. Like [`SampleLog::read_range`], samples
+    /// already overwritten are skipped; the stream ends at the newest sample
+    /// `log` knew of, or after the first error. Pass `last + 1` to pick up
+    /// where an earlier stream stopped.
+    pub fn samples<'a, const R: usize>(&'a mut self, log: &'a SampleLog<R>, from: u32) -> impl Stream<Item = Result<(u32, [u8; R]), Mb85rcError>> + 'a {
+        let stored = log.range();
+        let start = from.max(stored.start).min(stored.end);
+        stream::unfold((self, start), move |(fram, n)| async move {
+            if n == stored.end {
+                return None;
+            }
+            let mut sample = [0u8; R];
+            let result = fram.read_at(log.slot_addr(n), &mut sample).await.map(|()| (n, sample));
+            let next = if result.is_ok() { n.wrapping_add(1) } else { stored.end };
+            Some((result, (fram, next)))
+        })
+    }
+
+    /// Stream the records in `log` with sequence numbers from `from` on, oldest first
+    ///
+    /// The audit-log counterpart of [`samples`](Self::samples): records are
+    /// read one at a time as the stream is polled, and a corrupt record
+    /// yields [`ErrorKind::InvalidData`] and ends the stream.
+    pub fn audit_records<'a>(&'a mut self, log: &'a AuditLog, from: u32) -> impl Stream<Item = Result<AuditRecord, Mb85rcError>> + 'a {
+        let stored = log.range();
+        let start = from.max(stored.start).min(stored.end);
+        stream::unfold((self, start), move |(fram, seq)| async move {
+            if seq == stored.end {
+                return None;
+            }
+            let mut raw = [0u8; AuditLog::RECORD_SIZE as usize];
+            let result = match fram.read_at(log.record_addr(seq), &mut raw).await {
+                Ok(()) => AuditRecord::decode(&raw)
+                    .filter(|record| record.seq == seq)
+                    .ok_or(Mb85rcError::new(ErrorKind::InvalidData)),
+                Err(e) => Err(e),
+            };
+            let next = if result.is_ok() { seq.wrapping_add(1) } else { stored.end };
+            Some((result, (fram, next)))
+        })
+    }
+}
+
+impl<I2C, WP> AsyncMB85RC<I2C, WP> {
+    /// Make an address on this device, checking it isn't past the end
+    pub fn address(&self, addr: u32) -> Result<FramAddress, Mb85rcError> {
+        FramAddress::new(addr, self.device_size)
+            .ok_or(Mb85rcError::new(ErrorKind::InvalidAddress))
+    }
+
+    /// Get the auto-detected or [manually set](Builder::with_size) size of the device
+    pub fn fram_size(&self) -> u32 {
+        self.device_size
+    }
+
+    /// Get the largest number of data bytes sent or received in one I2C transaction
+    pub fn max_transfer(&self) -> usize {
+        self.max_transfer
+    }
+
+    /// Deconstruct the interface and hand back the I2C bus and the write-protect pin
+    pub fn release_with_pin(self) -> (I2C, WP) {
+        (self.i2c, self.write_protect)
+    }
+}
+
+impl<I2C> AsyncMB85RC<I2C> {
+    /// Deconstruct the interface and hand back the I2C bus
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<WP: WriteProtect> Builder<Ready, WP> {
+    /// Finish the builder and construct the async interface, handing the bus back if that fails
+    ///
+    /// Arbitration retries recognise a lost arbitration from the error's
+    /// [`ErrorKind`](embedded_hal_async::i2c::ErrorKind), so the `is_loss`
+    /// function given to [`with_arbitration_retry`](Self::with_arbitration_retry)
+    /// isn't used.
+    pub async fn connect_async<I2C: i2c::I2c>(self, i2c: I2C) -> Result<AsyncMB85RC<I2C, WP>, ConnectError<I2C>> {
+        AsyncMB85RC::new(i2c, self).await
+    }
+
+    /// Finish the builder and construct the async interface on an Embassy shared bus
+    ///
+    /// `bus` is the mutex every driver on the bus shares, as in
+    /// [`embassy_embedded_hal::shared_bus::asynch::i2c`].
+    pub async fn connect_shared<'a, M, BUS>(self, bus: &'a Mutex<M, BUS>) -> Result<SharedBusFram<'a, M, BUS, WP>, Mb85rcError>
+    where
+        M: RawMutex,
+        BUS: i2c::I2c,
+    {
+        self.connect_async(I2cDevice::new(bus)).await.map_err(|e| e.error)
+    }
+}
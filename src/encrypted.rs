@@ -0,0 +1,96 @@
+use chacha20::ChaCha20;
+use chacha20::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+
+use crate::{FramDevice, FramAddress, SeekFrom, Mb85rcError, ErrorKind};
+
+/// At-rest encryption for a region, with a cursor
+///
+/// Every byte is XORed with a ChaCha20 keystream positioned by its address,
+/// so reads and writes can land anywhere without touching their
+/// neighbours. Put it around a [`FramSlice`](crate::FramSlice) and code
+/// that already uses `Read`/`Write`/`Seek` (with the `std-io` feature) or
+/// [`FramDevice`] keeps working unchanged.
+///
+/// Use a different nonce for each region sharing a key, for example the
+/// region's start address. This gives confidentiality only: nothing detects
+/// tampering, and since overwriting a byte reuses its keystream, someone
+/// who sees the chip before and after a write learns how the two plaintexts
+/// differ. Combine with [`AuthRegion`](crate::AuthRegion) if that matters.
+pub struct EncryptedStream<D: FramDevice> {
+    dev: D,
+    cipher: ChaCha20,
+    cursor: FramAddress,
+}
+
+impl<D: FramDevice> EncryptedStream<D> {
+    /// Encrypt `dev` with `key` and `nonce`
+    pub fn new(dev: D, key: &[u8; 32], nonce: &[u8; 12]) -> Self {
+        let cursor = FramAddress::new(0, dev.capacity()).unwrap();
+        Self { dev, cipher: ChaCha20::new(key.into(), nonce.into()), cursor }
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    fn apply(&mut self, addr: u32, data: &mut [u8]) {
+        self.cipher.seek(addr as u64);
+        self.cipher.apply_keystream(data);
+    }
+
+    /// Current position of the cursor
+    pub fn position(&self) -> FramAddress {
+        self.cursor
+    }
+
+    /// Move the cursor, returning the new position
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<u32, Mb85rcError> {
+        self.cursor = pos.resolve(self.cursor)?;
+        Ok(self.cursor.get())
+    }
+
+    /// Read and decrypt bytes at the cursor and advance it, stopping at the end
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        self.read_at(self.cursor.get(), &mut buf[..len])?;
+        self.cursor = self.cursor.checked_add(len as u32).unwrap();
+        Ok(len)
+    }
+
+    /// Encrypt and write bytes at the cursor and advance it, stopping at the end
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        self.write_at(self.cursor.get(), &buf[..len])?;
+        self.cursor = self.cursor.checked_add(len as u32).unwrap();
+        Ok(len)
+    }
+}
+
+impl<D: FramDevice> FramDevice for EncryptedStream<D> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.dev.read_at(addr, buf)?;
+        self.apply(addr, buf);
+        Ok(())
+    }
+
+    fn write_at(&mut self, addr: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        if addr as u64 + data.len() as u64 > self.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+
+        let mut buf = [0u8; 64];
+        for (i, chunk) in data.chunks(buf.len()).enumerate() {
+            let pos = addr + (i * buf.len()) as u32;
+            let buf = &mut buf[..chunk.len()];
+            buf.copy_from_slice(chunk);
+            self.apply(pos, buf);
+            self.dev.write_at(pos, buf)?;
+        }
+        Ok(())
+    }
+}
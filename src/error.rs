@@ -0,0 +1,240 @@
+use core::fmt;
+use core::error::Error;
+
+/// What went wrong in a [`Mb85rcError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The I2C bus reported an error
+    I2c,
+    /// An address lies past the end of device memory
+    InvalidAddress,
+    /// An access would run past the end of device memory
+    OutOfRange,
+    /// A seek would move the cursor outside of device memory
+    InvalidSeek,
+    /// The device size couldn't be read from the device ID
+    UnknownSize,
+    /// Nothing answered at the configured address during address discovery
+    ///
+    /// Bit `n` of `candidates` is set if an FRAM answered at `0x50 + n` instead
+    AddressNotFound {
+        /// Addresses that did answer, as a bitmask
+        candidates: u8,
+    },
+    /// The device didn't answer the device-ID command
+    NoDeviceId,
+    /// The device ID has a manufacturer code other than Fujitsu/RAMXEED
+    WrongDevice {
+        /// The manufacturer code that was read
+        found: u16,
+    },
+    /// A batch has no room left for another write
+    BatchFull,
+    /// A caller-provided buffer is too small for the operation
+    BufferTooSmall,
+    /// A batch commit failed and the original contents were restored
+    RolledBack,
+    /// A batch commit failed and restoring the original contents failed too
+    RollbackFailed,
+    /// Two ranges that must be separate overlap
+    Overlap,
+    /// The requested item isn't stored
+    NotFound,
+    /// Data is too large for the space it's meant to go in
+    TooLarge,
+    /// Stored data is corrupt or couldn't be decoded
+    InvalidData,
+    /// There is no free space left
+    Full,
+    /// The operation isn't allowed in the current state
+    WrongState,
+    /// Stored data failed its authentication check
+    AuthFailed,
+    /// The region is locked
+    Locked,
+    /// Writing formatted output failed
+    Format,
+    /// The device has been marked as failed
+    Failed,
+    /// The write-protect pin couldn't be driven
+    Pin,
+}
+
+impl ErrorKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::I2c => "I2C Error",
+            ErrorKind::InvalidAddress => "Address is past the end of device memory",
+            ErrorKind::OutOfRange => "Access would run past the end of device memory",
+            ErrorKind::InvalidSeek => "Cannot seek outside of device memory",
+            ErrorKind::UnknownSize => "Could not automatically get FRAM size. Use `Builder::with_size(u32)`.",
+            ErrorKind::AddressNotFound { .. } => "No FRAM at the configured address",
+            ErrorKind::NoDeviceId => "Device did not answer the device-ID command",
+            ErrorKind::WrongDevice { .. } => "Device is not a Fujitsu/RAMXEED FRAM",
+            ErrorKind::BatchFull => "Batch has no room for another write",
+            ErrorKind::BufferTooSmall => "Buffer is too small",
+            ErrorKind::RolledBack => "Commit failed and was rolled back",
+            ErrorKind::RollbackFailed => "Commit failed and could not be rolled back",
+            ErrorKind::Overlap => "Ranges overlap",
+            ErrorKind::NotFound => "Not found",
+            ErrorKind::TooLarge => "Data is too large",
+            ErrorKind::InvalidData => "Stored data is invalid",
+            ErrorKind::Full => "No free space left",
+            ErrorKind::WrongState => "Not allowed in the current state",
+            ErrorKind::AuthFailed => "Authentication check failed",
+            ErrorKind::Locked => "Region is locked",
+            ErrorKind::Format => "Could not write formatted output",
+            ErrorKind::Failed => "Device has been marked as failed",
+            ErrorKind::Pin => "Could not drive the write-protect pin",
+        }
+    }
+}
+
+/// Longest bus error text an [`Mb85rcError`] keeps
+const DETAIL_LEN: usize = 48;
+
+/// Error type for anything that might happen on the I2C side of things
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Mb85rcError {
+    kind: ErrorKind,
+    detail: [u8; DETAIL_LEN],
+    detail_len: u8,
+}
+
+impl Mb85rcError {
+    pub(crate) fn new(kind: ErrorKind) -> Mb85rcError {
+        Mb85rcError { kind, detail: [0; DETAIL_LEN], detail_len: 0 }
+    }
+
+    /// An [`ErrorKind::I2c`] error keeping the `Debug` text of the bus error `e`
+    ///
+    /// Text past the first 48 bytes is cut off.
+    pub(crate) fn i2c(e: &impl fmt::Debug) -> Mb85rcError {
+        let mut error = Self::new(ErrorKind::I2c);
+        let mut text = Detail { buf: &mut error.detail, len: 0 };
+        // running out of room is the only way this fails, and what fitted is kept
+        let _ = fmt::write(&mut text, format_args!("{:?}", e));
+        error.detail_len = text.len as u8;
+        error
+    }
+
+    /// What kind of error this is
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The `Debug` text of the bus error behind an [`ErrorKind::I2c`] error, if there was one
+    pub fn detail(&self) -> Option<&str> {
+        match self.detail_len {
+            0 => None,
+            // only whole characters are ever copied in
+            len => core::str::from_utf8(&self.detail[..len as usize]).ok(),
+        }
+    }
+}
+
+/// Writer that fills a fixed buffer with as many whole characters as fit
+struct Detail<'a> {
+    buf: &'a mut [u8; DETAIL_LEN],
+    len: usize,
+}
+
+impl fmt::Write for Detail<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let end = self.len + c.len_utf8();
+            if end > DETAIL_LEN {
+                return Err(fmt::Error);
+            }
+            c.encode_utf8(&mut self.buf[self.len..end]);
+            self.len = end;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Mb85rcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("Mb85rcError");
+        s.field("kind", &self.kind);
+        if let Some(detail) = self.detail() {
+            s.field("detail", &detail);
+        }
+        s.finish()
+    }
+}
+
+impl From<ErrorKind> for Mb85rcError {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl fmt::Display for Mb85rcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind.as_str())?;
+
+        if let Some(detail) = self.detail() {
+            write!(f, ": {}", detail)?;
+        }
+
+        if let ErrorKind::WrongDevice { found } = self.kind {
+            write!(f, " (manufacturer ID 0x{:03X})", found)?;
+        }
+
+        if let ErrorKind::AddressNotFound { candidates } = self.kind {
+            if candidates == 0 {
+                write!(f, " (no other FRAM answered)")?;
+            } else {
+                write!(f, " (FRAM answered at")?;
+                for n in (0..8).filter(|n| candidates & (1 << n) != 0) {
+                    write!(f, " 0x{:02X}", 0x50 + n)?;
+                }
+                write!(f, ")")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for Mb85rcError {
+    fn description(&self) -> &str {
+        self.kind.as_str()
+    }
+}
+
+/// Failure to construct the interface, giving the bus back
+pub struct ConnectError<I2C> {
+    /// What went wrong
+    pub error: Mb85rcError,
+    /// The bus that was passed in
+    pub i2c: I2C,
+}
+
+impl<I2C> ConnectError<I2C> {
+    pub(crate) fn new(error: Mb85rcError, i2c: I2C) -> Self {
+        Self { error, i2c }
+    }
+}
+
+impl<I2C> From<ConnectError<I2C>> for Mb85rcError {
+    fn from(e: ConnectError<I2C>) -> Self {
+        e.error
+    }
+}
+
+impl<I2C> fmt::Debug for ConnectError<I2C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConnectError").field("error", &self.error).finish_non_exhaustive()
+    }
+}
+
+impl<I2C> fmt::Display for ConnectError<I2C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl<I2C> Error for ConnectError<I2C> {}
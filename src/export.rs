@@ -0,0 +1,480 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::ops::Range;
+
+use crate::{AuditLog, AuditRecord, FramDevice, SampleLog};
+
+/// What [`Exporter`] writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// A header line of field names, then one comma-separated line per record
+    Csv,
+    /// An array of objects, one per line, keyed by field name
+    Json,
+}
+
+/// How a [`Field`] is stored in a sample
+///
+/// Numbers are little-endian, like everything else in this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    /// Unsigned byte
+    U8,
+    /// Unsigned 16-bit integer
+    U16,
+    /// Unsigned 32-bit integer
+    U32,
+    /// Unsigned 64-bit integer
+    U64,
+    /// Signed byte
+    I8,
+    /// Signed 16-bit integer
+    I16,
+    /// Signed 32-bit integer
+    I32,
+    /// Signed 64-bit integer
+    I64,
+    /// Single-precision float
+    F32,
+    /// Double-precision float
+    F64,
+    /// Raw bytes, written out as hex
+    Bytes(usize),
+}
+
+impl FieldType {
+    /// Bytes the field takes up in a sample
+    pub const fn size(self) -> usize {
+        match self {
+            FieldType::U8 | FieldType::I8 => 1,
+            FieldType::U16 | FieldType::I16 => 2,
+            FieldType::U32 | FieldType::I32 | FieldType::F32 => 4,
+            FieldType::U64 | FieldType::I64 | FieldType::F64 => 8,
+            FieldType::Bytes(n) => n,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Value {
+        macro_rules! le {
+            ($t:ty) => { <$t>::from_le_bytes(bytes.try_into().unwrap()) };
+        }
+        match self {
+            FieldType::U8 => Value::UInt(bytes[0] as u64),
+            FieldType::U16 => Value::UInt(le!(u16) as u64),
+            FieldType::U32 => Value::UInt(le!(u32) as u64),
+            FieldType::U64 => Value::UInt(le!(u64)),
+            FieldType::I8 => Value::Int(bytes[0] as i8 as i64),
+            FieldType::I16 => Value::Int(le!(i16) as i64),
+            FieldType::I32 => Value::Int(le!(i32) as i64),
+            FieldType::I64 => Value::Int(le!(i64)),
+            FieldType::F32 => Value::Float32(le!(f32)),
+            FieldType::F64 => Value::Float(le!(f64)),
+            FieldType::Bytes(_) => Value::Bytes(bytes.to_vec()),
+        }
+    }
+}
+
+/// One named field of a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Field {
+    /// Column name in the output
+    pub name: &'static str,
+    /// How it's stored
+    pub ty: FieldType,
+}
+
+impl Field {
+    /// A field called `name` stored as `ty`
+    pub const fn new(name: &'static str, ty: FieldType) -> Self {
+        Self { name, ty }
+    }
+}
+
+/// Writes decoded log records as CSV or JSON, for handing field data to analysts
+///
+/// The `export_*` methods read records straight off the device: samples from
+/// a [`SampleLog`] are split into columns by a schema of [`Field`]s laid out
+/// back to back, and [`AuditLog`] records have their own columns. Each
+/// starts with a `seq` column holding the record's number. With the `serde`
+/// feature, `export_serde` writes any flat
+/// `Serialize` struct, named by its serde field names, for records decoded
+/// some other way. Everything written goes in the same table, so stick to
+/// one kind of record per exporter, and call [`finish`](Self::finish) at
+/// the end.
+pub struct Exporter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    columns: Option<Vec<String>>,
+    rows: u64,
+}
+
+impl<W: Write> Exporter<W> {
+    /// Write to `out` in `format`
+    pub fn new(out: W, format: ExportFormat) -> Self {
+        Self { out, format, columns: None, rows: 0 }
+    }
+
+    /// How many records have been written
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Write the samples of `log` numbered in `range`, split into columns by `schema`, returning how many were written
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if `schema` is bigger than
+    /// a sample; bytes past its end are ignored.
+    pub fn export_samples<D: FramDevice + ?Sized, const R: usize>(&mut self, dev: &mut D, log: &SampleLog<R>, range: Range<u32>, schema: &[Field]) -> io::Result<u32> {
+        if schema.iter().map(|f| f.ty.size()).sum::<usize>() > R {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "schema is bigger than a sample"));
+        }
+        let names: Vec<&str> = schema.iter().map(|f| f.name).collect();
+        self.set_columns(&names)?;
+
+        let mut buf = vec![0u8; R * 64];
+        let mut next = range.start;
+        let mut count = 0;
+        loop {
+            let read = log.read_range(dev, next..range.end, &mut buf).map_err(io::Error::other)?;
+            if read.is_empty() {
+                return Ok(count);
+            }
+            for (seq, sample) in read.clone().zip(buf.chunks(R)) {
+                let mut values = Vec::with_capacity(schema.len());
+                let mut offset = 0;
+                for field in schema {
+                    values.push(field.ty.decode(&sample[offset..offset + field.ty.size()]));
+                    offset += field.ty.size();
+                }
+                self.write_row(seq, &values)?;
+            }
+            count += read.len() as u32;
+            next = read.end;
+        }
+    }
+
+    /// Write the records of `log` with sequence numbers in `range`, returning how many were written
+    pub fn export_audit<D: FramDevice + ?Sized>(&mut self, dev: &mut D, log: &AuditLog, range: Range<u32>) -> io::Result<u32> {
+        self.set_columns(&["addr", "len", "time", "tag"])?;
+
+        let mut records = [AuditRecord { seq: 0, addr: 0, len: 0, time: 0, tag: 0 }; 32];
+        let mut next = range.start;
+        let mut count = 0;
+        loop {
+            let read = log.read_range(dev, next..range.end, &mut records).map_err(io::Error::other)?;
+            if read.is_empty() {
+                return Ok(count);
+            }
+            for record in &records[..read.len()] {
+                let values = [record.addr, record.len, record.time, record.tag].map(|v| Value::UInt(v as u64));
+                self.write_row(record.seq, &values)?;
+            }
+            count += read.len() as u32;
+            next = read.end;
+        }
+    }
+
+    /// Write `record` numbered `seq`, with columns named after its serde fields
+    ///
+    /// `record` has to serialize as a struct or map of plain values; nested
+    /// structs and sequences fail with [`io::ErrorKind::InvalidInput`].
+    /// Byte strings are written as hex and `None` as an empty field.
+    #[cfg(feature = "serde")]
+    pub fn export_serde<T: serde::Serialize + ?Sized>(&mut self, seq: u32, record: &T) -> io::Result<()> {
+        let mut fields = Vec::new();
+        record.serialize(flatten::RowSerializer(&mut fields))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.0))?;
+        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        self.set_columns(&names)?;
+        let values: Vec<Value> = fields.into_iter().map(|(_, value)| value).collect();
+        self.write_row(seq, &values)
+    }
+
+    /// End the output and give back the writer
+    pub fn finish(mut self) -> io::Result<W> {
+        match (self.format, self.rows) {
+            (ExportFormat::Json, 0) => writeln!(self.out, "[]")?,
+            (ExportFormat::Json, _) => writeln!(self.out, "\n]")?,
+            // a header with no rows is still useful
+            (ExportFormat::Csv, _) => {}
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn set_columns(&mut self, names: &[&str]) -> io::Result<()> {
+        let columns: Vec<String> = std::iter::once("seq").chain(names.iter().copied()).map(String::from).collect();
+        match &self.columns {
+            Some(existing) if *existing == columns => return Ok(()),
+            Some(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, "records don't match the columns already written")),
+            None => {}
+        }
+        if self.format == ExportFormat::Csv {
+            let header: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+            writeln!(self.out, "{}", header.join(","))?;
+        }
+        self.columns = Some(columns);
+        Ok(())
+    }
+
+    fn write_row(&mut self, seq: u32, values: &[Value]) -> io::Result<()> {
+        let columns = self.columns.as_ref().unwrap();
+        let mut line = String::new();
+        match self.format {
+            ExportFormat::Csv => {
+                line.push_str(&seq.to_string());
+                for value in values {
+                    line.push(',');
+                    line.push_str(&csv_field(&value.to_text()));
+                }
+                line.push('\n');
+            }
+            ExportFormat::Json => {
+                line.push_str(if self.rows == 0 { "[\n" } else { ",\n" });
+                write!(line, "{{\"seq\":{}", seq).unwrap();
+                for (name, value) in columns[1..].iter().zip(values) {
+                    write!(line, ",{}:{}", json_string(name), value.to_json()).unwrap();
+                }
+                line.push('}');
+            }
+        }
+        self.out.write_all(line.as_bytes())?;
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+/// A decoded field
+#[derive(Debug, Clone, PartialEq)]
+// only serde records have the non-numeric ones
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+enum Value {
+    None,
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    // kept apart so it prints without f64 rounding noise
+    Float32(f32),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    fn to_text(&self) -> String {
+        match self {
+            Value::None => String::new(),
+            Value::Bool(b) => b.to_string(),
+            Value::UInt(n) => n.to_string(),
+            Value::Int(n) => n.to_string(),
+            Value::Float32(x) => x.to_string(),
+            Value::Float(x) => x.to_string(),
+            Value::Str(s) => s.clone(),
+            Value::Bytes(bytes) => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        match self {
+            Value::None => "null".into(),
+            // JSON has no NaN or infinity
+            Value::Float32(x) if !x.is_finite() => "null".into(),
+            Value::Float(x) if !x.is_finite() => "null".into(),
+            Value::Str(_) | Value::Bytes(_) => json_string(&self.to_text()),
+            _ => self.to_text(),
+        }
+    }
+}
+
+/// `s` quoted if it has to be
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.into(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Flattens a `Serialize` struct into named [`Value`]s
+#[cfg(feature = "serde")]
+mod flatten {
+    use std::fmt;
+
+    use serde::ser::{self, Impossible, Serialize};
+
+    use super::Value;
+
+    #[derive(Debug)]
+    pub(super) struct SerError(pub(super) String);
+
+    impl fmt::Display for SerError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for SerError {}
+
+    impl ser::Error for SerError {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            SerError(msg.to_string())
+        }
+    }
+
+    fn unsupported<T>(what: &str) -> Result<T, SerError> {
+        Err(SerError(format!("can't export {} as a field", what)))
+    }
+
+    /// Serializes a whole record: a struct or a map
+    pub(super) struct RowSerializer<'a>(pub(super) &'a mut Vec<(String, Value)>);
+
+    pub(super) struct Fields<'a> {
+        fields: &'a mut Vec<(String, Value)>,
+        key: Option<String>,
+    }
+
+    impl ser::SerializeStruct for Fields<'_> {
+        type Ok = ();
+        type Error = SerError;
+
+        fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerError> {
+            self.fields.push((key.into(), value.serialize(ValueSerializer)?));
+            Ok(())
+        }
+
+        fn end(self) -> Result<(), SerError> {
+            Ok(())
+        }
+    }
+
+    impl ser::SerializeMap for Fields<'_> {
+        type Ok = ();
+        type Error = SerError;
+
+        fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerError> {
+            self.key = Some(key.serialize(ValueSerializer)?.to_text());
+            Ok(())
+        }
+
+        fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+            let key = self.key.take().unwrap_or_default();
+            self.fields.push((key, value.serialize(ValueSerializer)?));
+            Ok(())
+        }
+
+        fn end(self) -> Result<(), SerError> {
+            Ok(())
+        }
+    }
+
+    impl<'a> ser::Serializer for RowSerializer<'a> {
+        type Ok = ();
+        type Error = SerError;
+        type SerializeSeq = Impossible<(), SerError>;
+        type SerializeTuple = Impossible<(), SerError>;
+        type SerializeTupleStruct = Impossible<(), SerError>;
+        type SerializeTupleVariant = Impossible<(), SerError>;
+        type SerializeMap = Fields<'a>;
+        type SerializeStruct = Fields<'a>;
+        type SerializeStructVariant = Impossible<(), SerError>;
+
+        fn serialize_struct(self, _: &'static str, _: usize) -> Result<Fields<'a>, SerError> {
+            Ok(Fields { fields: self.0, key: None })
+        }
+
+        fn serialize_map(self, _: Option<usize>) -> Result<Fields<'a>, SerError> {
+            Ok(Fields { fields: self.0, key: None })
+        }
+
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), SerError> {
+            value.serialize(self)
+        }
+
+        fn serialize_bool(self, _: bool) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_i8(self, _: i8) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_i16(self, _: i16) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_i32(self, _: i32) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_i64(self, _: i64) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_u8(self, _: u8) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_u16(self, _: u16) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_u32(self, _: u32) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_u64(self, _: u64) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_f32(self, _: f32) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_f64(self, _: f64) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_char(self, _: char) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_str(self, _: &str) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_bytes(self, _: &[u8]) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_none(self) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_unit(self) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_unit_struct(self, _: &'static str) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), SerError> { unsupported("a bare value") }
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<(), SerError> { unsupported("an enum") }
+        fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, SerError> { unsupported("a sequence") }
+        fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, SerError> { unsupported("a tuple") }
+        fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, SerError> { unsupported("a tuple struct") }
+        fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, SerError> { unsupported("an enum") }
+        fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, SerError> { unsupported("an enum") }
+        fn collect_str<T: fmt::Display + ?Sized>(self, _: &T) -> Result<(), SerError> { unsupported("a bare value") }
+    }
+
+    /// Serializes one field's value
+    struct ValueSerializer;
+
+    impl ser::Serializer for ValueSerializer {
+        type Ok = Value;
+        type Error = SerError;
+        type SerializeSeq = Impossible<Value, SerError>;
+        type SerializeTuple = Impossible<Value, SerError>;
+        type SerializeTupleStruct = Impossible<Value, SerError>;
+        type SerializeTupleVariant = Impossible<Value, SerError>;
+        type SerializeMap = Impossible<Value, SerError>;
+        type SerializeStruct = Impossible<Value, SerError>;
+        type SerializeStructVariant = Impossible<Value, SerError>;
+
+        fn serialize_bool(self, v: bool) -> Result<Value, SerError> { Ok(Value::Bool(v)) }
+        fn serialize_i8(self, v: i8) -> Result<Value, SerError> { Ok(Value::Int(v as i64)) }
+        fn serialize_i16(self, v: i16) -> Result<Value, SerError> { Ok(Value::Int(v as i64)) }
+        fn serialize_i32(self, v: i32) -> Result<Value, SerError> { Ok(Value::Int(v as i64)) }
+        fn serialize_i64(self, v: i64) -> Result<Value, SerError> { Ok(Value::Int(v)) }
+        fn serialize_u8(self, v: u8) -> Result<Value, SerError> { Ok(Value::UInt(v as u64)) }
+        fn serialize_u16(self, v: u16) -> Result<Value, SerError> { Ok(Value::UInt(v as u64)) }
+        fn serialize_u32(self, v: u32) -> Result<Value, SerError> { Ok(Value::UInt(v as u64)) }
+        fn serialize_u64(self, v: u64) -> Result<Value, SerError> { Ok(Value::UInt(v)) }
+        fn serialize_f32(self, v: f32) -> Result<Value, SerError> { Ok(Value::Float32(v)) }
+        fn serialize_f64(self, v: f64) -> Result<Value, SerError> { Ok(Value::Float(v)) }
+        fn serialize_char(self, v: char) -> Result<Value, SerError> { Ok(Value::Str(v.to_string())) }
+        fn serialize_str(self, v: &str) -> Result<Value, SerError> { Ok(Value::Str(v.into())) }
+        fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerError> { Ok(Value::Bytes(v.into())) }
+        fn serialize_none(self) -> Result<Value, SerError> { Ok(Value::None) }
+        fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerError> { value.serialize(self) }
+        fn serialize_unit(self) -> Result<Value, SerError> { Ok(Value::None) }
+        fn serialize_unit_struct(self, _: &'static str) -> Result<Value, SerError> { Ok(Value::None) }
+        fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<Value, SerError> { Ok(Value::Str(variant.into())) }
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<Value, SerError> { value.serialize(self) }
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<Value, SerError> { unsupported("an enum with data") }
+        fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, SerError> { unsupported("a sequence") }
+        fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, SerError> { unsupported("a tuple") }
+        fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, SerError> { unsupported("a tuple struct") }
+        fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, SerError> { unsupported("an enum with data") }
+        fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, SerError> { unsupported("a nested map") }
+        fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, SerError> { unsupported("a nested struct") }
+        fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, SerError> { unsupported("an enum with data") }
+        fn collect_str<T: fmt::Display + ?Sized>(self, value: &T) -> Result<Value, SerError> { Ok(Value::Str(value.to_string())) }
+    }
+}
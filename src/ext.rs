@@ -0,0 +1,252 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError, ErrorKind, MAX_TRANSFER};
+
+/// Convenience operations available on every [`FramDevice`]
+///
+/// Implemented for all devices, including `dyn FramDevice`; bring it into
+/// scope with `use mb85rc::FramExt`.
+pub trait FramExt: FramDevice {
+    /// Read `N` bytes at `addr`, let `f` change them, and write back whatever changed
+    ///
+    /// Nothing is written if `f` leaves the bytes alone; otherwise only the
+    /// span from the first to the last changed byte goes back to the
+    /// device. Returns whatever `f` returns.
+    fn update<const N: usize, R>(&mut self, addr: u32, f: impl FnOnce(&mut [u8; N]) -> R) -> Result<R, Mb85rcError> {
+        let mut buf = [0u8; N];
+        self.read_at(addr, &mut buf)?;
+        let old = buf;
+        let result = f(&mut buf);
+
+        let first = buf.iter().zip(old.iter()).position(|(a, b)| a != b);
+        let last = buf.iter().zip(old.iter()).rposition(|(a, b)| a != b);
+        if let (Some(first), Some(last)) = (first, last) {
+            self.write_at(addr + first as u32, &buf[first..=last])?;
+        }
+        Ok(result)
+    }
+
+    /// Write `new` at `addr` only if the bytes there currently equal `expected`
+    ///
+    /// Returns whether the swap happened. The read and the write happen under
+    /// the same `&mut` borrow, so nothing else going through this handle can
+    /// sneak in between; other bus masters have to agree on a lock convention
+    /// for it to mean anything to them.
+    fn compare_and_swap<const N: usize>(&mut self, addr: u32, expected: &[u8; N], new: &[u8; N]) -> Result<bool, Mb85rcError> {
+        let mut current = [0u8; N];
+        self.read_at(addr, &mut current)?;
+        if current != *expected {
+            return Ok(false);
+        }
+        if expected != new {
+            self.write_at(addr, new)?;
+        }
+        Ok(true)
+    }
+
+    /// Set the bit at `bit_addr`, which is bit `bit_addr % 8` of byte `bit_addr / 8`
+    ///
+    /// Only the byte holding the bit is read and, if it changes, rewritten.
+    /// For a bit array that doesn't start at address 0, see
+    /// [`FramBitmap`](crate::FramBitmap).
+    fn set_bit(&mut self, bit_addr: u32) -> Result<(), Mb85rcError> {
+        self.update(bit_addr / 8, |b: &mut [u8; 1]| b[0] |= 1 << (bit_addr % 8))
+    }
+
+    /// Clear the bit at `bit_addr`
+    fn clear_bit(&mut self, bit_addr: u32) -> Result<(), Mb85rcError> {
+        self.update(bit_addr / 8, |b: &mut [u8; 1]| b[0] &= !(1 << (bit_addr % 8)))
+    }
+
+    /// Whether the bit at `bit_addr` is set
+    fn test_bit(&mut self, bit_addr: u32) -> Result<bool, Mb85rcError> {
+        let mut byte = [0u8];
+        self.read_at(bit_addr / 8, &mut byte)?;
+        Ok(byte[0] & (1 << (bit_addr % 8)) != 0)
+    }
+
+    /// Address of the first occurrence of `needle` within `range`
+    ///
+    /// The range is read in [`MAX_TRANSFER`]-byte chunks, carrying the tail of
+    /// each chunk over so matches that straddle two reads are still found.
+    /// `needle` can be at most [`MAX_TRANSFER`] bytes; an empty one never
+    /// matches.
+    fn find(&mut self, range: Range<u32>, needle: &[u8]) -> Result<Option<u32>, Mb85rcError> {
+        let mut found = None;
+        search(self, range, needle, |addr| {
+            found = Some(addr);
+            false
+        })?;
+        Ok(found)
+    }
+
+    /// Find every non-overlapping occurrence of `needle` within `range`
+    ///
+    /// Addresses are stored in `out` in order and the total number of matches
+    /// is returned, which may be more than fit in `out`.
+    fn find_all(&mut self, range: Range<u32>, needle: &[u8], out: &mut [u32]) -> Result<usize, Mb85rcError> {
+        let mut count = 0;
+        search(self, range, needle, |addr| {
+            if let Some(slot) = out.get_mut(count) {
+                *slot = addr;
+            }
+            count += 1;
+            true
+        })?;
+        Ok(count)
+    }
+
+    /// Offset of the first byte where `a` and `b` differ, `None` if they match
+    ///
+    /// Both ranges are read in chunks, so nothing close to their full size
+    /// is ever held in RAM. If one range is shorter and matches the start of
+    /// the other, the mismatch is reported at the shorter one's length.
+    fn compare(&mut self, a: Range<u32>, b: Range<u32>) -> Result<Option<u32>, Mb85rcError> {
+        const CHUNK: usize = MAX_TRANSFER / 2;
+        check_range(self, &a)?;
+        check_range(self, &b)?;
+
+        let len = a.len().min(b.len()) as u32;
+        let (mut buf_a, mut buf_b) = ([0u8; CHUNK], [0u8; CHUNK]);
+        let mut offset = 0;
+        while offset < len {
+            let n = (len - offset).min(CHUNK as u32) as usize;
+            self.read_at(a.start + offset, &mut buf_a[..n])?;
+            self.read_at(b.start + offset, &mut buf_b[..n])?;
+            if let Some(i) = buf_a[..n].iter().zip(&buf_b[..n]).position(|(x, y)| x != y) {
+                return Ok(Some(offset + i as u32));
+            }
+            offset += n as u32;
+        }
+        Ok((a.len() != b.len()).then_some(len))
+    }
+
+    /// Offset of the first byte where `range` differs from `expected`, `None` if they match
+    ///
+    /// Lengths are treated the same way as in [`compare`](Self::compare).
+    fn compare_with(&mut self, range: Range<u32>, expected: &[u8]) -> Result<Option<u32>, Mb85rcError> {
+        check_range(self, &range)?;
+
+        let len = range.len().min(expected.len()) as u32;
+        let mut buf = [0u8; MAX_TRANSFER];
+        for (offset, want) in (0..len).step_by(MAX_TRANSFER).zip(expected[..len as usize].chunks(MAX_TRANSFER)) {
+            let got = &mut buf[..want.len()];
+            self.read_at(range.start + offset, got)?;
+            if let Some(i) = got.iter().zip(want).position(|(x, y)| x != y) {
+                return Ok(Some(offset + i as u32));
+            }
+        }
+        Ok((range.len() != expected.len()).then_some(len))
+    }
+
+    /// Whether every byte in `range` is `value`
+    ///
+    /// Typically `0x00` or `0xFF`, to tell whether a device or region has ever
+    /// been written. Reading stops at the first chunk holding anything else.
+    fn is_blank(&mut self, range: Range<u32>, value: u8) -> Result<bool, Mb85rcError> {
+        check_range(self, &range)?;
+
+        let mut buf = [0u8; MAX_TRANSFER];
+        for addr in range.clone().step_by(MAX_TRANSFER) {
+            let chunk = &mut buf[..(range.end - addr).min(MAX_TRANSFER as u32) as usize];
+            self.read_at(addr, chunk)?;
+            if chunk.iter().any(|&b| b != value) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Fill `range` with `pattern`, one [`MAX_TRANSFER`]-byte write at a time
+    fn fill_pattern(&mut self, range: Range<u32>, pattern: Pattern) -> Result<(), Mb85rcError> {
+        check_range(self, &range)?;
+
+        let mut buf = [0u8; MAX_TRANSFER];
+        for addr in range.clone().step_by(MAX_TRANSFER) {
+            let chunk = &mut buf[..(range.end - addr).min(MAX_TRANSFER as u32) as usize];
+            for (a, b) in (addr..).zip(chunk.iter_mut()) {
+                *b = pattern.byte_at(a);
+            }
+            self.write_at(addr, chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: FramDevice + ?Sized> FramExt for D {}
+
+/// Fill patterns for [`FramExt::fill_pattern`]
+///
+/// Every pattern is a function of the absolute address, so a dump of a
+/// filled region reads the same no matter where the fill started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// The same byte everywhere
+    Constant(u8),
+    /// `0x55` at even addresses and `0xAA` at odd ones
+    Alternating,
+    /// The low byte of the address
+    Incrementing,
+    /// Each aligned 32-bit word holds its own address, little-endian
+    AddressInAddress,
+}
+
+impl Pattern {
+    /// The byte this pattern puts at `addr`
+    pub fn byte_at(&self, addr: u32) -> u8 {
+        match *self {
+            Self::Constant(b) => b,
+            Self::Alternating => if addr & 1 == 0 { 0x55 } else { 0xAA },
+            Self::Incrementing => addr as u8,
+            Self::AddressInAddress => (addr & !3).to_le_bytes()[(addr & 3) as usize],
+        }
+    }
+}
+
+fn check_range<D: FramDevice + ?Sized>(dev: &D, range: &Range<u32>) -> Result<(), Mb85rcError> {
+    match range.start <= range.end && range.end <= dev.capacity() {
+        true => Ok(()),
+        false => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+    }
+}
+
+/// Call `found` with each non-overlapping match of `needle` in `range` until it returns `false`
+fn search<D: FramDevice + ?Sized>(dev: &mut D, range: Range<u32>, needle: &[u8], mut found: impl FnMut(u32) -> bool) -> Result<(), Mb85rcError> {
+    check_range(dev, &range)?;
+    if needle.len() > MAX_TRANSFER {
+        return Err(Mb85rcError::new(ErrorKind::TooLarge));
+    }
+    if needle.is_empty() {
+        return Ok(());
+    }
+
+    let mut buf = [0u8; MAX_TRANSFER];
+    // address of buf[0], and how much of buf holds data
+    let mut base = range.start;
+    let mut filled = 0;
+    let mut next = range.start;
+    while next < range.end {
+        let n = (range.end - next).min((MAX_TRANSFER - filled) as u32) as usize;
+        dev.read_at(next, &mut buf[filled..filled + n])?;
+        filled += n;
+        next += n as u32;
+
+        let mut i = 0;
+        while i + needle.len() <= filled {
+            if buf[i..i + needle.len()] == *needle {
+                if !found(base + i as u32) {
+                    return Ok(());
+                }
+                i += needle.len();
+            } else {
+                i += 1;
+            }
+        }
+
+        // whatever is left after i is shorter than the needle and may be
+        // the start of a match that continues in the next chunk
+        buf.copy_within(i..filled, 0);
+        base += i as u32;
+        filled -= i;
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Device wrapper that breaks long accesses up and pauses between the pieces
+///
+/// Reads and writes are passed on at most `chunk` bytes at a time, and
+/// `pause` runs between one piece and the next, never before the first or
+/// after the last. Make `pause` sleep for a few hundred microseconds, or
+/// yield to the scheduler, and other devices on the same I2C bus get a turn
+/// even while something is dumping the whole FRAM. The driver's own
+/// [`max_transfer`](crate::MB85RC::max_transfer) caps how long any single
+/// transaction holds the bus; this caps how long an operation keeps going
+/// without a break.
+pub struct FairFram<D: FramDevice, P: FnMut()> {
+    dev: D,
+    chunk: usize,
+    pause: P,
+}
+
+impl<D: FramDevice, P: FnMut()> FairFram<D, P> {
+    /// Pass accesses on to `dev` `chunk` bytes at a time, calling `pause` in between
+    pub fn new(dev: D, chunk: usize, pause: P) -> Self {
+        Self { dev, chunk: chunk.max(1), pause }
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    fn check(&self, addr: u32, len: usize) -> Result<(), Mb85rcError> {
+        match addr as u64 + len as u64 <= self.dev.capacity() as u64 {
+            true => Ok(()),
+            false => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+}
+
+impl<D: FramDevice, P: FnMut()> FramDevice for FairFram<D, P> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.check(addr, buf.len())?;
+        for (i, piece) in buf.chunks_mut(self.chunk).enumerate() {
+            if i > 0 {
+                (self.pause)();
+            }
+            self.dev.read_at(addr + (i * self.chunk) as u32, piece)?;
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        // check up front so a write past the end doesn't land half of itself first
+        self.check(addr, buf.len())?;
+        for (i, piece) in buf.chunks(self.chunk).enumerate() {
+            if i > 0 {
+                (self.pause)();
+            }
+            self.dev.write_at(addr + (i * self.chunk) as u32, piece)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//! C ABI over the Linux i2c-dev backend
+//!
+//! Build a C library with `cargo rustc --release --features ffi --crate-type cdylib`
+//! (or `staticlib`) and include `include/mb85rc.h`.
+
+use std::ffi::{c_char, c_int, CStr};
+use std::ptr;
+use std::slice;
+
+use linux_embedded_hal::I2cdev;
+
+use crate::{MB85RC, Builder, ErrorKind, Mb85rcError};
+
+/// Opaque device handle handed to C
+pub struct Mb85rcHandle(MB85RC<I2cdev>);
+
+const MB85RC_OK: c_int = 0;
+const MB85RC_EINVAL: c_int = -1;
+const MB85RC_ERANGE: c_int = -2;
+const MB85RC_EIO: c_int = -3;
+
+fn error_code(e: Mb85rcError) -> c_int {
+    match e.kind() {
+        ErrorKind::I2c => MB85RC_EIO,
+        ErrorKind::InvalidAddress | ErrorKind::OutOfRange => MB85RC_ERANGE,
+        _ => MB85RC_EINVAL,
+    }
+}
+
+/// Open the FRAM at `address` on the i2c-dev bus at `path`
+///
+/// Pass a `size` of 0 to auto-detect it. Returns null on failure.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mb85rc_open(path: *const c_char, address: u8, size: u32) -> *mut Mb85rcHandle {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(p) => p,
+        Err(_) => return ptr::null_mut(),
+    };
+    let i2c = match I2cdev::new(path) {
+        Ok(i2c) => i2c,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let mut builder = Builder::new().with_address(address);
+    if size != 0 {
+        builder = builder.with_size(size);
+    }
+
+    match builder.try_connect_i2c(i2c) {
+        Ok(fram) => Box::into_raw(Box::new(Mb85rcHandle(fram))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Read `len` bytes at `addr` into `buf`, returning 0 or a negative error code
+///
+/// # Safety
+///
+/// `handle` must come from [`mb85rc_open`] and `buf` must be valid for `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mb85rc_read(handle: *mut Mb85rcHandle, addr: u32, buf: *mut u8, len: usize) -> c_int {
+    if handle.is_null() || (buf.is_null() && len != 0) {
+        return MB85RC_EINVAL;
+    }
+    let fram = &mut (*handle).0;
+    let buf = if len == 0 { &mut [][..] } else { slice::from_raw_parts_mut(buf, len) };
+
+    match fram.address(addr).and_then(|a| fram.fram_read(a, buf)) {
+        Ok(_) => MB85RC_OK,
+        Err(e) => error_code(e),
+    }
+}
+
+/// Write `len` bytes from `buf` at `addr`, returning 0 or a negative error code
+///
+/// # Safety
+///
+/// `handle` must come from [`mb85rc_open`] and `buf` must be valid for `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mb85rc_write(handle: *mut Mb85rcHandle, addr: u32, buf: *const u8, len: usize) -> c_int {
+    if handle.is_null() || (buf.is_null() && len != 0) {
+        return MB85RC_EINVAL;
+    }
+    let fram = &mut (*handle).0;
+    let buf = if len == 0 { &[][..] } else { slice::from_raw_parts(buf, len) };
+
+    match fram.address(addr).and_then(|a| fram.fram_write(a, buf)) {
+        Ok(_) => MB85RC_OK,
+        Err(e) => error_code(e),
+    }
+}
+
+/// Size of the device in bytes, or 0 for a null handle
+///
+/// # Safety
+///
+/// `handle` must be null or come from [`mb85rc_open`].
+#[no_mangle]
+pub unsafe extern "C" fn mb85rc_size(handle: *const Mb85rcHandle) -> u32 {
+    match handle.as_ref() {
+        Some(h) => h.0.fram_size(),
+        None => 0,
+    }
+}
+
+/// Close the device and free the handle
+///
+/// # Safety
+///
+/// `handle` must be null or come from [`mb85rc_open`], and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn mb85rc_close(handle: *mut Mb85rcHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::{self, crc32, Crc32};
+
+/// Identifies a file store, "FST1"
+const MAGIC: u32 = u32::from_le_bytes(*b"FST1");
+/// Bytes of magic, slot count, padding and CRC at the start of the store
+const HEADER: u32 = 12;
+/// Bytes per directory entry
+const ENTRY: u32 = 36;
+/// Longest file name
+pub const FILE_NAME_LEN: usize = 16;
+
+/// A file in a [`FileStore`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileInfo {
+    name: [u8; FILE_NAME_LEN],
+    offset: u32,
+    /// Length of the file in bytes
+    pub len: u32,
+    /// CRC-32 of the file's contents
+    pub crc: u32,
+    seq: u32,
+    slot: u16,
+}
+
+impl FileInfo {
+    /// The file's name
+    pub fn name(&self) -> &str {
+        let end = self.name.iter().position(|&b| b == 0).unwrap_or(FILE_NAME_LEN);
+        core::str::from_utf8(&self.name[..end]).unwrap_or("")
+    }
+
+    fn end(&self) -> u32 {
+        self.offset + self.len
+    }
+}
+
+/// A flat directory of named blobs
+///
+/// The device (usually a [`FramSlice`](crate::FramSlice)) starts with a
+/// directory of fixed-size entries giving each file's name, location, length
+/// and CRC, followed by the file data. Rewriting a file puts the new
+/// contents in free space and adds a fresh entry before dropping the old
+/// one, so a reset part way through leaves either the old or the new file.
+/// That means a rewrite needs room for both copies, which is why the
+/// directory has one more slot than the number of files it can hold.
+pub struct FileStore<D: FramDevice> {
+    dev: D,
+    slots: u16,
+    next_seq: u32,
+}
+
+impl<D: FramDevice> FileStore<D> {
+    /// Create an empty store on `dev` with room for `max_files` files
+    pub fn format(mut dev: D, max_files: u16) -> Result<Self, Mb85rcError> {
+        let slots = max_files.checked_add(1).ok_or(Mb85rcError::new(ErrorKind::TooLarge))?;
+        if HEADER as u64 + slots as u64 * ENTRY as u64 > dev.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+
+        let mut header = [0u8; HEADER as usize];
+        header[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[4..6].copy_from_slice(&slots.to_le_bytes());
+        let crc = crc32(&header[..8]);
+        header[8..12].copy_from_slice(&crc.to_le_bytes());
+        dev.write_at(0, &header)?;
+
+        let mut store = Self { dev, slots, next_seq: 0 };
+        for slot in 0..slots {
+            store.clear_slot(slot)?;
+        }
+        Ok(store)
+    }
+
+    /// Open an existing store, failing with [`ErrorKind::InvalidData`] if there isn't one
+    ///
+    /// Finishes any rewrite that was interrupted by dropping the older of
+    /// two entries with the same name.
+    pub fn mount(mut dev: D) -> Result<Self, Mb85rcError> {
+        let mut header = [0u8; HEADER as usize];
+        dev.read_at(0, &mut header)?;
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let crc = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if magic != MAGIC || crc != crc32(&header[..8]) {
+            return Err(Mb85rcError::new(ErrorKind::InvalidData));
+        }
+
+        let slots = u16::from_le_bytes([header[4], header[5]]);
+        let mut store = Self { dev, slots, next_seq: 0 };
+        for slot in 0..slots {
+            let Some(file) = store.entry(slot)? else { continue };
+            store.next_seq = store.next_seq.max(file.seq.wrapping_add(1));
+            for other in slot + 1..slots {
+                let Some(other) = store.entry(other)? else { continue };
+                if other.name == file.name {
+                    let older = if other.seq.wrapping_sub(file.seq) as i32 > 0 { file.slot } else { other.slot };
+                    store.clear_slot(older)?;
+                }
+            }
+        }
+        Ok(store)
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Most files the store can hold
+    pub fn max_files(&self) -> u16 {
+        self.slots - 1
+    }
+
+    fn data_start(&self) -> u32 {
+        HEADER + self.slots as u32 * ENTRY
+    }
+
+    fn slot_addr(&self, slot: u16) -> u32 {
+        HEADER + slot as u32 * ENTRY
+    }
+
+    fn clear_slot(&mut self, slot: u16) -> Result<(), Mb85rcError> {
+        self.dev.write_at(self.slot_addr(slot), &[0u8; ENTRY as usize])
+    }
+
+    /// Read the entry in `slot`, `None` if it's empty or fails its CRC
+    fn entry(&mut self, slot: u16) -> Result<Option<FileInfo>, Mb85rcError> {
+        let mut raw = [0u8; ENTRY as usize];
+        self.dev.read_at(self.slot_addr(slot), &mut raw)?;
+        if raw[0] == 0 || crc32(&raw[..32]) != u32::from_le_bytes(raw[32..36].try_into().unwrap()) {
+            return Ok(None);
+        }
+        Ok(Some(FileInfo {
+            name: raw[..16].try_into().unwrap(),
+            offset: u32::from_le_bytes(raw[16..20].try_into().unwrap()),
+            len: u32::from_le_bytes(raw[20..24].try_into().unwrap()),
+            crc: u32::from_le_bytes(raw[24..28].try_into().unwrap()),
+            seq: u32::from_le_bytes(raw[28..32].try_into().unwrap()),
+            slot,
+        }))
+    }
+
+    /// Iterate over every file
+    pub fn files(&mut self) -> Files<'_, D> {
+        Files { store: self, slot: 0 }
+    }
+
+    /// Look up a file by name
+    pub fn find(&mut self, name: &str) -> Result<Option<FileInfo>, Mb85rcError> {
+        for file in self.files() {
+            let file = file?;
+            if file.name() == name {
+                return Ok(Some(file));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Bytes of data space not used by any file
+    pub fn free(&mut self) -> Result<u32, Mb85rcError> {
+        let mut free = self.dev.capacity() - self.data_start();
+        for file in self.files() {
+            free -= file?.len;
+        }
+        Ok(free)
+    }
+
+    /// Lowest address with `len` free bytes
+    fn find_space(&mut self, len: u32) -> Result<Option<u32>, Mb85rcError> {
+        let mut best: Option<u32> = None;
+        // a file can only ever start at the beginning of the data area or
+        // right after another file
+        for candidate in 0..=self.slots {
+            let start = match candidate {
+                0 => self.data_start(),
+                n => match self.entry(n - 1)? {
+                    Some(file) => file.end(),
+                    None => continue,
+                },
+            };
+            if best.is_some_and(|b| b <= start) || start as u64 + len as u64 > self.dev.capacity() as u64 {
+                continue;
+            }
+            let mut fits = true;
+            for slot in 0..self.slots {
+                if let Some(file) = self.entry(slot)? {
+                    if start < file.end() && file.offset < start + len {
+                        fits = false;
+                        break;
+                    }
+                }
+            }
+            if fits {
+                best = Some(start);
+            }
+        }
+        Ok(best)
+    }
+
+    /// Create or replace the file called `name`
+    pub fn write(&mut self, name: &str, data: &[u8]) -> Result<FileInfo, Mb85rcError> {
+        if name.is_empty() || name.len() > FILE_NAME_LEN || name.as_bytes().contains(&0) {
+            return Err(Mb85rcError::new(ErrorKind::InvalidData));
+        }
+        let len = u32::try_from(data.len()).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+
+        let old = self.find(name)?;
+        let mut slot = None;
+        for s in 0..self.slots {
+            if self.entry(s)?.is_none() {
+                slot = Some(s);
+                break;
+            }
+        }
+        let slot = slot.ok_or(Mb85rcError::new(ErrorKind::Full))?;
+        if old.is_none() && self.files().count() >= self.max_files() as usize {
+            return Err(Mb85rcError::new(ErrorKind::Full));
+        }
+        let offset = self.find_space(len)?.ok_or(Mb85rcError::new(ErrorKind::TooLarge))?;
+
+        self.dev.write_at(offset, data)?;
+        let mut file = FileInfo { name: [0; FILE_NAME_LEN], offset, len, crc: crc32(data), seq: self.next_seq, slot };
+        file.name[..name.len()].copy_from_slice(name.as_bytes());
+
+        let mut raw = [0u8; ENTRY as usize];
+        raw[..16].copy_from_slice(&file.name);
+        raw[16..20].copy_from_slice(&file.offset.to_le_bytes());
+        raw[20..24].copy_from_slice(&file.len.to_le_bytes());
+        raw[24..28].copy_from_slice(&file.crc.to_le_bytes());
+        raw[28..32].copy_from_slice(&file.seq.to_le_bytes());
+        let crc = crc32(&raw[..32]);
+        raw[32..36].copy_from_slice(&crc.to_le_bytes());
+        self.dev.write_at(self.slot_addr(slot), &raw)?;
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        if let Some(old) = old {
+            self.clear_slot(old.slot)?;
+        }
+        Ok(file)
+    }
+
+    /// Read the file called `name` into `buf`, returning its length
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if the contents don't match the CRC.
+    pub fn read(&mut self, name: &str, buf: &mut [u8]) -> Result<Option<usize>, Mb85rcError> {
+        let Some(file) = self.find(name)? else { return Ok(None) };
+        let buf = buf.get_mut(..file.len as usize).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        self.dev.read_at(file.offset, buf)?;
+        match crc32(buf) == file.crc {
+            true => Ok(Some(buf.len())),
+            false => Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        }
+    }
+
+    /// Check a file's contents against its CRC without reading them out
+    pub fn verify(&mut self, file: &FileInfo) -> Result<bool, Mb85rcError> {
+        let mut crc = Crc32::new();
+        crc::update_from_device(&mut crc, &mut self.dev, file.offset, file.len)?;
+        Ok(crc.finish() == file.crc)
+    }
+
+    /// Delete the file called `name`
+    pub fn remove(&mut self, name: &str) -> Result<(), Mb85rcError> {
+        let file = self.find(name)?.ok_or(Mb85rcError::new(ErrorKind::NotFound))?;
+        self.clear_slot(file.slot)
+    }
+}
+
+/// Iterator over the files in a [`FileStore`], from [`FileStore::files`]
+pub struct Files<'a, D: FramDevice> {
+    store: &'a mut FileStore<D>,
+    slot: u16,
+}
+
+impl<D: FramDevice> Iterator for Files<'_, D> {
+    type Item = Result<FileInfo, Mb85rcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.slot < self.store.slots {
+            let slot = self.slot;
+            self.slot += 1;
+            match self.store.entry(slot) {
+                Ok(Some(file)) => return Some(Ok(file)),
+                Ok(None) => {},
+                Err(e) => {
+                    self.slot = self.store.slots;
+                    return Some(Err(e));
+                },
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,64 @@
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+use crate::{MB85RC, NoWriteProtect};
+
+/// A FRAM interface stored in a `static` and shared through critical sections
+///
+/// Register the device once at startup with [`init`](Self::init), then reach
+/// it from anywhere (including a panic handler) with [`with`](Self::with).
+/// Declare one with [`static_fram!`](crate::static_fram).
+pub struct GlobalFram<I2C, WP = NoWriteProtect> {
+    inner: Mutex<RefCell<Option<MB85RC<I2C, WP>>>>,
+}
+
+impl<I2C, WP> GlobalFram<I2C, WP> {
+    /// Create an empty slot
+    pub const fn new() -> Self {
+        Self { inner: Mutex::new(RefCell::new(None)) }
+    }
+
+    /// Register the interface, handing back any previously registered one
+    pub fn init(&self, fram: MB85RC<I2C, WP>) -> Option<MB85RC<I2C, WP>> {
+        critical_section::with(|cs| self.inner.borrow(cs).replace(Some(fram)))
+    }
+
+    /// Remove the interface from the slot
+    pub fn take(&self) -> Option<MB85RC<I2C, WP>> {
+        critical_section::with(|cs| self.inner.borrow(cs).take())
+    }
+
+    /// Run `f` on the interface inside a critical section
+    ///
+    /// Returns `None` if nothing is registered or the interface is already in
+    /// use further up the stack (e.g. a panic raised from inside another `with`),
+    /// so it is safe to call from a panic handler.
+    pub fn with<R>(&self, f: impl FnOnce(&mut MB85RC<I2C, WP>) -> R) -> Option<R> {
+        critical_section::with(|cs| {
+            let mut slot = self.inner.borrow(cs).try_borrow_mut().ok()?;
+            slot.as_mut().map(f)
+        })
+    }
+}
+
+impl<I2C, WP> Default for GlobalFram<I2C, WP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Declare a `static` [`GlobalFram`] for the given bus type (and write-protect pin type)
+///
+/// `static_fram!(FRAM: MyI2c);` expands to
+/// `static FRAM: GlobalFram<MyI2c> = GlobalFram::new();`, and
+/// `static_fram!(FRAM: MyI2c, MyPin);` to
+/// `static FRAM: GlobalFram<MyI2c, MyPin> = GlobalFram::new();`
+#[macro_export]
+macro_rules! static_fram {
+    ($vis:vis $name:ident : $i2c:ty) => {
+        $vis static $name: $crate::GlobalFram<$i2c> = $crate::GlobalFram::new();
+    };
+    ($vis:vis $name:ident : $i2c:ty, $wp:ty) => {
+        $vis static $name: $crate::GlobalFram<$i2c, $wp> = $crate::GlobalFram::new();
+    };
+}
@@ -0,0 +1,105 @@
+use crate::{FramDevice, Mb85rcError};
+use crate::crc::crc32;
+
+/// Identifies a posted message, "HOFF"
+const MAGIC: u32 = u32::from_le_bytes(*b"HOFF");
+
+/// A message left in a [`Mailbox`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handoff {
+    /// Stay in the bootloader's firmware update mode
+    EnterDfu,
+    /// Why the sender is rebooting, in the application's own codes
+    RebootReason(u32),
+    /// Where the sender left a log worth looking at
+    LogPointer(u32),
+    /// Anything else; `kind` should be `0x100` or above to stay clear of the built-in messages
+    Custom {
+        /// Application-defined message type
+        kind: u16,
+        /// Application-defined argument
+        arg: u32,
+    },
+}
+
+impl Handoff {
+    fn encode(self) -> (u16, u32) {
+        match self {
+            Self::EnterDfu => (1, 0),
+            Self::RebootReason(reason) => (2, reason),
+            Self::LogPointer(addr) => (3, addr),
+            Self::Custom { kind, arg } => (kind, arg),
+        }
+    }
+
+    fn decode(kind: u16, arg: u32) -> Self {
+        match kind {
+            1 => Self::EnterDfu,
+            2 => Self::RebootReason(arg),
+            3 => Self::LogPointer(arg),
+            kind => Self::Custom { kind, arg },
+        }
+    }
+}
+
+/// A one-message mailbox for passing requests across a reset
+///
+/// Typically the application posts to one mailbox for the bootloader and
+/// the bootloader answers in another. A message is a magic number, the
+/// message and a CRC, so whatever the FRAM happened to hold after
+/// a brownout or a fresh board never reads as a message. The handle only
+/// records where the mailbox lives; pass the device to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mailbox {
+    addr: u32,
+}
+
+impl Mailbox {
+    /// Bytes of FRAM the mailbox takes up
+    pub const SIZE: u32 = 16;
+
+    /// Mailbox stored at `addr`
+    pub const fn new(addr: u32) -> Self {
+        Self { addr }
+    }
+
+    /// Leave `message`, replacing any message already there
+    pub fn post<D: FramDevice + ?Sized>(&self, dev: &mut D, message: Handoff) -> Result<(), Mb85rcError> {
+        let (kind, arg) = message.encode();
+        let mut raw = [0u8; Self::SIZE as usize];
+        raw[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        raw[4..6].copy_from_slice(&kind.to_le_bytes());
+        raw[8..12].copy_from_slice(&arg.to_le_bytes());
+        let crc = crc32(&raw[..12]);
+        raw[12..16].copy_from_slice(&crc.to_le_bytes());
+        dev.write_at(self.addr, &raw)
+    }
+
+    /// The waiting message, if there is a valid one
+    pub fn peek<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<Handoff>, Mb85rcError> {
+        let mut raw = [0u8; Self::SIZE as usize];
+        dev.read_at(self.addr, &mut raw)?;
+        let magic = u32::from_le_bytes(raw[0..4].try_into().unwrap());
+        let crc = u32::from_le_bytes(raw[12..16].try_into().unwrap());
+        if magic != MAGIC || crc != crc32(&raw[..12]) {
+            return Ok(None);
+        }
+        let kind = u16::from_le_bytes([raw[4], raw[5]]);
+        let arg = u32::from_le_bytes(raw[8..12].try_into().unwrap());
+        Ok(Some(Handoff::decode(kind, arg)))
+    }
+
+    /// Read the waiting message and clear the mailbox
+    pub fn take<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<Handoff>, Mb85rcError> {
+        let message = self.peek(dev)?;
+        if message.is_some() {
+            self.clear(dev)?;
+        }
+        Ok(message)
+    }
+
+    /// Throw away any waiting message
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        dev.write_at(self.addr, &[0u8; 4])
+    }
+}
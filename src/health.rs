@@ -0,0 +1,195 @@
+use embedded_hal::blocking::i2c;
+use core::error::Error;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind, Diagnostics, MB85RC, WriteProtect};
+
+/// How a [`HealthMonitor`] rates its device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Health {
+    /// Everything is working
+    Healthy,
+    /// Accesses are failing or data is coming back corrupt, but it isn't hopeless yet
+    Degraded,
+    /// Too many accesses in a row have failed; stays this way until [`reset`](HealthMonitor::reset)
+    Failed,
+}
+
+/// Device wrapper that keeps track of how well the device is doing
+///
+/// Failed reads and writes are counted as they happen, and storage layers
+/// report corrupt data through [`crc_error`](Self::crc_error). The device
+/// becomes [`Degraded`](Health::Degraded) after a few failures in a row or
+/// too many CRC errors, and [`Failed`](Health::Failed) after more failures
+/// in a row. A success clears the run of failures, but `Failed` sticks until
+/// supervisory code has dealt with it (reset the bus, told someone) and
+/// called [`reset`](Self::reset).
+///
+/// With [`with_short_circuit`](Self::with_short_circuit), accesses to a
+/// failed device return [`ErrorKind::Failed`] straight away instead of
+/// waiting on the bus.
+pub struct HealthMonitor<D: FramDevice> {
+    dev: D,
+    degraded_after: u32,
+    failed_after: u32,
+    crc_degraded_after: u32,
+    short_circuit: bool,
+    failed: bool,
+    consecutive: u32,
+    failures: u32,
+    crc_errors: u32,
+    last_error: Option<Mb85rcError>,
+}
+
+impl<D: FramDevice> HealthMonitor<D> {
+    /// Watch `dev`, degrading after 3 failures in a row or 10 CRC errors and failing after 10 in a row
+    pub fn new(dev: D) -> Self {
+        Self {
+            dev,
+            degraded_after: 3,
+            failed_after: 10,
+            crc_degraded_after: 10,
+            short_circuit: false,
+            failed: false,
+            consecutive: 0,
+            failures: 0,
+            crc_errors: 0,
+            last_error: None,
+        }
+    }
+
+    /// Degrade after `degraded` failures in a row and fail after `failed`
+    pub fn with_thresholds(mut self, degraded: u32, failed: u32) -> Self {
+        self.degraded_after = degraded.max(1);
+        self.failed_after = failed.max(self.degraded_after);
+        self
+    }
+
+    /// Degrade once `errors` CRC errors have been reported, 0 for never
+    pub fn with_crc_threshold(mut self, errors: u32) -> Self {
+        self.crc_degraded_after = errors;
+        self
+    }
+
+    /// Refuse accesses without touching the device while it's failed
+    pub fn with_short_circuit(mut self, enabled: bool) -> Self {
+        self.short_circuit = enabled;
+        self
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// The current rating
+    pub fn health(&self) -> Health {
+        if self.failed {
+            Health::Failed
+        } else if self.consecutive >= self.degraded_after
+            || (self.crc_degraded_after != 0 && self.crc_errors >= self.crc_degraded_after) {
+            Health::Degraded
+        } else {
+            Health::Healthy
+        }
+    }
+
+    /// Failed accesses since the last success
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive
+    }
+
+    /// Failed accesses since the last reset
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// CRC errors reported since the last reset
+    pub fn crc_errors(&self) -> u32 {
+        self.crc_errors
+    }
+
+    /// The most recent error, from an access or [`crc_error`](Self::crc_error)
+    pub fn last_error(&self) -> Option<Mb85rcError> {
+        self.last_error
+    }
+
+    /// Note that data read through this device failed its CRC
+    pub fn crc_error(&mut self) {
+        self.crc_errors = self.crc_errors.saturating_add(1);
+        self.last_error = Some(Mb85rcError::new(ErrorKind::InvalidData));
+    }
+
+    /// Clear every count and go back to [`Healthy`](Health::Healthy)
+    pub fn reset(&mut self) {
+        self.failed = false;
+        self.consecutive = 0;
+        self.failures = 0;
+        self.crc_errors = 0;
+        self.last_error = None;
+    }
+
+    fn check(&self) -> Result<(), Mb85rcError> {
+        match self.short_circuit && self.failed {
+            true => Err(Mb85rcError::new(ErrorKind::Failed)),
+            false => Ok(()),
+        }
+    }
+
+    fn record(&mut self, outcome: Result<(), Mb85rcError>) -> Result<(), Mb85rcError> {
+        match outcome {
+            Ok(()) => self.consecutive = 0,
+            // running off the end of the device is the caller's mistake, not the device's
+            Err(e) if e.kind() == ErrorKind::OutOfRange => {},
+            Err(e) => {
+                self.consecutive = self.consecutive.saturating_add(1);
+                self.failures = self.failures.saturating_add(1);
+                self.last_error = Some(e);
+                if self.consecutive >= self.failed_after {
+                    self.failed = true;
+                }
+            },
+        }
+        outcome
+    }
+}
+
+impl<I2C, WP: WriteProtect> HealthMonitor<MB85RC<I2C, WP>>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    /// Collect a [`Diagnostics`] report from the driver, with the health fields filled in
+    ///
+    /// Reading the device ID for the report doesn't count towards the health.
+    pub fn diagnostics(&mut self) -> Diagnostics {
+        let report = self.dev.diagnostics();
+        Diagnostics {
+            health: Some(self.health()),
+            consecutive_failures: self.consecutive,
+            failures: self.failures,
+            crc_errors: self.crc_errors,
+            last_error: self.last_error.map(|e| e.kind()).or(report.last_error),
+            ..report
+        }
+    }
+}
+
+impl<D: FramDevice> FramDevice for HealthMonitor<D> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.check()?;
+        let outcome = self.dev.read_at(addr, buf);
+        self.record(outcome)
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        self.check()?;
+        let outcome = self.dev.write_at(addr, buf);
+        self.record(outcome)
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::mirror::Mirrored;
+
+/// Bytes of header in front of every block: size and flags, kept in two CRC-checked copies
+const HEADER: u32 = Mirrored::<8>::SIZE;
+/// Don't split off free blocks with less room than this
+const MIN_SPLIT: u32 = HEADER + 4;
+/// Flag bit marking a block as allocated
+const USED: u32 = 1;
+
+/// Handle to a block allocated from a [`FramHeap`]
+///
+/// This is just the offset of the block's data within the heap, so it can be
+/// stored in FRAM and turned back into a handle with [`from_raw`](Self::from_raw).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Block(u32);
+
+impl Block {
+    /// The offset of the block's data within the heap
+    pub fn into_raw(self) -> u32 {
+        self.0
+    }
+
+    /// Rebuild a handle from [`into_raw`](Self::into_raw)
+    pub fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    fn header(self) -> u32 {
+        self.0 - HEADER
+    }
+}
+
+/// Totals gathered while checking a [`FramHeap`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Number of blocks, free or not
+    pub blocks: u32,
+    /// Bytes of data space in allocated blocks
+    pub used: u32,
+    /// Bytes of data space in free blocks
+    pub free: u32,
+    /// Data space of the largest free block
+    pub largest_free: u32,
+}
+
+/// Allocator for variable-size blobs
+///
+/// The device (usually a [`FramSlice`](crate::FramSlice)) is covered by a
+/// chain of blocks, each with a header giving its size and whether it's in
+/// use. Allocation takes the first free block that's big enough and splits
+/// off what's left; freeing merges neighbouring free blocks back together.
+/// Headers are kept in two alternating CRC-checked copies, so a reset part
+/// way through updating one leaves the previous header, and the updates are
+/// ordered so the chain is consistent whichever headers made it. A reset
+/// can leave a split-off block's header behind without the chain reaching
+/// it, which is harmless. [`mount`](Self::mount) checks the whole chain
+/// before handing out the heap.
+pub struct FramHeap<D: FramDevice> {
+    dev: D,
+}
+
+impl<D: FramDevice> FramHeap<D> {
+    /// Bytes of header in front of every block's data
+    pub const HEADER_SIZE: u32 = HEADER;
+
+    /// Open an existing heap, failing with [`ErrorKind::InvalidData`] if it doesn't pass [`check`](Self::check)
+    pub fn mount(dev: D) -> Result<Self, Mb85rcError> {
+        let mut heap = Self { dev };
+        heap.check()?;
+        Ok(heap)
+    }
+
+    /// Create an empty heap on `dev`, discarding whatever was there
+    pub fn format(mut dev: D) -> Result<Self, Mb85rcError> {
+        let size = dev.capacity();
+        if size < MIN_SPLIT {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        write_header(&mut dev, 0, size, 0)?;
+        Ok(Self { dev })
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Walk the chain of blocks, calling `f` with each block's offset, size and flags
+    fn walk(&mut self, mut f: impl FnMut(&mut D, u32, u32, u32) -> Result<bool, Mb85rcError>) -> Result<(), Mb85rcError> {
+        let end = self.dev.capacity();
+        let mut pos = 0;
+        while pos < end {
+            let (size, flags) = read_header(&mut self.dev, pos)?;
+            if size < HEADER || size > end - pos {
+                return Err(Mb85rcError::new(ErrorKind::InvalidData));
+            }
+            if !f(&mut self.dev, pos, size, flags)? {
+                return Ok(());
+            }
+            pos += size;
+        }
+        Ok(())
+    }
+
+    /// Check every block header and that the blocks exactly cover the device
+    pub fn check(&mut self) -> Result<HeapStats, Mb85rcError> {
+        let mut stats = HeapStats::default();
+        self.walk(|_, _, size, flags| {
+            stats.blocks += 1;
+            if flags & USED != 0 {
+                stats.used += size - HEADER;
+            } else {
+                stats.free += size - HEADER;
+                stats.largest_free = stats.largest_free.max(size - HEADER);
+            }
+            Ok(true)
+        })?;
+        Ok(stats)
+    }
+
+    /// Allocate a block with room for at least `len` bytes
+    pub fn alloc(&mut self, len: u32) -> Result<Block, Mb85rcError> {
+        let need = len.checked_add(HEADER).ok_or(Mb85rcError::new(ErrorKind::TooLarge))?;
+        let mut found = None;
+        self.walk(|dev, pos, size, flags| {
+            if flags & USED != 0 || size < need {
+                return Ok(true);
+            }
+
+            // write the leftover block's header before shrinking this one, so
+            // a reset in between leaves this block whole
+            let size = if size - need >= MIN_SPLIT {
+                write_header(dev, pos + need, size - need, 0)?;
+                need
+            } else {
+                size
+            };
+            write_header(dev, pos, size, USED)?;
+            found = Some(Block(pos + HEADER));
+            Ok(false)
+        })?;
+        found.ok_or(Mb85rcError::new(ErrorKind::Full))
+    }
+
+    /// Check `block` is a valid allocated block and get its data size
+    fn block_size(&mut self, block: Block) -> Result<u32, Mb85rcError> {
+        if block.0 < HEADER || block.0 > self.dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::NotFound));
+        }
+        match read_header(&mut self.dev, block.header()) {
+            Ok((size, flags)) if flags & USED != 0 => Ok(size - HEADER),
+            _ => Err(Mb85rcError::new(ErrorKind::NotFound)),
+        }
+    }
+
+    /// Data space in `block`, which may be more than was asked for
+    pub fn size(&mut self, block: Block) -> Result<u32, Mb85rcError> {
+        self.block_size(block)
+    }
+
+    /// Release `block` and merge free neighbours
+    pub fn free(&mut self, block: Block) -> Result<(), Mb85rcError> {
+        let size = self.block_size(block)?;
+        write_header(&mut self.dev, block.header(), size + HEADER, 0)?;
+        self.coalesce()
+    }
+
+    /// Merge every run of adjacent free blocks into one
+    fn coalesce(&mut self) -> Result<(), Mb85rcError> {
+        let mut run: Option<(u32, u32)> = None;
+        self.walk(|dev, pos, size, flags| {
+            match (run, flags & USED != 0) {
+                (Some((start, len)), false) => {
+                    write_header(dev, start, len + size, 0)?;
+                    run = Some((start, len + size));
+                },
+                (None, false) => run = Some((pos, size)),
+                (_, true) => run = None,
+            }
+            Ok(true)
+        })
+    }
+
+    fn check_access(&mut self, block: Block, offset: u32, len: usize) -> Result<(), Mb85rcError> {
+        let size = self.block_size(block)?;
+        match offset as u64 + len as u64 <= size as u64 {
+            true => Ok(()),
+            false => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    /// Read from `block` starting `offset` bytes into its data
+    pub fn read(&mut self, block: Block, offset: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.check_access(block, offset, buf.len())?;
+        self.dev.read_at(block.0 + offset, buf)
+    }
+
+    /// Write to `block` starting `offset` bytes into its data
+    pub fn write(&mut self, block: Block, offset: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        self.check_access(block, offset, data.len())?;
+        self.dev.write_at(block.0 + offset, data)
+    }
+}
+
+fn read_header<D: FramDevice + ?Sized>(dev: &mut D, pos: u32) -> Result<(u32, u32), Mb85rcError> {
+    let (_, raw) = Mirrored::<8>::new(pos).read(dev)?.ok_or(Mb85rcError::new(ErrorKind::InvalidData))?;
+    let size = u32::from_le_bytes(raw[0..4].try_into().unwrap());
+    let flags = u32::from_le_bytes(raw[4..8].try_into().unwrap());
+    Ok((size, flags))
+}
+
+fn write_header<D: FramDevice + ?Sized>(dev: &mut D, pos: u32, size: u32, flags: u32) -> Result<(), Mb85rcError> {
+    let mut raw = [0u8; 8];
+    raw[0..4].copy_from_slice(&size.to_le_bytes());
+    raw[4..8].copy_from_slice(&flags.to_le_bytes());
+    Mirrored::<8>::new(pos).write(dev, &raw)
+}
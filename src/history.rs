@@ -0,0 +1,217 @@
+use core::cmp::Reverse;
+
+use crate::{FramDevice, Codec, Mb85rcError, ErrorKind};
+use crate::crc::{self, Crc32};
+use crate::snapshot::copy_within;
+
+/// Bytes of bookkeeping at the start of every slot: sequence, timestamp, length, CRC
+const HEADER: u32 = 14;
+
+/// Description of one stored config version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// Sequence number, increasing by one with every save
+    pub seq: u32,
+    /// Timestamp passed in when the version was saved
+    pub timestamp: u32,
+    /// Length of the stored config in bytes
+    pub len: u16,
+    /// CRC-32 over the version's header fields and data
+    pub crc: u32,
+}
+
+/// Keeps the last few versions of a config blob in a ring of slots
+///
+/// The device (usually a [`FramSlice`](crate::FramSlice)) is split into
+/// `slots` equal slots. Each save goes into the slot after the newest one,
+/// overwriting the oldest version, so the previous `slots - 1` versions stay
+/// available for listing and rolling back to. A version whose write was cut
+/// short fails its CRC and is ignored.
+pub struct ConfigHistory<D: FramDevice> {
+    dev: D,
+    slots: u32,
+    slot_size: u32,
+}
+
+impl<D: FramDevice> ConfigHistory<D> {
+    /// Split `dev` into `slots` slots
+    ///
+    /// Fails if a slot would be too small to hold its header
+    pub fn new(dev: D, slots: u32) -> Result<Self, Mb85rcError> {
+        let slot_size = dev.capacity().checked_div(slots).unwrap_or(0);
+        if slot_size <= HEADER {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok(Self { dev, slots, slot_size })
+    }
+
+    /// Largest config that fits in a slot
+    pub fn max_len(&self) -> usize {
+        ((self.slot_size - HEADER) as usize).min(u16::MAX as usize)
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    fn header_crc(seq: u32, timestamp: u32, len: u16) -> Crc32 {
+        let mut crc = Crc32::new();
+        crc.update(&seq.to_le_bytes());
+        crc.update(&timestamp.to_le_bytes());
+        crc.update(&len.to_le_bytes());
+        crc
+    }
+
+    /// Read and check the version in slot `slot`
+    fn slot_info(&mut self, slot: u32) -> Result<Option<VersionInfo>, Mb85rcError> {
+        let base = slot * self.slot_size;
+        let mut header = [0u8; HEADER as usize];
+        self.dev.read_at(base, &mut header)?;
+
+        let seq = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let timestamp = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let len = u16::from_le_bytes(header[8..10].try_into().unwrap());
+        let stored = u32::from_le_bytes(header[10..14].try_into().unwrap());
+        if len as usize > self.max_len() {
+            return Ok(None);
+        }
+
+        let mut crc = Self::header_crc(seq, timestamp, len);
+        crc::update_from_device(&mut crc, &mut self.dev, base + HEADER, len as u32)?;
+        if crc.finish() != stored {
+            return Ok(None);
+        }
+
+        Ok(Some(VersionInfo { seq, timestamp, len, crc: stored }))
+    }
+
+    /// The newest valid version and the slot it's in
+    fn newest(&mut self) -> Result<Option<(u32, VersionInfo)>, Mb85rcError> {
+        let mut newest: Option<(u32, VersionInfo)> = None;
+        for slot in 0..self.slots {
+            if let Some(info) = self.slot_info(slot)? {
+                if newest.is_none_or(|(_, n)| info.seq > n.seq) {
+                    newest = Some((slot, info));
+                }
+            }
+        }
+        Ok(newest)
+    }
+
+    fn find(&mut self, seq: u32) -> Result<(u32, VersionInfo), Mb85rcError> {
+        for slot in 0..self.slots {
+            if let Some(info) = self.slot_info(slot)?.filter(|i| i.seq == seq) {
+                return Ok((slot, info));
+            }
+        }
+        Err(Mb85rcError::new(ErrorKind::NotFound))
+    }
+
+    /// Where the next version goes and the sequence number it gets
+    fn next_slot(&mut self) -> Result<(u32, u32), Mb85rcError> {
+        Ok(match self.newest()? {
+            Some((slot, info)) => ((slot + 1) % self.slots, info.seq.wrapping_add(1)),
+            None => (0, 0),
+        })
+    }
+
+    fn write_header(&mut self, slot: u32, info: &VersionInfo) -> Result<(), Mb85rcError> {
+        let mut header = [0u8; HEADER as usize];
+        header[0..4].copy_from_slice(&info.seq.to_le_bytes());
+        header[4..8].copy_from_slice(&info.timestamp.to_le_bytes());
+        header[8..10].copy_from_slice(&info.len.to_le_bytes());
+        header[10..14].copy_from_slice(&info.crc.to_le_bytes());
+        self.dev.write_at(slot * self.slot_size, &header)
+    }
+
+    /// Store `data` as the newest version
+    pub fn save(&mut self, data: &[u8], timestamp: u32) -> Result<VersionInfo, Mb85rcError> {
+        if data.len() > self.max_len() {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+
+        let (slot, seq) = self.next_slot()?;
+        let len = data.len() as u16;
+        let mut crc = Self::header_crc(seq, timestamp, len);
+        crc.update(data);
+        let info = VersionInfo { seq, timestamp, len, crc: crc.finish() };
+
+        self.dev.write_at(slot * self.slot_size + HEADER, data)?;
+        self.write_header(slot, &info)?;
+        Ok(info)
+    }
+
+    /// List stored versions, newest first, into `out`, returning how many were listed
+    ///
+    /// If `out` is too small, only the newest versions are listed
+    pub fn versions(&mut self, out: &mut [VersionInfo]) -> Result<usize, Mb85rcError> {
+        let mut n = 0;
+        for slot in 0..self.slots {
+            let Some(info) = self.slot_info(slot)? else { continue };
+            if n < out.len() {
+                out[n] = info;
+                n += 1;
+            } else if let Some(oldest) = out.iter_mut().min_by_key(|v| v.seq).filter(|v| v.seq < info.seq) {
+                *oldest = info;
+            }
+        }
+        out[..n].sort_unstable_by_key(|v| Reverse(v.seq));
+        Ok(n)
+    }
+
+    /// Read the newest version into `buf`
+    ///
+    /// Returns `None` if nothing valid has been saved yet
+    pub fn load_latest(&mut self, buf: &mut [u8]) -> Result<Option<VersionInfo>, Mb85rcError> {
+        match self.newest()? {
+            Some((slot, info)) => self.read_slot(slot, &info, buf).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Read version `seq` into `buf`
+    pub fn load(&mut self, seq: u32, buf: &mut [u8]) -> Result<VersionInfo, Mb85rcError> {
+        let (slot, info) = self.find(seq)?;
+        self.read_slot(slot, &info, buf)
+    }
+
+    fn read_slot(&mut self, slot: u32, info: &VersionInfo, buf: &mut [u8]) -> Result<VersionInfo, Mb85rcError> {
+        let buf = buf.get_mut(..info.len as usize).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        self.dev.read_at(slot * self.slot_size + HEADER, buf)?;
+        Ok(*info)
+    }
+
+    /// Store `value` as the newest version, encoded by `codec` using `scratch` as the encode buffer
+    pub fn save_value<T, C: Codec<T>>(&mut self, codec: &C, value: &T, scratch: &mut [u8], timestamp: u32) -> Result<VersionInfo, Mb85rcError> {
+        let len = codec.encode(value, scratch)?;
+        self.save(&scratch[..len], timestamp)
+    }
+
+    /// Read and decode the newest version, using `scratch` to hold the encoded bytes
+    pub fn load_latest_value<T, C: Codec<T>>(&mut self, codec: &C, scratch: &mut [u8]) -> Result<Option<T>, Mb85rcError> {
+        match self.load_latest(scratch)? {
+            Some(info) => codec.decode(&scratch[..info.len as usize]).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Make version `seq` the newest again by saving a copy of it
+    ///
+    /// History stays linear: the versions in between are kept, and the copy
+    /// gets a new sequence number and `timestamp`.
+    pub fn rollback(&mut self, seq: u32, timestamp: u32) -> Result<VersionInfo, Mb85rcError> {
+        let (from, old) = self.find(seq)?;
+        let (slot, seq) = self.next_slot()?;
+
+        let mut crc = Self::header_crc(seq, timestamp, old.len);
+        crc::update_from_device(&mut crc, &mut self.dev, from * self.slot_size + HEADER, old.len as u32)?;
+        let info = VersionInfo { seq, timestamp, len: old.len, crc: crc.finish() };
+
+        if from != slot {
+            copy_within(&mut self.dev, from * self.slot_size + HEADER, slot * self.slot_size + HEADER, old.len as u32)?;
+        }
+        self.write_header(slot, &info)?;
+        Ok(info)
+    }
+}
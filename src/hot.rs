@@ -0,0 +1,99 @@
+use core::ops::Range;
+
+use crate::{Clock, FramDevice, Mb85rcError, PersistentStats};
+
+/// Device wrapper that counts writes to each of `N` regions and keeps the counts on the device
+///
+/// Shows which regions are hot, whether autosave and debounce intervals
+/// are doing their job, and catches a write loop gone wild before it shows
+/// up as a support ticket. A write touching several regions counts once
+/// for each. The counts are kept in a [`PersistentStats`] at `addr`, which
+/// [`poll`](Self::poll) flushes at most once every
+/// [`with_interval`](Self::with_interval) ticks; those flushes aren't
+/// counted.
+pub struct WriteCounters<D: FramDevice, const N: usize> {
+    dev: D,
+    regions: [Range<u32>; N],
+    stats: PersistentStats<N>,
+}
+
+impl<D: FramDevice, const N: usize> WriteCounters<D, N> {
+    /// Bytes of FRAM the counts take up at `addr`
+    pub const SIZE: u32 = PersistentStats::<N>::SIZE;
+
+    /// Count writes to `regions` of `dev`, carrying on from the counts stored at `addr`
+    pub fn load(mut dev: D, addr: u32, regions: [Range<u32>; N]) -> Result<Self, Mb85rcError> {
+        let stats = PersistentStats::load(&mut dev, addr)?;
+        Ok(Self { dev, regions, stats })
+    }
+
+    /// Flush from [`poll`](Self::poll) at most once every `ticks`
+    pub fn with_interval(mut self, ticks: u32) -> Self {
+        self.stats = self.stats.with_interval(ticks);
+        self
+    }
+
+    /// The regions being counted
+    pub fn regions(&self) -> &[Range<u32>; N] {
+        &self.regions
+    }
+
+    /// Writes to region `index` since the counts were last reset
+    ///
+    /// Fails with [`ErrorKind::OutOfRange`](crate::ErrorKind::OutOfRange) if there's no such region.
+    pub fn count(&self, index: usize) -> Result<u64, Mb85rcError> {
+        self.stats.counter(index)
+    }
+
+    /// Writes to every region since the counts were last reset
+    pub fn counts(&self) -> &[u64; N] {
+        self.stats.counters()
+    }
+
+    /// Store the counts if they changed and the interval has passed, returning whether they were
+    pub fn poll(&mut self, now: u32) -> Result<bool, Mb85rcError> {
+        self.stats.poll(&mut self.dev, now)
+    }
+
+    /// [`poll`](Self::poll) with the time from `clock`
+    pub fn poll_with<K: Clock + ?Sized>(&mut self, clock: &mut K) -> Result<bool, Mb85rcError> {
+        self.poll(clock.now())
+    }
+
+    /// Store the counts now
+    pub fn flush(&mut self) -> Result<(), Mb85rcError> {
+        self.stats.flush(&mut self.dev)
+    }
+
+    /// Set every count back to zero and store that
+    pub fn reset(&mut self) -> Result<(), Mb85rcError> {
+        self.stats.reset(&mut self.dev)
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+}
+
+impl<D: FramDevice, const N: usize> FramDevice for WriteCounters<D, N> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.dev.read_at(addr, buf)
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        self.dev.write_at(addr, buf)?;
+
+        let end = addr as u64 + buf.len() as u64;
+        for (i, region) in self.regions.iter().enumerate() {
+            if !buf.is_empty() && (addr as u64) < region.end as u64 && end > region.start as u64 {
+                self.stats.increment(i)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+/// Manufacturer code Fujitsu (now RAMXEED) FRAM reports in its device ID
+pub const FUJITSU_MANUFACTURER_ID: u16 = 0x00A;
+
+/// Known parts by density nybble and third ID byte
+const MODELS: &[(u8, u8, &str)] = &[
+    (0x0, 0x10, "MB85RC04V"),
+    (0x3, 0x58, "MB85RC64TA"),
+    (0x5, 0x10, "MB85RC256V"),
+    (0x6, 0x58, "MB85RC512T"),
+    (0x7, 0x58, "MB85RC1MT"),
+];
+
+/// Third ID byte of the known part with `density`, if there is one
+#[cfg(feature = "std")]
+pub(crate) fn product_for(density: u8) -> Option<u8> {
+    MODELS.iter().find(|(d, _, _)| *d == density).map(|(_, product, _)| *product)
+}
+
+/// Device ID read from an FRAM's reserved `0xF8` slave address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceId {
+    bytes: [u8; 3],
+}
+
+impl DeviceId {
+    /// Interpret the three bytes of a device-ID response
+    pub fn from_bytes(bytes: [u8; 3]) -> Self {
+        Self { bytes }
+    }
+
+    /// The raw bytes as read from the device
+    pub fn bytes(&self) -> [u8; 3] {
+        self.bytes
+    }
+
+    /// The 12-bit manufacturer code
+    pub fn manufacturer(&self) -> u16 {
+        ((self.bytes[0] as u16) << 4) | (self.bytes[1] >> 4) as u16
+    }
+
+    /// Whether the manufacturer code is Fujitsu/RAMXEED
+    pub fn is_fujitsu(&self) -> bool {
+        self.manufacturer() == FUJITSU_MANUFACTURER_ID
+    }
+
+    /// The density nybble, where the device holds 2^N KiB, or 512 bytes for 0
+    pub fn density(&self) -> u8 {
+        self.bytes[1] & 0xF
+    }
+
+    /// The third ID byte, which Fujitsu reserves for proprietary use
+    ///
+    /// Some parts and boards encode a revision or variant here
+    pub fn proprietary(&self) -> u8 {
+        self.bytes[2]
+    }
+
+    /// Size of the device in bytes according to its density
+    pub fn size(&self) -> u32 {
+        // the 4 Kbit MB85RC04V breaks the pattern
+        match self.density() {
+            0 => 512,
+            density => (1 << density) * 1024,
+        }
+    }
+
+    /// Part name for known devices, e.g. `"MB85RC256V"`
+    ///
+    /// Returns `None` for other manufacturers and parts not in the table
+    pub fn model(&self) -> Option<&'static str> {
+        if !self.is_fujitsu() {
+            return None;
+        }
+        MODELS.iter()
+            .find(|(density, product, _)| *density == self.density() && *product == self.proprietary())
+            .map(|(_, _, name)| *name)
+    }
+}
@@ -0,0 +1,137 @@
+use embedded_hal::blocking::i2c;
+use core::error::Error;
+use std::io::{Seek, Read, Write, ErrorKind};
+use std::io;
+
+use crate::{MB85RC, SharedFram, FramSlice, FramDevice, SeekFrom, WriteProtect};
+
+fn convert_seek(pos: io::SeekFrom) -> io::Result<SeekFrom> {
+    match pos {
+        io::SeekFrom::Start(p) => match u32::try_from(p) {
+            Ok(p) => Ok(SeekFrom::Start(p)),
+            Err(_) => Err(io::Error::new(ErrorKind::InvalidInput, "Cannot seek outside of device memory")),
+        },
+        io::SeekFrom::End(p) => Ok(SeekFrom::End(p)),
+        io::SeekFrom::Current(p) => Ok(SeekFrom::Current(p)),
+    }
+}
+
+impl<I2C, WP> Seek for MB85RC<I2C, WP> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        MB85RC::seek(self, convert_seek(pos)?)
+            .map(u64::from)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
+    }
+}
+
+impl<I2C, WP: WriteProtect> Read for MB85RC<I2C, WP>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        MB85RC::read(self, buf).map_err(io::Error::other)
+    }
+}
+
+impl<I2C, WP: WriteProtect> Write for MB85RC<I2C, WP>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        MB85RC::write(self, buf).map_err(io::Error::other)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // No need to flush anything
+        Ok(())
+    }
+}
+
+impl<I2C, WP> Seek for SharedFram<I2C, WP> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        SharedFram::seek(self, convert_seek(pos)?)
+            .map(u64::from)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
+    }
+}
+
+impl<I2C, WP: WriteProtect> Read for SharedFram<I2C, WP>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        SharedFram::read(self, buf).map_err(io::Error::other)
+    }
+}
+
+impl<I2C, WP: WriteProtect> Write for SharedFram<I2C, WP>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        SharedFram::write(self, buf).map_err(io::Error::other)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<D: FramDevice + ?Sized> Seek for FramSlice<'_, D> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        FramSlice::seek(self, convert_seek(pos)?)
+            .map(u64::from)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
+    }
+}
+
+impl<D: FramDevice + ?Sized> Read for FramSlice<'_, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        FramSlice::read(self, buf).map_err(io::Error::other)
+    }
+}
+
+impl<D: FramDevice + ?Sized> Write for FramSlice<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        FramSlice::write(self, buf).map_err(io::Error::other)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl<D: FramDevice> Seek for crate::EncryptedStream<D> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        crate::EncryptedStream::seek(self, convert_seek(pos)?)
+            .map(u64::from)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl<D: FramDevice> Read for crate::EncryptedStream<D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        crate::EncryptedStream::read(self, buf).map_err(io::Error::other)
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl<D: FramDevice> Write for crate::EncryptedStream<D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        crate::EncryptedStream::write(self, buf).map_err(io::Error::other)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError, ErrorKind, MAX_TRANSFER};
+
+/// Reads a range one `N`-byte chunk at a time into a reused buffer
+///
+/// Each call to [`next_chunk`](Self::next_chunk) reads the next chunk and
+/// lends it out along with its address, so a whole device can be streamed
+/// through a small buffer. The last chunk may be shorter. It can't be a
+/// real [`Iterator`] because each chunk borrows the buffer, so loop with
+/// `while let`.
+pub struct Chunks<'a, D: FramDevice + ?Sized, const N: usize> {
+    dev: &'a mut D,
+    range: Range<u32>,
+    buf: [u8; N],
+}
+
+impl<'a, D: FramDevice + ?Sized, const N: usize> Chunks<'a, D, N> {
+    /// Read `range` of `dev` in `N`-byte chunks
+    pub fn new(dev: &'a mut D, range: Range<u32>) -> Result<Self, Mb85rcError> {
+        if N == 0 || range.start > range.end || range.end > dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok(Self { dev, range, buf: [0u8; N] })
+    }
+
+    /// The part of the range that hasn't been read yet
+    pub fn remaining(&self) -> Range<u32> {
+        self.range.clone()
+    }
+
+    /// Read the next chunk, returning its address and contents
+    pub fn next_chunk(&mut self) -> Option<Result<(u32, &mut [u8]), Mb85rcError>> {
+        if self.range.is_empty() {
+            return None;
+        }
+
+        let addr = self.range.start;
+        let len = (self.range.end - addr).min(N as u32) as usize;
+        if let Err(e) = self.dev.read_at(addr, &mut self.buf[..len]) {
+            self.range.start = self.range.end;
+            return Some(Err(e));
+        }
+        self.range.start += len as u32;
+        Some(Ok((addr, &mut self.buf[..len])))
+    }
+}
+
+/// Pull-based reader over a range, for feeding parsers without `std::io`
+///
+/// Bytes come out one at a time through [`Iterator`], or as fixed-size
+/// records through [`read_record`](Self::read_record), while the reader
+/// refills an internal [`MAX_TRANSFER`]-byte buffer behind the scenes, one
+/// transaction per refill.
+pub struct RegionReader<'a, D: FramDevice + ?Sized> {
+    dev: &'a mut D,
+    range: Range<u32>,
+    chunk: usize,
+    buf: [u8; MAX_TRANSFER],
+    pos: usize,
+    len: usize,
+}
+
+impl<'a, D: FramDevice + ?Sized> RegionReader<'a, D> {
+    /// Read `range` of `dev`
+    pub fn new(dev: &'a mut D, range: Range<u32>) -> Result<Self, Mb85rcError> {
+        if range.start > range.end || range.end > dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok(Self { dev, range, chunk: MAX_TRANSFER, buf: [0u8; MAX_TRANSFER], pos: 0, len: 0 })
+    }
+
+    /// Refill at most `chunk` bytes at a time, capped at [`MAX_TRANSFER`]
+    pub fn with_chunk(mut self, chunk: usize) -> Self {
+        self.chunk = chunk.clamp(1, MAX_TRANSFER);
+        self
+    }
+
+    /// Bytes left to read
+    pub fn remaining(&self) -> u32 {
+        (self.len - self.pos) as u32 + (self.range.end - self.range.start)
+    }
+
+    fn refill(&mut self) -> Result<(), Mb85rcError> {
+        let len = (self.range.end - self.range.start).min(self.chunk as u32) as usize;
+        self.dev.read_at(self.range.start, &mut self.buf[..len])?;
+        self.range.start += len as u32;
+        self.pos = 0;
+        self.len = len;
+        Ok(())
+    }
+
+    /// Fill `buf` from the stream
+    ///
+    /// Fails with [`ErrorKind::OutOfRange`], consuming nothing, if fewer
+    /// than `buf.len()` bytes are left.
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        if buf.len() as u64 > self.remaining() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        let mut done = 0;
+        while done < buf.len() {
+            if self.pos == self.len {
+                self.refill()?;
+            }
+            let n = (buf.len() - done).min(self.len - self.pos);
+            buf[done..done + n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+            self.pos += n;
+            done += n;
+        }
+        Ok(())
+    }
+
+    /// Read the next `N`-byte record, `None` once fewer than `N` bytes are left
+    pub fn read_record<const N: usize>(&mut self) -> Result<Option<[u8; N]>, Mb85rcError> {
+        if (N as u64) > self.remaining() as u64 {
+            return Ok(None);
+        }
+        let mut record = [0u8; N];
+        self.read_exact(&mut record)?;
+        Ok(Some(record))
+    }
+}
+
+impl<D: FramDevice + ?Sized> Iterator for RegionReader<'_, D> {
+    type Item = Result<u8, Mb85rcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == self.len {
+            if self.range.is_empty() {
+                return None;
+            }
+            if let Err(e) = self.refill() {
+                self.range.start = self.range.end;
+                return Some(Err(e));
+            }
+        }
+        self.pos += 1;
+        Some(Ok(self.buf[self.pos - 1]))
+    }
+}
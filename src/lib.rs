@@ -1,8 +1,280 @@
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
 //! Quick and basic implimentation of an interface for writing and reading
 //! to MB85RC-series I2C FRAM modules
 //! 
 //! Developed with the MB85RC256V in mind
+//!
+//! The driver itself only needs `core` and never allocates. Enable the
+//! `std-io` feature (on by default) for `std::io::{Read, Write, Seek}`
+//! implementations, and `critical-section` for a global `GlobalFram`
+//! slot reachable from anywhere, including panic handlers.
 
 mod mb85rc;
-pub use mb85rc::{MB85RC, Builder};
+pub use mb85rc::{MB85RC, Builder, Ready, NeedsSize, SeekFrom, MAX_TRANSFER};
+
+mod error;
+pub use error::{Mb85rcError, ErrorKind, ConnectError};
+
+mod address;
+pub use address::FramAddress;
+
+mod addressing;
+pub use addressing::{Addressing, SingleAddress, Banked, OneByte, TwoByte, OneByteBanked, TwoByteBanked, MAX_ADDRESS_BYTES};
+
+mod device;
+pub use device::FramDevice;
+
+mod ext;
+pub use ext::{FramExt, Pattern};
+
+mod id;
+pub use id::{DeviceId, FUJITSU_MANUFACTURER_ID};
+
+mod scan;
+pub use scan::{scan, Probe, ScanEntry, FRAM_ADDRESSES};
+
+mod slice;
+pub use slice::FramSlice;
+
+mod iter;
+pub use iter::{Chunks, RegionReader};
+
+mod buffered;
+pub use buffered::BufferedFram;
+
+mod bridge;
+pub use bridge::UsbBridge;
+
+mod protect;
+pub use protect::{WriteProtect, NoWriteProtect};
+
+mod health;
+pub use health::{HealthMonitor, Health};
+
+mod diagnostics;
+pub use diagnostics::Diagnostics;
+
+mod fair;
+pub use fair::FairFram;
+
+mod queue;
+pub use queue::WriteQueue;
+
+mod cache;
+pub use cache::{CachedFram, CacheLine};
+
+mod observe;
+pub use observe::{Observed, Observer};
+
+mod clock;
+pub use clock::Clock;
+#[cfg(feature = "std")]
+pub use clock::SystemClock;
+#[cfg(feature = "fugit")]
+pub use clock::FugitClock;
+#[cfg(feature = "embedded-time")]
+pub use clock::EmbeddedTimeClock;
+
+mod audit;
+pub use audit::{AuditLog, AuditRecord, Audited};
+
+mod batch;
+pub use batch::Batch;
+
+mod snapshot;
+pub use snapshot::Snapshot;
+
+mod codec;
+pub use codec::{Codec, LittleEndian};
+
+mod history;
+pub use history::{ConfigHistory, VersionInfo};
+
+mod autosave;
+pub use autosave::Autosave;
+
+mod debounce;
+pub use debounce::Debounced;
+
+mod migrate;
+pub use migrate::{Migrator, Migration};
+
+mod tlv;
+pub use tlv::{TlvStore, TlvRecord, TlvRecords};
+
+mod string;
+pub use string::FramString;
+
+mod bitmap;
+pub use bitmap::FramBitmap;
+
+mod stack;
+pub use stack::FramStack;
+
+mod slots;
+pub use slots::SlotAllocator;
+
+mod heap;
+pub use heap::{FramHeap, Block, HeapStats};
+
+mod region;
+pub use region::{RegionTable, Region, REGION_NAME_LEN};
+
+mod superblock;
+pub use superblock::{Superblock, MountState, SUPERBLOCK_SIZE};
+
+mod files;
+pub use files::{FileStore, FileInfo, Files, FILE_NAME_LEN};
+
+mod watch;
+pub use watch::Watcher;
+
+mod schedule;
+pub use schedule::BackupSchedule;
+
+mod mirror;
+
+mod ota;
+pub use ota::{OtaStaging, OtaStatus, OtaState};
+
+mod boot;
+pub use boot::{BootFlags, BootState, BootSlot};
+
+mod handoff;
+pub use handoff::{Mailbox, Handoff};
+
+mod dump;
+pub use dump::{CrashDump, CrashWriter, DumpSection};
+
+mod resets;
+pub use resets::ResetCounters;
+
+mod stats;
+pub use stats::PersistentStats;
+
+mod hot;
+pub use hot::WriteCounters;
+
+mod samples;
+pub use samples::SampleLog;
+
+mod counter;
+pub use counter::{MonotonicCounter, NonceCounter};
+
+mod retention;
+pub use retention::{write_retention_pattern, verify_retention_pattern, RetentionReport};
+
+mod delta;
+pub use delta::{make_delta, apply_delta};
+
+mod resume;
+pub use resume::{ResumableRestore, RestoreProgress};
+
+mod borrowed;
+pub use borrowed::BorrowedI2c;
+
+mod recovery;
+pub use recovery::{BusRecovery, RecoveringI2c, RecoveryPolicy, Escalate, Failure, Operation, Action};
+
+mod chunked;
+pub use chunked::{ChunkedWrite, ChunkedRead};
+
+#[cfg(feature = "critical-section")]
+mod global;
+#[cfg(feature = "critical-section")]
+pub use global::GlobalFram;
+
+#[cfg(feature = "embassy")]
+mod embassy;
+#[cfg(feature = "embassy")]
+pub use embassy::{AsyncMB85RC, SharedBusFram};
+
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub use shared::SharedFram;
+
+#[cfg(feature = "std")]
+mod backup;
+#[cfg(feature = "std")]
+pub use backup::{BackupManager, dump_parallel};
+
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+pub use config::FramConfig;
+
+#[cfg(feature = "std")]
+mod sysfs;
+#[cfg(feature = "std")]
+pub use sysfs::{I2cAdapter, i2c_adapters, find_i2c_adapter, find_i2c_alias};
+
+#[cfg(feature = "std")]
+mod export;
+#[cfg(feature = "std")]
+pub use export::{Exporter, ExportFormat, Field, FieldType};
+
+#[cfg(feature = "linux")]
+mod linux;
+
+#[cfg(feature = "std")]
+mod sim;
+#[cfg(feature = "std")]
+pub use sim::{SimFram, SimError, Fault};
+
+#[cfg(feature = "crypto")]
+mod auth;
+#[cfg(feature = "crypto")]
+pub use auth::AuthRegion;
+
+#[cfg(feature = "crypto")]
+mod vault;
+#[cfg(feature = "crypto")]
+pub use vault::KeyVault;
+
+#[cfg(feature = "crypto")]
+mod secret;
+#[cfg(feature = "crypto")]
+pub use secret::SecretStore;
+
+#[cfg(feature = "crypto")]
+mod encrypted;
+#[cfg(feature = "crypto")]
+pub use encrypted::EncryptedStream;
+
+#[cfg(feature = "crypto")]
+mod password;
+#[cfg(feature = "crypto")]
+pub use password::{PasswordBlock, Kdf, DerivedKeys};
+
+#[cfg(feature = "compression")]
+mod compressed;
+#[cfg(feature = "compression")]
+pub use compressed::CompressedRegion;
+
+#[cfg(feature = "signing")]
+mod signed;
+#[cfg(feature = "signing")]
+pub use signed::ImageVerifier;
+
+pub mod crc;
+
+pub mod text;
+
+pub mod telemetry;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "cbor")]
+pub mod cbor;
+
+#[cfg(feature = "std-io")]
+mod io;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "python")]
+pub mod python;
@@ -0,0 +1,30 @@
+use std::io;
+use std::path::Path;
+
+use linux_embedded_hal::I2cdev;
+
+use crate::{MB85RC, Builder, FramConfig};
+
+impl MB85RC<I2cdev> {
+    /// Open the FRAM at `address` on the i2c-dev bus at `path`, e.g. `MB85RC::open("/dev/i2c-1", 0x50)`
+    ///
+    /// The size is read from the device ID, and connecting fails unless the
+    /// ID says it's a Fujitsu/RAMXEED part. Use a [`Builder`] for anything
+    /// else, such as parts without a device ID.
+    pub fn open<P: AsRef<Path>>(path: P, address: u8) -> io::Result<Self> {
+        let builder = Builder::new().with_address(address).with_id_check(true);
+        connect(path.as_ref(), builder)
+    }
+}
+
+impl FramConfig {
+    /// Open [`bus`](Self::bus) and connect with these settings
+    pub fn connect(&self) -> io::Result<MB85RC<I2cdev>> {
+        connect(Path::new(&self.bus), self.builder())
+    }
+}
+
+fn connect(path: &Path, builder: Builder) -> io::Result<MB85RC<I2cdev>> {
+    let i2c = I2cdev::new(path)?;
+    builder.try_connect_i2c(i2c).map_err(|e| io::Error::other(e.error))
+}
@@ -1,161 +1,447 @@
 use embedded_hal::blocking::i2c;
-use core::fmt;
-use std::error::Error;
-use std::io::{Seek, SeekFrom, Read, Write, ErrorKind};
-use std::io;
+use core::error::Error;
+use core::marker::PhantomData;
+use core::ops::Range;
+
+use crate::{FramAddress, FramSlice, Chunks, RegionReader, Batch, Snapshot, BorrowedI2c, Mb85rcError, ErrorKind, ConnectError, DeviceId, Diagnostics};
+use crate::addressing::{Addressing, SingleAddress, Banked, TwoByte, MAX_ADDRESS_BYTES};
+use crate::{WriteProtect, NoWriteProtect, UsbBridge};
+use crate::scan::{read_device_id, FRAM_ADDRESSES};
+
+/// Largest number of data bytes moved in a single I2C transaction
+///
+/// Writes are staged in a stack buffer of this size, so this also bounds how
+/// much stack a transfer uses. Lower it per device with [`Builder::with_max_transfer`].
+pub const MAX_TRANSFER: usize = 256;
+
+/// Retry settings from [`Builder::with_arbitration_retry`]
+#[derive(Clone, Copy)]
+pub(crate) struct ArbitrationRetry {
+    pub(crate) retries: u8,
+    is_loss: fn(&dyn Error) -> bool,
+}
+
+/// Run the transaction `op`, retrying it after arbitration losses if `retry` allows
+fn retry_arbitration<E: Error>(retry: Option<ArbitrationRetry>, mut op: impl FnMut() -> Result<(), E>) -> Result<(), Mb85rcError> {
+    let mut attempts = 0;
+    loop {
+        match op() {
+            Ok(()) => return Ok(()),
+            Err(e) => match retry {
+                Some(r) if attempts < r.retries && (r.is_loss)(&e) => attempts += 1,
+                _ => return Err(Mb85rcError::i2c(&e)),
+            },
+        }
+    }
+}
 
 /// Interface for the FRAM module over I2C
 /// 
-/// Construct this using a [`Builder`] to set the address and size
-pub struct MB85RC<I2C> {
+/// Construct this using a [`Builder`] to set the address and size. `WP` is
+/// the write-protect pin, if [one was given](Builder::with_write_protect).
+pub struct MB85RC<I2C, WP = NoWriteProtect> {
     i2c: I2C,
     device_addr: u8,
     device_size: u32,
-    cursor: u16,
+    max_transfer: usize,
+    arbitration_retry: Option<ArbitrationRetry>,
+    addressing: &'static dyn Addressing,
+    write_protect: WP,
+    protected: bool,
+    auto_unprotect: bool,
+    cursor: FramAddress,
 }
 
-impl<I2C> MB85RC<I2C>
+impl<I2C, WP: WriteProtect> MB85RC<I2C, WP>
 where
     I2C: i2c::WriteRead + i2c::Write,
     <I2C as i2c::WriteRead>::Error: Error,
     <I2C as i2c::Write>::Error: Error,
 {
-    fn new(mut i2c: I2C, device_addr: u8, size: Option<u32>) -> Self {
-        let device_size = match size {
-            Some(s) => s,
-            None => {
-                let meta = match Self::read_metadata(&mut i2c, device_addr) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        panic!("Could not automatically get FRAM size. Use `Builder::with_size(u32)`.");
-                    },
-                };
-                let size = (1 << (meta[1] & 0xF)) * 1024;
-                println!("Device size reports to be {} bytes.", size);
-                size
-            },
+    fn new<S>(mut i2c: I2C, mut config: Builder<S, WP>) -> Result<Self, ConnectError<I2C>> {
+        // before anything else touches the bus, so the device is never writable by accident
+        if let Err(e) = config.write_protect.set_protected(config.protected) {
+            return Err(ConnectError::new(e, i2c));
+        }
+
+        let device_addr = if config.discover_address {
+            match Self::discover_address(&mut i2c, config.device_addr, config.addressing) {
+                Ok(a) => a,
+                Err(e) => return Err(ConnectError::new(e, i2c)),
+            }
+        } else {
+            config.device_addr
         };
 
-        Self {
+        let id = if config.device_size.is_none() || config.check_id {
+            match Self::read_metadata(&mut i2c, device_addr) {
+                Ok(v) => Some(DeviceId::from_bytes(v)),
+                Err(_) if config.check_id => return Err(ConnectError::new(Mb85rcError::new(ErrorKind::NoDeviceId), i2c)),
+                Err(_) => return Err(ConnectError::new(Mb85rcError::new(ErrorKind::UnknownSize), i2c)),
+            }
+        } else {
+            None
+        };
+
+        if let Some(id) = id.filter(|id| config.check_id && !id.is_fujitsu()) {
+            return Err(ConnectError::new(Mb85rcError::new(ErrorKind::WrongDevice { found: id.manufacturer() }), i2c));
+        }
+
+        // the ID is always read when no size is given
+        let device_size = config.device_size.unwrap_or_else(|| id.unwrap().size());
+
+        Ok(Self {
             i2c,
             device_addr,
             device_size,
-            cursor: 0,
+            max_transfer: config.max_transfer.clamp(1, MAX_TRANSFER),
+            arbitration_retry: config.arbitration_retry,
+            addressing: config.addressing,
+            write_protect: config.write_protect,
+            protected: config.protected,
+            auto_unprotect: config.auto_unprotect,
+            cursor: FramAddress::new(0, device_size).unwrap(),
+        })
+    }
+
+    /// Find the FRAM, preferring `preferred` and falling back to the one other address that answers
+    fn discover_address(i2c: &mut I2C, preferred: u8, addressing: &dyn Addressing) -> Result<u8, Mb85rcError> {
+        // parts without a device ID still answer a plain read, framed the way the
+        // part expects so no address byte is taken for data
+        let mut addr_buf = [0u8; MAX_ADDRESS_BYTES];
+        let n = addressing.address_bytes(0, &mut addr_buf);
+        if read_device_id(i2c, preferred).is_ok() || i2c.write_read(preferred, &addr_buf[..n], &mut [0u8]).is_ok() {
+            return Ok(preferred);
+        }
+
+        // only the device-ID command is safe to send to whatever else is out there
+        let mut candidates = 0u8;
+        for (i, addr) in FRAM_ADDRESSES.enumerate() {
+            if addr != preferred && read_device_id(i2c, addr).is_ok() {
+                candidates |= 1 << i;
+            }
+        }
+
+        if candidates.count_ones() == 1 {
+            Ok(FRAM_ADDRESSES.start() + candidates.trailing_zeros() as u8)
+        } else {
+            Err(Mb85rcError::new(ErrorKind::AddressNotFound { candidates }))
         }
     }
 
+    fn check_access(&self, addr: FramAddress, len: usize) -> Result<(), Mb85rcError> {
+        match FramAddress::new(addr.get(), self.device_size) {
+            Some(a) if a.fits(len) => Ok(()),
+            _ => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    /// Bytes from `pos` the next transaction can move, at most `remaining`
+    fn transaction_len(&self, pos: u32, remaining: usize) -> usize {
+        remaining.min(self.max_transfer).min(self.addressing.span(pos) as usize)
+    }
+
     /// Directly read bytes at `addr` into the provided buffer
-    pub fn fram_read(&mut self, addr: u16, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
-        let addr_hi = (addr >> 8) as u8;
-        let addr_lo = (addr & 0xFF) as u8;
-        let addr_buf = [addr_hi, addr_lo];
+    ///
+    /// Long reads are split into transactions of at most the configured max transfer size
+    pub fn fram_read(&mut self, addr: FramAddress, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        self.check_access(addr, buf.len())?;
 
-        match self.i2c.write_read(self.device_addr, &addr_buf, buf) {
-            Ok(_) => Ok(buf.len()),
-            Err(e) => Err(Mb85rcError::new(format!("I2C Error: {}", e).as_str())),
+        let mut addr_buf = [0u8; MAX_ADDRESS_BYTES];
+        let mut pos = addr.get();
+        let mut rest = &mut buf[..];
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at_mut(self.transaction_len(pos, rest.len()));
+            let slave = self.addressing.slave_address(self.device_addr, pos);
+            let n = self.addressing.address_bytes(pos, &mut addr_buf);
+            retry_arbitration(self.arbitration_retry, || self.i2c.write_read(slave, &addr_buf[..n], chunk))?;
+            pos += chunk.len() as u32;
+            rest = tail;
         }
+
+        Ok(buf.len())
     }
 
     /// Directly write bytes at `addr` from the provided buffer
-    pub fn fram_write(&mut self, addr: u16, buf: &[u8]) -> Result<usize, Mb85rcError> {
-        let addr_hi = (addr >> 8) as u8;
-        let addr_lo = (addr & 0xFF) as u8;
-        let addr_buf = [addr_hi, addr_lo];
-        let write_buf = [&addr_buf, buf].concat();
+    ///
+    /// Long writes are split into transactions of at most the configured max transfer size.
+    /// While the device is [protected](Self::protect) this fails with
+    /// [`ErrorKind::Locked`], unless [auto-unprotect](Builder::with_auto_unprotect)
+    /// is on, in which case WP is lowered for the write and raised again after it.
+    pub fn fram_write(&mut self, addr: FramAddress, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        self.check_access(addr, buf.len())?;
+        if !self.protected {
+            return self.write_transactions(addr, buf);
+        }
+        if !self.auto_unprotect {
+            return Err(Mb85rcError::new(ErrorKind::Locked));
+        }
+
+        self.write_protect.set_protected(false)?;
+        let written = self.write_transactions(addr, buf);
+        // protect again even if the write failed
+        let relocked = self.write_protect.set_protected(true);
+        written.and_then(|n| relocked.map(|_| n))
+    }
+
+    /// Raise WP so the device ignores writes
+    ///
+    /// Fails with [`ErrorKind::WrongState`] if there's no WP pin.
+    pub fn protect(&mut self) -> Result<(), Mb85rcError> {
+        self.write_protect.set_protected(true)?;
+        self.protected = true;
+        Ok(())
+    }
 
-        match self.i2c.write(self.device_addr, &write_buf) {
-            Ok(_) => Ok(buf.len()),
-            Err(e) => Err(Mb85rcError::new(format!("I2C Error: {}", e).as_str())),
+    /// Lower WP so the device can be written
+    pub fn unprotect(&mut self) -> Result<(), Mb85rcError> {
+        self.write_protect.set_protected(false)?;
+        self.protected = false;
+        Ok(())
+    }
+
+    fn write_transactions(&mut self, addr: FramAddress, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        let mut write_buf = [0u8; MAX_ADDRESS_BYTES + MAX_TRANSFER];
+        let mut addr_buf = [0u8; MAX_ADDRESS_BYTES];
+        let mut pos = addr.get();
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at(self.transaction_len(pos, rest.len()));
+            let slave = self.addressing.slave_address(self.device_addr, pos);
+            let n = self.addressing.address_bytes(pos, &mut addr_buf);
+            let len = n + chunk.len();
+            write_buf[..n].copy_from_slice(&addr_buf[..n]);
+            write_buf[n..len].copy_from_slice(chunk);
+            retry_arbitration(self.arbitration_retry, || self.i2c.write(slave, &write_buf[..len]))?;
+            pos += chunk.len() as u32;
+            rest = tail;
         }
+
+        Ok(buf.len())
     }
 
     fn read_metadata(i2c: &mut I2C, addr: u8) -> Result<[u8;3], Mb85rcError> {
-        // density of the FRAM module is 2^N kB, where N is the lower nybble of the second metadata byte
-        let write_buf = [addr << 1];
-        let mut read_buf = [0u8; 3];
+        read_device_id(i2c, addr).map_err(|e| Mb85rcError::i2c(&e))
+    }
+
+    /// Borrow a window of device memory with its own offsets and cursor
+    ///
+    /// See [`FramSlice`]
+    pub fn slice(&mut self, range: Range<u32>) -> Result<FramSlice<'_, Self>, Mb85rcError> {
+        FramSlice::new(self, range)
+    }
+
+    /// Read `range` in `N`-byte chunks through a reused buffer
+    ///
+    /// See [`Chunks`]
+    pub fn chunks<const N: usize>(&mut self, range: Range<u32>) -> Result<Chunks<'_, Self, N>, Mb85rcError> {
+        Chunks::new(self, range)
+    }
+
+    /// Stream `range` a byte or record at a time, refilling [`max_transfer`](Self::max_transfer) bytes at once
+    ///
+    /// See [`RegionReader`]
+    pub fn reader(&mut self, range: Range<u32>) -> Result<RegionReader<'_, Self>, Mb85rcError> {
+        let chunk = self.max_transfer();
+        Ok(RegionReader::new(self, range)?.with_chunk(chunk))
+    }
+
+    /// Start recording a [`Batch`] of writes, buffered in `N` bytes, to apply together
+    pub fn begin_batch<const N: usize>(&mut self) -> Batch<'_, Self, N> {
+        Batch::new(self)
+    }
+
+    /// Save a copy of `region` in the scratch area at `scratch`
+    ///
+    /// See [`Snapshot`]
+    pub fn snapshot(&mut self, region: Range<u32>, scratch: u32) -> Result<Snapshot, Mb85rcError> {
+        Snapshot::take(self, region, scratch)
+    }
+
+    /// Read the device ID and look up the part name, e.g. `"MB85RC256V"`
+    ///
+    /// See [`DeviceId::model`]
+    pub fn model(&mut self) -> Result<Option<&'static str>, Mb85rcError> {
+        Ok(self.device_id()?.model())
+    }
+
+    /// Read the device ID
+    pub fn device_id(&mut self) -> Result<DeviceId, Mb85rcError> {
+        Self::read_metadata(&mut self.i2c, self.device_addr).map(DeviceId::from_bytes)
+    }
 
-        match i2c.write_read(0xF8 >> 1, &write_buf, &mut read_buf) {
-            Ok(_) => Ok(read_buf),
-            Err(e) => Err(Mb85rcError::new(format!("I2C Error: {}", e).as_str())),
+    /// Collect a [`Diagnostics`] report
+    ///
+    /// This reads the device ID, so it touches the bus, but never fails: if
+    /// the ID can't be read the report says so and records why in
+    /// `last_error`. The health fields are left empty, since the driver
+    /// doesn't keep track of them itself.
+    pub fn diagnostics(&mut self) -> Diagnostics {
+        let id = self.device_id();
+        Diagnostics {
+            device_id: id.ok(),
+            address: self.device_addr,
+            size: self.device_size,
+            max_transfer: self.max_transfer,
+            arbitration_retries: self.arbitration_retry.map_or(0, |r| r.retries),
+            health: None,
+            consecutive_failures: 0,
+            failures: 0,
+            crc_errors: 0,
+            last_error: id.err().map(|e| e.kind()),
         }
     }
 
+    /// Read the raw device-ID response into `buf`
+    ///
+    /// The standard ID is three bytes, but this reads as many as `buf` holds
+    /// for parts that send vendor-specific data after them
+    pub fn device_id_raw(&mut self, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.i2c.write_read(0xF8 >> 1, &[self.device_addr << 1], buf)
+            .map_err(|e| Mb85rcError::i2c(&e))
+    }
+
+    /// Read bytes at the cursor into the provided buffer and advance the cursor
+    ///
+    /// Reads stop at the end of device memory, so fewer bytes than requested
+    /// (or none) may be returned
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        let n = self.fram_read(self.cursor, &mut buf[..len])?;
+        self.cursor = self.cursor.checked_add(n as u32).unwrap();
+        Ok(n)
+    }
+
+    /// Write bytes from the provided buffer at the cursor and advance the cursor
+    ///
+    /// Writes stop at the end of device memory, so fewer bytes than provided
+    /// (or none) may be written
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        let n = self.fram_write(self.cursor, &buf[..len])?;
+        self.cursor = self.cursor.checked_add(n as u32).unwrap();
+        Ok(n)
+    }
+}
+
+impl<I2C, WP> MB85RC<I2C, WP> {
+    /// Whether WP is raised and writes are refused
+    pub fn is_protected(&self) -> bool {
+        self.protected
+    }
+
+    /// Get a checked [`FramAddress`] for byte `addr` of this device
+    pub fn address(&self, addr: u32) -> Result<FramAddress, Mb85rcError> {
+        FramAddress::new(addr, self.device_size)
+            .ok_or(Mb85rcError::new(ErrorKind::InvalidAddress))
+    }
+
     /// Get the auto-detected or [manually set](Builder::with_size) size of the device
     pub fn fram_size(&self) -> u32 {
         self.device_size
     }
-}
 
-impl<I2C> Seek for MB85RC<I2C> {
-    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
-        match pos {
-            SeekFrom::Start(p) => {
-                let new_cursor = p as i64;
+    /// Get the largest number of data bytes sent or received in one I2C transaction
+    pub fn max_transfer(&self) -> usize {
+        self.max_transfer
+    }
 
-                if new_cursor >= self.device_size.into() {
-                    Err(io::Error::new(ErrorKind::UnexpectedEof, "Cannot seek past device memory size"))
-                } else {
-                    self.cursor = p as u16;
-                    Ok(self.cursor.into())
-                }
-            },
-            SeekFrom::Current(p) => {
-                let new_cursor = (self.cursor as i64) + p;
-                
-                if new_cursor < 0 {
-                    Err(io::Error::new(ErrorKind::InvalidInput, "Invalid argument (position would be negative)"))
-                } else {
-                    self.cursor = new_cursor as u16;
-                    Ok(self.cursor.into())
-                }
-            },
-            SeekFrom::End(p) => {
-                let new_cursor = (self.cursor as i64) + p;
-
-                if new_cursor < 0 {
-                    Err(io::Error::new(ErrorKind::InvalidInput, "Invalid argument (position would be negative)"))
-                } else if new_cursor >= self.device_size.into() {
-                    Err(io::Error::new(ErrorKind::UnexpectedEof, "Cannot seek past device memory size"))
-                } else {
-                    self.cursor = new_cursor as u16;
-                    Ok(self.cursor.into())
-                }
-            },
-        }
+    /// Current position of the cursor used by [`read`](Self::read) and [`write`](Self::write)
+    pub fn position(&self) -> FramAddress {
+        self.cursor
     }
-}
 
-impl<I2C> Read for MB85RC<I2C> 
-where
-    I2C: i2c::WriteRead + i2c::Write,
-    <I2C as i2c::WriteRead>::Error: Error,
-    <I2C as i2c::Write>::Error: Error,
-{
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.fram_read(self.cursor, buf).map_err(|e| io::Error::new(ErrorKind::Other, e))
+    /// Get temporary mutable access to the I2C bus
+    ///
+    /// For sending transactions the driver doesn't wrap (vendor commands,
+    /// debugging) without giving up the interface
+    pub fn with_bus<R>(&mut self, f: impl FnOnce(&mut I2C) -> R) -> R {
+        f(&mut self.i2c)
+    }
+
+    /// Deconstruct the interface and hand back the I2C bus and the write-protect pin
+    ///
+    /// The pin is handed back in whatever state it was last driven to.
+    pub fn release_with_pin(self) -> (I2C, WP) {
+        (self.i2c, self.write_protect)
+    }
+
+    /// Move the cursor, returning the new position
+    ///
+    /// The cursor may be placed anywhere from the start of the device up to
+    /// (and including) the end of device memory
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<u32, Mb85rcError> {
+        self.cursor = pos.resolve(self.cursor)?;
+        Ok(self.cursor.get())
     }
 }
 
-impl<I2C> Write for MB85RC<I2C>
-where
-    I2C: i2c::WriteRead + i2c::Write,
-    <I2C as i2c::WriteRead>::Error: Error,
-    <I2C as i2c::Write>::Error: Error,
-{
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.fram_write(self.cursor, buf).map_err(|e| io::Error::new(ErrorKind::Other, e))
+impl<I2C> MB85RC<I2C> {
+    /// Deconstruct the interface and hand back the I2C bus so it can be reused
+    ///
+    /// With a write-protect pin configured, use
+    /// [`release_with_pin`](Self::release_with_pin) to get the pin back too.
+    pub fn release(self) -> I2C {
+        self.i2c
     }
+}
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        // No need to flush anything
-        Ok(())
+/// Cursor movement for [`MB85RC::seek`], mirroring `std::io::SeekFrom`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// Set the cursor to this many bytes from the start of the device
+    Start(u32),
+    /// Set the cursor relative to the end of device memory
+    End(i64),
+    /// Set the cursor relative to its current position
+    Current(i64),
+}
+
+impl SeekFrom {
+    /// Work out where a cursor currently at `cursor` would end up
+    pub(crate) fn resolve(self, cursor: FramAddress) -> Result<FramAddress, Mb85rcError> {
+        let capacity = cursor.capacity();
+        let target = match self {
+            SeekFrom::Start(p) => FramAddress::new(p, capacity),
+            SeekFrom::Current(p) => cursor.checked_offset(p),
+            SeekFrom::End(p) => FramAddress::end(capacity).checked_offset(p),
+        };
+
+        target.ok_or(Mb85rcError::new(ErrorKind::InvalidSeek))
     }
 }
 
+/// [`Builder`] state that can connect
+///
+/// Either a size was given, or it will be read from the device ID.
+pub enum Ready {}
+
+/// [`Builder`] state that has to be given a size before it can connect
+///
+/// See [`Builder::without_size_detection`] and
+/// [`Builder::with_banked_addressing`].
+pub enum NeedsSize {}
+
 /// Builder to create the interface with parameters
-pub struct Builder {
-    device_addr: u8,
-    device_size: Option<u32>,
+///
+/// The type parameter tracks whether the configuration can work at all:
+/// only a `Builder<Ready>` has the `connect` methods, so asking for a part
+/// with no device ID to be connected without telling it the size is a
+/// compile error rather than an [`ErrorKind::UnknownSize`] at runtime.
+/// `WP` is the write-protect pin, if one is given with
+/// [`with_write_protect`](Builder::with_write_protect).
+pub struct Builder<S = Ready, WP = NoWriteProtect> {
+    pub(crate) device_addr: u8,
+    pub(crate) device_size: Option<u32>,
+    pub(crate) max_transfer: usize,
+    pub(crate) discover_address: bool,
+    pub(crate) check_id: bool,
+    pub(crate) arbitration_retry: Option<ArbitrationRetry>,
+    pub(crate) addressing: &'static dyn Addressing,
+    pub(crate) write_protect: WP,
+    pub(crate) protected: bool,
+    pub(crate) auto_unprotect: bool,
+    state: PhantomData<S>,
 }
 
 impl Builder {
@@ -164,6 +450,86 @@ impl Builder {
         Self {
             device_addr: 0x50,
             device_size: None,
+            max_transfer: MAX_TRANSFER,
+            discover_address: false,
+            check_id: false,
+            arbitration_retry: None,
+            addressing: &TwoByte,
+            write_protect: NoWriteProtect,
+            protected: false,
+            auto_unprotect: false,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<WP> Builder<Ready, WP> {
+    /// Don't read the size from the device ID, so it has to be given with [`with_size`](Builder::with_size)
+    ///
+    /// For parts that don't implement the device-ID command, like the
+    /// MB85RC16. Any size already given is forgotten.
+    pub fn without_size_detection(self) -> Builder<NeedsSize, WP> {
+        Builder { device_size: None, ..self.into_state() }
+    }
+}
+
+impl<S> Builder<S> {
+    /// Drive the FRAM's WP pin with `write_protect`, starting out protected
+    ///
+    /// The pin is raised as the very first step of connecting, before
+    /// anything is sent on the bus, and the interface refuses writes until
+    /// [`unprotect`](MB85RC::unprotect) is called. Change that with
+    /// [`with_default_protection`](Builder::with_default_protection) and
+    /// [`with_auto_unprotect`](Builder::with_auto_unprotect).
+    pub fn with_write_protect<WP: WriteProtect>(self, write_protect: WP) -> Builder<S, WP> {
+        Builder {
+            device_addr: self.device_addr,
+            device_size: self.device_size,
+            max_transfer: self.max_transfer,
+            discover_address: self.discover_address,
+            check_id: self.check_id,
+            arbitration_retry: self.arbitration_retry,
+            addressing: self.addressing,
+            write_protect,
+            protected: true,
+            auto_unprotect: self.auto_unprotect,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<S, WP: WriteProtect> Builder<S, WP> {
+    /// Whether WP starts out raised when connecting
+    pub fn with_default_protection(mut self, protected: bool) -> Self {
+        self.protected = protected;
+        self
+    }
+
+    /// Lower WP just for the duration of each write instead of refusing writes while protected
+    ///
+    /// The device then stays protected between writes without the caller
+    /// having to manage the pin, which keeps a runaway bus master or glitch
+    /// from changing it the rest of the time.
+    pub fn with_auto_unprotect(mut self, enabled: bool) -> Self {
+        self.auto_unprotect = enabled;
+        self
+    }
+}
+
+impl<S, WP> Builder<S, WP> {
+    fn into_state<T>(self) -> Builder<T, WP> {
+        Builder {
+            device_addr: self.device_addr,
+            device_size: self.device_size,
+            max_transfer: self.max_transfer,
+            discover_address: self.discover_address,
+            check_id: self.check_id,
+            arbitration_retry: self.arbitration_retry,
+            addressing: self.addressing,
+            write_protect: self.write_protect,
+            protected: self.protected,
+            auto_unprotect: self.auto_unprotect,
+            state: PhantomData,
         }
     }
 
@@ -174,42 +540,130 @@ impl Builder {
     }
 
     /// Set the size of the FRAM module in bytes (overrides auto-detection)
-    pub fn with_size(mut self, size: u32) -> Self {
-        self.device_size = Some(size);
+    pub fn with_size(self, size: u32) -> Builder<Ready, WP> {
+        Builder { device_size: Some(size), ..self.into_state() }
+    }
+
+    /// Limit how many data bytes go into a single I2C transaction
+    ///
+    /// Useful for bus adapters with small transfer buffers. Values are clamped
+    /// to between 1 and [`MAX_TRANSFER`].
+    pub fn with_max_transfer(mut self, bytes: usize) -> Self {
+        self.max_transfer = bytes;
+        self
+    }
+
+    /// Keep transactions small enough for a USB-to-I2C `bridge`
+    ///
+    /// Same as [`with_max_transfer`](Self::with_max_transfer) with
+    /// [`UsbBridge::max_transfer`].
+    pub fn with_usb_bridge(self, bridge: UsbBridge) -> Self {
+        self.with_max_transfer(bridge.max_transfer())
+    }
+
+    /// Look for the FRAM on the other 0x50-0x57 addresses if it doesn't answer at the configured one
+    ///
+    /// If exactly one other address answers the device-ID command, the
+    /// interface connects there. Otherwise connecting fails with
+    /// [`ErrorKind::AddressNotFound`] listing every address that answered.
+    pub fn with_address_discovery(mut self, enabled: bool) -> Self {
+        self.discover_address = enabled;
+        self
+    }
+
+    /// Check the device ID reports a Fujitsu/RAMXEED part before connecting
+    ///
+    /// Connecting fails with [`ErrorKind::WrongDevice`] for another
+    /// manufacturer's code, or [`ErrorKind::NoDeviceId`] if nothing answers
+    /// the device-ID command (EEPROMs and RTCs don't), instead of going on to
+    /// overwrite whatever is at that address.
+    pub fn with_id_check(mut self, enabled: bool) -> Self {
+        self.check_id = enabled;
+        self
+    }
+
+    /// Retry a transaction up to `retries` times when it fails with a lost arbitration
+    ///
+    /// On a multi-master bus losing arbitration is normal and just means
+    /// trying again. The HAL's error type doesn't say what went wrong in a
+    /// standard way, so `is_loss` decides from the error whether it was an
+    /// arbitration loss; any other error fails straight away as before. On
+    /// Linux, i2c-dev reports a lost arbitration as `EAGAIN`.
+    pub fn with_arbitration_retry(mut self, retries: u8, is_loss: fn(&dyn Error) -> bool) -> Self {
+        self.arbitration_retry = Some(ArbitrationRetry { retries, is_loss });
+        self
+    }
+
+    /// Frame memory addresses with `addressing` instead of two address bytes
+    ///
+    /// Parts that answer at one slave address work with the default
+    /// [`TwoByte`](crate::TwoByte), or [`OneByte`](crate::OneByte) for the
+    /// smallest. Banked parts go through
+    /// [`with_banked_addressing`](Self::with_banked_addressing) instead.
+    pub fn with_addressing<A: SingleAddress>(mut self, addressing: &'static A) -> Self {
+        self.addressing = addressing;
         self
     }
 
+    /// Frame memory addresses with `addressing`, which takes the bank from the slave address
+    ///
+    /// The MB85RC04V needs [`OneByteBanked`](crate::OneByteBanked) and the
+    /// MB85RC1MT [`TwoByteBanked`](crate::TwoByteBanked). The size decides
+    /// how many slave addresses the part answers at, so it has to be given
+    /// with [`with_size`](Self::with_size) before the builder can connect;
+    /// any size already given is forgotten. Transactions are split wherever
+    /// the addressing says the bank changes.
+    pub fn with_banked_addressing<A: Banked>(mut self, addressing: &'static A) -> Builder<NeedsSize, WP> {
+        self.addressing = addressing;
+        Builder { device_size: None, ..self.into_state() }
+    }
+}
+
+impl<WP: WriteProtect> Builder<Ready, WP> {
     /// Finish the builder and construct the interface by attaching an I2C bus
-    pub fn connect_i2c<I2C>(self, i2c: I2C) -> MB85RC<I2C>
+    ///
+    /// # Panics
+    ///
+    /// Panics if the device can't be found or its size can't be detected; use
+    /// [`try_connect_i2c`](Self::try_connect_i2c) to handle that instead
+    pub fn connect_i2c<I2C>(self, i2c: I2C) -> MB85RC<I2C, WP>
     where 
         I2C: i2c::WriteRead + i2c::Write,
         <I2C as i2c::WriteRead>::Error: Error,
         <I2C as i2c::Write>::Error: Error,
     {
-        MB85RC::new(i2c, self.device_addr, self.device_size)
+        match self.try_connect_i2c(i2c) {
+            Ok(fram) => fram,
+            Err(e) => panic!("{}", e.error),
+        }
     }
-}
-
-/// Error type for anything that might happen on the I2C side of things
-#[derive(Debug)]
-pub struct Mb85rcError {
-    details: String,
-}
 
-impl Mb85rcError {
-    fn new(msg: &str) -> Mb85rcError {
-        Mb85rcError { details: msg.to_string() }
+    /// Finish the builder and construct the interface, handing the bus back if that fails
+    pub fn try_connect_i2c<I2C>(self, i2c: I2C) -> Result<MB85RC<I2C, WP>, ConnectError<I2C>>
+    where
+        I2C: i2c::WriteRead + i2c::Write,
+        <I2C as i2c::WriteRead>::Error: Error,
+        <I2C as i2c::Write>::Error: Error,
+    {
+        MB85RC::new(i2c, self)
     }
-}
 
-impl fmt::Display for Mb85rcError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.details)
+    /// Finish the builder and construct the interface on a borrowed I2C bus
+    ///
+    /// The bus stays owned by the caller and is usable again once the
+    /// interface is dropped
+    pub fn borrow_i2c<I2C>(self, i2c: &mut I2C) -> MB85RC<BorrowedI2c<'_, I2C>, WP>
+    where
+        I2C: i2c::WriteRead + i2c::Write,
+        <I2C as i2c::WriteRead>::Error: Error,
+        <I2C as i2c::Write>::Error: Error,
+    {
+        self.connect_i2c(BorrowedI2c::new(i2c))
     }
 }
 
-impl Error for Mb85rcError {
-    fn description(&self) -> &str {
-        &self.details
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
     }
 }
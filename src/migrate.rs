@@ -0,0 +1,100 @@
+use core::ops::Range;
+
+use crate::{FramDevice, FramSlice, Mb85rcError, ErrorKind};
+use crate::snapshot::copy_within;
+use crate::mirror::Mirrored;
+
+/// Marker stored next to the version while a step is running
+const MIGRATING: u16 = 0xA5A5;
+
+/// One upgrade step, moving the stored layout from version `from` to `from + 1`
+pub struct Migration<D: FramDevice + ?Sized> {
+    /// Layout version this step upgrades from
+    pub from: u16,
+    /// Rewrite the data region for the next version
+    pub run: fn(&mut FramSlice<'_, D>) -> Result<(), Mb85rcError>,
+}
+
+/// Brings a data region's layout up to date by running registered [`Migration`]s
+///
+/// The current layout version is kept in a CRC-checked record of
+/// [`STATE_SIZE`](Self::STATE_SIZE) bytes at `version_addr`, outside the data
+/// region, so bumping the version and clearing the in-progress mark land
+/// together or not at all. On [`run`](Self::run), every step whose `from`
+/// matches the stored version is applied in turn, and the version is bumped
+/// after each one.
+///
+/// With a scratch area configured, the region is copied there before each
+/// step and the step is marked as in progress. If power is lost part way
+/// through, the next run puts the copy back and repeats the step from the
+/// start, so a step never runs on half-migrated data.
+pub struct Migrator<'a, D: FramDevice + ?Sized> {
+    steps: &'a [Migration<D>],
+    version_addr: u32,
+    region: Range<u32>,
+    scratch: Option<u32>,
+}
+
+impl<'a, D: FramDevice + ?Sized> Migrator<'a, D> {
+    /// Bytes of FRAM the version record takes up at `version_addr`
+    pub const STATE_SIZE: u32 = Mirrored::<4>::SIZE;
+
+    /// Migrate the data in `region`, with the version stored at `version_addr`
+    pub fn new(steps: &'a [Migration<D>], version_addr: u32, region: Range<u32>) -> Self {
+        Self { steps, version_addr, region, scratch: None }
+    }
+
+    /// Protect each step against power loss using a copy of the region at `scratch`
+    pub fn with_scratch(mut self, scratch: u32) -> Self {
+        self.scratch = Some(scratch);
+        self
+    }
+
+    /// Read the stored layout version
+    pub fn version(&self, dev: &mut D) -> Result<u16, Mb85rcError> {
+        Ok(self.read_state(dev)?.0)
+    }
+
+    /// The stored version and in-progress flag, both 0 before the first run
+    fn read_state(&self, dev: &mut D) -> Result<(u16, u16), Mb85rcError> {
+        let raw = match Mirrored::<4>::new(self.version_addr).read(dev)? {
+            Some((_, raw)) => raw,
+            None => [0u8; 4],
+        };
+        Ok((u16::from_le_bytes([raw[0], raw[1]]), u16::from_le_bytes([raw[2], raw[3]])))
+    }
+
+    fn write_state(&self, dev: &mut D, version: u16, flag: u16) -> Result<(), Mb85rcError> {
+        let mut raw = [0u8; 4];
+        raw[..2].copy_from_slice(&version.to_le_bytes());
+        raw[2..].copy_from_slice(&flag.to_le_bytes());
+        Mirrored::<4>::new(self.version_addr).write(dev, &raw)
+    }
+
+    /// Apply every pending step, returning the version the region ends up at
+    pub fn run(&self, dev: &mut D) -> Result<u16, Mb85rcError> {
+        let len = self.region.end.checked_sub(self.region.start).ok_or(Mb85rcError::new(ErrorKind::OutOfRange))?;
+        let (mut version, flag) = self.read_state(dev)?;
+
+        if flag == MIGRATING {
+            if let Some(scratch) = self.scratch {
+                copy_within(dev, scratch, self.region.start, len)?;
+            }
+            self.write_state(dev, version, 0)?;
+        }
+
+        while let Some(step) = self.steps.iter().find(|s| s.from == version) {
+            if let Some(scratch) = self.scratch {
+                copy_within(dev, self.region.start, scratch, len)?;
+                self.write_state(dev, version, MIGRATING)?;
+            }
+
+            (step.run)(&mut FramSlice::new(dev, self.region.clone())?)?;
+
+            version += 1;
+            self.write_state(dev, version, 0)?;
+        }
+
+        Ok(version)
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{FramDevice, Mb85rcError};
+use crate::crc::Crc32;
+
+/// Bytes of record data moved per transaction by [`MirroredBytes`]
+const CHUNK: usize = 32;
+
+/// An `N`-byte record kept in two alternating, CRC-checked copies
+///
+/// Each copy is a sequence number, the record and a CRC-32 over both. Writes
+/// go to the older copy, so a reset part way through leaves the previous
+/// record readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Mirrored<const N: usize> {
+    addr: u32,
+}
+
+impl<const N: usize> Mirrored<N> {
+    /// Bytes of FRAM both copies take up
+    pub(crate) const SIZE: u32 = MirroredBytes::size(N as u32);
+
+    pub(crate) const fn new(addr: u32) -> Self {
+        Self { addr }
+    }
+
+    pub(crate) fn addr(&self) -> u32 {
+        self.addr
+    }
+
+    fn copies(&self) -> MirroredBytes {
+        MirroredBytes::new(self.addr, N as u32)
+    }
+
+    /// The newest valid copy and its sequence number
+    pub(crate) fn read<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<(u32, [u8; N])>, Mb85rcError> {
+        self.copies().read(dev, [0u8; N], |data, offset, piece| {
+            data[offset as usize..offset as usize + piece.len()].copy_from_slice(piece);
+        })
+    }
+
+    /// Store `data` as the newest record
+    pub(crate) fn write<D: FramDevice + ?Sized>(&self, dev: &mut D, data: &[u8; N]) -> Result<(), Mb85rcError> {
+        let seq = match self.read(dev)? {
+            Some((seq, _)) => seq.wrapping_add(1),
+            None => 0,
+        };
+        self.copies().write(dev, seq, |put| put(data))
+    }
+}
+
+/// The same two-copy layout as [`Mirrored`] for a `len`-byte record handled a piece at a time
+///
+/// For records whose size isn't a constant the type system can use, like
+/// [`PersistentStats`](crate::PersistentStats)'s, or that are built up field
+/// by field rather than held in one array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MirroredBytes {
+    addr: u32,
+    len: u32,
+}
+
+impl MirroredBytes {
+    pub(crate) const fn new(addr: u32, len: u32) -> Self {
+        Self { addr, len }
+    }
+
+    /// Bytes of FRAM both copies of a `len`-byte record take up
+    pub(crate) const fn size(len: u32) -> u32 {
+        2 * (len + 8)
+    }
+
+    fn copy_addr(&self, copy: u32) -> u32 {
+        self.addr + copy * (self.len + 8)
+    }
+
+    fn read_copy<D: FramDevice + ?Sized, T>(&self, dev: &mut D, copy: u32, mut value: T, decode: &mut impl FnMut(&mut T, u32, &[u8])) -> Result<Option<(u32, T)>, Mb85rcError> {
+        let base = self.copy_addr(copy);
+        let mut seq = [0u8; 4];
+        dev.read_at(base, &mut seq)?;
+        let mut crc = Crc32::new();
+        crc.update(&seq);
+
+        let mut chunk = [0u8; CHUNK];
+        let mut offset = 0;
+        while offset < self.len {
+            let piece = &mut chunk[..(self.len - offset).min(CHUNK as u32) as usize];
+            dev.read_at(base + 4 + offset, piece)?;
+            crc.update(piece);
+            decode(&mut value, offset, piece);
+            offset += piece.len() as u32;
+        }
+
+        let mut stored = [0u8; 4];
+        dev.read_at(base + 4 + self.len, &mut stored)?;
+        Ok((crc.finish() == u32::from_le_bytes(stored)).then_some((u32::from_le_bytes(seq), value)))
+    }
+
+    /// The newest valid copy and its sequence number
+    ///
+    /// Each copy starts out as `empty` and `decode` is handed its data a
+    /// piece at a time, along with the offset of the piece in the record.
+    pub(crate) fn read<D: FramDevice + ?Sized, T: Clone>(&self, dev: &mut D, empty: T, mut decode: impl FnMut(&mut T, u32, &[u8])) -> Result<Option<(u32, T)>, Mb85rcError> {
+        match (self.read_copy(dev, 0, empty.clone(), &mut decode)?, self.read_copy(dev, 1, empty, &mut decode)?) {
+            (Some(a), Some(b)) => Ok(Some(if (b.0.wrapping_sub(a.0) as i32) > 0 { b } else { a })),
+            (a, b) => Ok(a.or(b)),
+        }
+    }
+
+    /// Store a record numbered `seq`, which should be one past the newest
+    ///
+    /// `encode` is handed a function to put the record's data in place, in
+    /// order, in as many pieces as it likes.
+    pub(crate) fn write<D: FramDevice + ?Sized>(&self, dev: &mut D, seq: u32, encode: impl FnOnce(&mut dyn FnMut(&[u8]) -> Result<(), Mb85rcError>) -> Result<(), Mb85rcError>) -> Result<(), Mb85rcError> {
+        let base = self.copy_addr(seq % 2);
+        let mut crc = Crc32::new();
+        crc.update(&seq.to_le_bytes());
+        dev.write_at(base, &seq.to_le_bytes())?;
+
+        let mut pos = base + 4;
+        encode(&mut |piece| {
+            dev.write_at(pos, piece)?;
+            crc.update(piece);
+            pos += piece.len() as u32;
+            Ok(())
+        })?;
+        debug_assert_eq!(pos, base + 4 + self.len, "record written with the wrong length");
+
+        // the CRC goes last so the copy only becomes valid once it's complete
+        dev.write_at(pos, &crc.finish().to_le_bytes())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::Mirrored;
+    use crate::{Builder, Fault, SimFram, MB85RC};
+
+    fn fram() -> MB85RC<SimFram> {
+        Builder::new().with_size(1024).connect_i2c(SimFram::new(1024))
+    }
+
+    #[test]
+    fn newest_copy_wins() {
+        let mut fram = fram();
+        let record = Mirrored::<4>::new(100);
+        assert_eq!(record.read(&mut fram).unwrap(), None);
+        for seq in 0..5u8 {
+            record.write(&mut fram, &[seq; 4]).unwrap();
+            assert_eq!(record.read(&mut fram).unwrap(), Some((seq as u32, [seq; 4])));
+        }
+    }
+
+    #[test]
+    fn torn_write_keeps_previous_record() {
+        // the write reads both copies in six transactions, then writes seq, data and CRC
+        for at in 6..9 {
+            for keep in 0..4 {
+                let mut fram = fram();
+                let record = Mirrored::<4>::new(100);
+                record.write(&mut fram, &[1; 4]).unwrap();
+                record.write(&mut fram, &[2; 4]).unwrap();
+
+                fram.with_bus(|sim| sim.inject(sim.transactions() + at, Fault::TornWrite(keep)));
+                let torn = record.write(&mut fram, &[3; 4]).is_err();
+                fram.with_bus(|sim| sim.clear_faults());
+
+                let (_, data) = record.read(&mut fram).unwrap().unwrap();
+                match torn {
+                    true => assert_eq!(data, [2; 4], "tear at transaction {} keeping {} bytes", at, keep),
+                    false => assert_eq!(data, [3; 4]),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{FramDevice, Mb85rcError};
+
+/// Callbacks run by [`Observed`] after every access
+///
+/// Both methods do nothing by default, so implement just the ones you need.
+/// `outcome` is what the access returned; after a failed read `data` holds
+/// whatever the buffer was left with.
+pub trait Observer {
+    /// Called after each read of `data.len()` bytes at `addr`
+    fn on_read(&mut self, addr: u32, data: &[u8], outcome: Result<(), Mb85rcError>) {
+        let _ = (addr, data, outcome);
+    }
+
+    /// Called after each write of `data` at `addr`
+    fn on_write(&mut self, addr: u32, data: &[u8], outcome: Result<(), Mb85rcError>) {
+        let _ = (addr, data, outcome);
+    }
+}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    fn on_read(&mut self, addr: u32, data: &[u8], outcome: Result<(), Mb85rcError>) {
+        (**self).on_read(addr, data, outcome)
+    }
+
+    fn on_write(&mut self, addr: u32, data: &[u8], outcome: Result<(), Mb85rcError>) {
+        (**self).on_write(addr, data, outcome)
+    }
+}
+
+/// Device wrapper that reports every access to an [`Observer`]
+///
+/// Handy for auditing, keeping a shadow copy in RAM or a live view of what
+/// the firmware is touching. Put it around the driver, or around a
+/// [`FramSlice`](crate::FramSlice) to watch just one region.
+pub struct Observed<D: FramDevice, O: Observer> {
+    dev: D,
+    observer: O,
+}
+
+impl<D: FramDevice, O: Observer> Observed<D, O> {
+    /// Report accesses to `dev` to `observer`
+    pub fn new(dev: D, observer: O) -> Self {
+        Self { dev, observer }
+    }
+
+    /// The observer
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// The observer, mutably
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Give back the device and the observer
+    pub fn into_inner(self) -> (D, O) {
+        (self.dev, self.observer)
+    }
+}
+
+impl<D: FramDevice, O: Observer> FramDevice for Observed<D, O> {
+    fn capacity(&self) -> u32 {
+        self.dev.capacity()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        let outcome = self.dev.read_at(addr, buf);
+        self.observer.on_read(addr, buf, outcome);
+        outcome
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        let outcome = self.dev.write_at(addr, buf);
+        self.observer.on_write(addr, buf, outcome);
+        outcome
+    }
+}
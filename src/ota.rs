@@ -0,0 +1,142 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::mirror::Mirrored;
+
+/// Bytes in an encoded [`OtaStatus`]
+const RECORD: usize = 40;
+
+/// Where a firmware update is up to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OtaState {
+    /// No update in progress
+    Idle,
+    /// An image is being downloaded
+    Downloading,
+    /// A complete image is waiting to be installed
+    Ready,
+    /// The bootloader installed the image
+    Applied,
+}
+
+impl OtaState {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Idle),
+            1 => Some(Self::Downloading),
+            2 => Some(Self::Ready),
+            3 => Some(Self::Applied),
+            _ => None,
+        }
+    }
+}
+
+/// Everything [`OtaStaging`] records about an update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtaStatus {
+    /// Where the update is up to
+    pub state: OtaState,
+    /// Times the bootloader has tried to install the image
+    pub attempts: u16,
+    /// Length of the image in bytes
+    pub image_len: u32,
+    /// Hash of the image, in whatever scheme the application uses
+    pub hash: [u8; 32],
+}
+
+impl OtaStatus {
+    const IDLE: Self = Self { state: OtaState::Idle, attempts: 0, image_len: 0, hash: [0; 32] };
+
+    fn to_bytes(self) -> [u8; RECORD] {
+        let mut raw = [0u8; RECORD];
+        raw[0] = self.state as u8;
+        raw[2..4].copy_from_slice(&self.attempts.to_le_bytes());
+        raw[4..8].copy_from_slice(&self.image_len.to_le_bytes());
+        raw[8..40].copy_from_slice(&self.hash);
+        raw
+    }
+
+    fn from_bytes(raw: &[u8; RECORD]) -> Result<Self, Mb85rcError> {
+        Ok(Self {
+            state: OtaState::from_u8(raw[0]).ok_or(Mb85rcError::new(ErrorKind::InvalidData))?,
+            attempts: u16::from_le_bytes([raw[2], raw[3]]),
+            image_len: u32::from_le_bytes(raw[4..8].try_into().unwrap()),
+            hash: raw[8..40].try_into().unwrap(),
+        })
+    }
+}
+
+/// Firmware update bookkeeping shared by a bootloader and an application
+///
+/// The status is one record kept in two alternating, CRC-checked copies, so
+/// every transition is atomic: a reset part way through leaves the previous
+/// state. The expected flow is [`begin_download`](Self::begin_download),
+/// [`mark_ready`](Self::mark_ready), then any number of
+/// [`record_attempt`](Self::record_attempt) calls from the bootloader before
+/// [`mark_applied`](Self::mark_applied). Transitions that don't fit the flow
+/// fail with [`ErrorKind::WrongState`]. The handle only records where the
+/// status lives; pass the device to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtaStaging {
+    record: Mirrored<RECORD>,
+}
+
+impl OtaStaging {
+    /// Bytes of FRAM the status takes up
+    pub const SIZE: u32 = Mirrored::<RECORD>::SIZE;
+
+    /// Status stored at `addr`
+    pub const fn new(addr: u32) -> Self {
+        Self { record: Mirrored::new(addr) }
+    }
+
+    /// The current status, idle if nothing valid has been stored yet
+    pub fn status<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<OtaStatus, Mb85rcError> {
+        match self.record.read(dev)? {
+            Some((_, raw)) => OtaStatus::from_bytes(&raw),
+            None => Ok(OtaStatus::IDLE),
+        }
+    }
+
+    fn transition<D: FramDevice + ?Sized>(
+        &self,
+        dev: &mut D,
+        from: &[OtaState],
+        f: impl FnOnce(&mut OtaStatus),
+    ) -> Result<OtaStatus, Mb85rcError> {
+        let mut status = self.status(dev)?;
+        if !from.contains(&status.state) {
+            return Err(Mb85rcError::new(ErrorKind::WrongState));
+        }
+        f(&mut status);
+        self.record.write(dev, &status.to_bytes())?;
+        Ok(status)
+    }
+
+    /// Start downloading an image of `image_len` bytes, abandoning any unfinished download
+    pub fn begin_download<D: FramDevice + ?Sized>(&self, dev: &mut D, image_len: u32, hash: [u8; 32]) -> Result<OtaStatus, Mb85rcError> {
+        use OtaState::*;
+        self.transition(dev, &[Idle, Downloading, Applied], |s| {
+            *s = OtaStatus { state: Downloading, attempts: 0, image_len, hash };
+        })
+    }
+
+    /// The download finished and the image checked out
+    pub fn mark_ready<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<OtaStatus, Mb85rcError> {
+        self.transition(dev, &[OtaState::Downloading], |s| s.state = OtaState::Ready)
+    }
+
+    /// Count an install attempt, returning the new count
+    pub fn record_attempt<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<u16, Mb85rcError> {
+        self.transition(dev, &[OtaState::Ready], |s| s.attempts = s.attempts.saturating_add(1))
+            .map(|s| s.attempts)
+    }
+
+    /// The bootloader installed the image
+    pub fn mark_applied<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<OtaStatus, Mb85rcError> {
+        self.transition(dev, &[OtaState::Ready], |s| s.state = OtaState::Applied)
+    }
+
+    /// Forget about any update, from any state
+    pub fn reset<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        self.record.write(dev, &OtaStatus::IDLE.to_bytes())
+    }
+}
@@ -0,0 +1,207 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::crc32;
+
+/// Identifies a password block, "PWD1"
+const MAGIC: u32 = u32::from_le_bytes(*b"PWD1");
+
+/// How a passphrase is stretched into a key
+///
+/// [`unlock`](PasswordBlock::unlock) refuses settings above the `MAX_*`
+/// limits, so a tampered block can't make it run for hours or exhaust memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    /// PBKDF2-HMAC-SHA256 with the given number of rounds
+    ///
+    /// More rounds make guessing slower, and unlocking too. Tens of
+    /// thousands is reasonable on a Linux host; a small MCU may need far
+    /// fewer. At most [`MAX_PBKDF2_ROUNDS`](Self::MAX_PBKDF2_ROUNDS).
+    Pbkdf2 {
+        /// Iteration count
+        rounds: u32,
+    },
+    /// Argon2id with one lane, the best choice for passphrases on a host
+    ///
+    /// Every guess needs the memory as well as the time, which takes away
+    /// most of the advantage of guessing on a GPU. Tens of MiB and a few
+    /// iterations is typical. Needs `std` for the memory.
+    #[cfg(feature = "std")]
+    Argon2 {
+        /// Memory in KiB, at least 8 and at most [`MAX_ARGON2_MEMORY_KIB`](Self::MAX_ARGON2_MEMORY_KIB)
+        memory_kib: u32,
+        /// Passes over the memory, at most [`MAX_ARGON2_ITERATIONS`](Self::MAX_ARGON2_ITERATIONS)
+        iterations: u32,
+    },
+    /// A single HKDF-SHA256 extract and expand, for high-entropy secrets only
+    ///
+    /// Cheap enough for any MCU, but does nothing to slow down guessing, so
+    /// a human-chosen passphrase falls to a dictionary attack. Only use it
+    /// when the "passphrase" is a long random secret, such as a 32-byte key
+    /// held by a maintenance tool.
+    Hkdf,
+}
+
+impl Kdf {
+    /// Most PBKDF2 rounds a block may ask for
+    pub const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+    /// Most Argon2 memory a block may ask for, 256 MiB
+    pub const MAX_ARGON2_MEMORY_KIB: u32 = 256 * 1024;
+    /// Most Argon2 iterations a block may ask for
+    pub const MAX_ARGON2_ITERATIONS: u32 = 64;
+
+    fn within_limits(self) -> bool {
+        match self {
+            Kdf::Pbkdf2 { rounds } => rounds <= Self::MAX_PBKDF2_ROUNDS,
+            #[cfg(feature = "std")]
+            Kdf::Argon2 { memory_kib, iterations } => memory_kib <= Self::MAX_ARGON2_MEMORY_KIB && iterations <= Self::MAX_ARGON2_ITERATIONS,
+            Kdf::Hkdf => true,
+        }
+    }
+
+    fn derive(self, passphrase: &[u8], salt: &[u8; 16]) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        match self {
+            Kdf::Pbkdf2 { rounds } => pbkdf2::pbkdf2_hmac::<Sha256>(passphrase, salt, rounds.max(1), &mut key),
+            #[cfg(feature = "std")]
+            Kdf::Argon2 { memory_kib, iterations } => {
+                use argon2::{Algorithm, Argon2, Block, Params, Version};
+                // clamped to what Argon2 accepts, so neither can fail
+                let params = Params::new(memory_kib.max(Params::MIN_M_COST), iterations.max(1), 1, Some(key.len())).unwrap();
+                let mut memory = std::vec![Block::default(); params.block_count()];
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into_with_memory(passphrase, salt, &mut key, &mut memory[..])
+                    .unwrap();
+            },
+            Kdf::Hkdf => hkdf::Hkdf::<Sha256>::new(Some(salt), passphrase)
+                .expand(b"mb85rc password", &mut key)
+                .unwrap(),
+        }
+        key
+    }
+
+    /// The KDF's id, rounds and memory as stored in a block
+    fn encode(self) -> (u8, u32, u32) {
+        match self {
+            Kdf::Pbkdf2 { rounds } => (1, rounds, 0),
+            Kdf::Hkdf => (2, 0, 0),
+            #[cfg(feature = "std")]
+            Kdf::Argon2 { memory_kib, iterations } => (3, iterations, memory_kib),
+        }
+    }
+
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn decode(id: u8, rounds: u32, memory: u32) -> Option<Self> {
+        match id {
+            1 => Some(Kdf::Pbkdf2 { rounds }),
+            2 => Some(Kdf::Hkdf),
+            #[cfg(feature = "std")]
+            3 => Some(Kdf::Argon2 { memory_kib: memory, iterations: rounds }),
+            _ => None,
+        }
+    }
+}
+
+/// Keys unlocked by a [`PasswordBlock`]
+///
+/// The master key is wiped when this is dropped.
+pub struct DerivedKeys {
+    master: [u8; 32],
+}
+
+impl DerivedKeys {
+    fn mac(&self, context: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.master).unwrap();
+        mac.update(context);
+        mac
+    }
+
+    /// A key for the region called `label`, different for every label
+    ///
+    /// Pass it to [`EncryptedStream`](crate::EncryptedStream),
+    /// [`AuthRegion`](crate::AuthRegion) or [`SecretStore`](crate::SecretStore).
+    pub fn region_key(&self, label: &[u8]) -> [u8; 32] {
+        let mut mac = self.mac(b"mb85rc region ");
+        mac.update(label);
+        mac.finalize().into_bytes().into()
+    }
+}
+
+impl Drop for DerivedKeys {
+    fn drop(&mut self) {
+        self.master.fill(0);
+    }
+}
+
+/// Salt and verifier for deriving region keys from a passphrase
+///
+/// The block stores which [`Kdf`] to use, its salt and a value that proves a
+/// passphrase is right without revealing the key, all covered by a CRC.
+/// [`unlock`](Self::unlock) stretches a passphrase with the stored settings
+/// and hands back [`DerivedKeys`] if it matches. Handy for configuration that
+/// only a maintenance tool with the passphrase should see. The handle only
+/// records where the block lives; pass the device to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordBlock {
+    addr: u32,
+}
+
+impl PasswordBlock {
+    /// Bytes of FRAM the block takes up
+    pub const SIZE: u32 = 64;
+
+    /// Block stored at `addr`
+    pub const fn new(addr: u32) -> Self {
+        Self { addr }
+    }
+
+    /// Set the passphrase, replacing any earlier one
+    ///
+    /// `salt` should be random and different for every device. Keys derived
+    /// from the old passphrase stop working, so re-encrypt anything that
+    /// used them. Fails with [`ErrorKind::TooLarge`] if `kdf` is over the
+    /// limits [`unlock`](Self::unlock) accepts.
+    pub fn format<D: FramDevice + ?Sized>(&self, dev: &mut D, passphrase: &[u8], salt: [u8; 16], kdf: Kdf) -> Result<DerivedKeys, Mb85rcError> {
+        if !kdf.within_limits() {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+        let keys = DerivedKeys { master: kdf.derive(passphrase, &salt) };
+        let (id, rounds, memory) = kdf.encode();
+
+        let mut raw = [0u8; Self::SIZE as usize];
+        raw[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        raw[4] = id;
+        raw[5..8].copy_from_slice(&memory.to_le_bytes()[..3]);
+        raw[8..12].copy_from_slice(&rounds.to_le_bytes());
+        raw[12..28].copy_from_slice(&salt);
+        raw[28..60].copy_from_slice(&keys.mac(b"mb85rc verify").finalize().into_bytes());
+        let crc = crc32(&raw[..60]);
+        raw[60..64].copy_from_slice(&crc.to_le_bytes());
+        dev.write_at(self.addr, &raw)?;
+        Ok(keys)
+    }
+
+    /// Derive the keys from `passphrase`
+    ///
+    /// Fails with [`ErrorKind::AuthFailed`] if the passphrase is wrong and
+    /// [`ErrorKind::InvalidData`] if there's no valid block, or it asks for
+    /// more work than the [`Kdf`] limits allow.
+    pub fn unlock<D: FramDevice + ?Sized>(&self, dev: &mut D, passphrase: &[u8]) -> Result<DerivedKeys, Mb85rcError> {
+        let mut raw = [0u8; Self::SIZE as usize];
+        dev.read_at(self.addr, &mut raw)?;
+        let magic = u32::from_le_bytes(raw[0..4].try_into().unwrap());
+        let crc = u32::from_le_bytes(raw[60..64].try_into().unwrap());
+        let memory = u32::from_le_bytes([raw[5], raw[6], raw[7], 0]);
+        let rounds = u32::from_le_bytes(raw[8..12].try_into().unwrap());
+        let kdf = Kdf::decode(raw[4], rounds, memory)
+            .filter(|kdf| magic == MAGIC && crc == crc32(&raw[..60]) && kdf.within_limits())
+            .ok_or(Mb85rcError::new(ErrorKind::InvalidData))?;
+
+        let keys = DerivedKeys { master: kdf.derive(passphrase, raw[12..28].try_into().unwrap()) };
+        match keys.mac(b"mb85rc verify").verify_slice(&raw[28..60]) {
+            Ok(()) => Ok(keys),
+            Err(_) => Err(Mb85rcError::new(ErrorKind::AuthFailed)),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{Mb85rcError, ErrorKind};
+
+/// Something that drives the FRAM's WP pin
+///
+/// While WP is high the chip ignores writes. Any
+/// `FnMut(bool) -> Result<(), Mb85rcError>` closure works, so a GPIO from
+/// whatever HAL the board uses can be hooked up with something like
+/// `move |protected| wp.set_state(protected.into()).map_err(|_| ErrorKind::Pin.into())`.
+/// Pass it to [`Builder::with_write_protect`](crate::Builder::with_write_protect).
+pub trait WriteProtect {
+    /// Drive WP high if `protected`, low otherwise
+    fn set_protected(&mut self, protected: bool) -> Result<(), Mb85rcError>;
+}
+
+impl<F: FnMut(bool) -> Result<(), Mb85rcError>> WriteProtect for F {
+    fn set_protected(&mut self, protected: bool) -> Result<(), Mb85rcError> {
+        self(protected)
+    }
+}
+
+/// No WP pin: WP is tied low, so the device can always be written
+///
+/// Protecting fails with [`ErrorKind::WrongState`], since there is nothing to do it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoWriteProtect;
+
+impl WriteProtect for NoWriteProtect {
+    fn set_protected(&mut self, protected: bool) -> Result<(), Mb85rcError> {
+        match protected {
+            true => Err(Mb85rcError::new(ErrorKind::WrongState)),
+            false => Ok(()),
+        }
+    }
+}
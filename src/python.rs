@@ -0,0 +1,112 @@
+//! Python bindings over the Linux i2c-dev backend
+//!
+//! Build the extension module with
+//! `cargo rustc --release --features python --crate-type cdylib`, copy
+//! `libmb85rc.so` to `mb85rc.so` somewhere on the Python path and `import mb85rc`.
+
+use std::fmt::Write;
+
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::types::PyBytes;
+
+use linux_embedded_hal::I2cdev;
+
+use crate::{MB85RC, Builder, ErrorKind, Mb85rcError};
+
+fn to_pyerr(e: Mb85rcError) -> PyErr {
+    match e.kind() {
+        ErrorKind::I2c => PyIOError::new_err(e.to_string()),
+        _ => PyValueError::new_err(e.to_string()),
+    }
+}
+
+/// An MB85RC FRAM on a Linux I2C bus
+#[pyclass(name = "Fram")]
+pub struct PyFram {
+    fram: MB85RC<I2cdev>,
+}
+
+impl PyFram {
+    fn read_vec(&mut self, addr: u32, len: usize) -> PyResult<Vec<u8>> {
+        // check the range before allocating, so a huge length can't exhaust memory
+        let addr = self.fram.address(addr).map_err(to_pyerr)?;
+        if !addr.fits(len) {
+            return Err(to_pyerr(Mb85rcError::new(ErrorKind::OutOfRange)));
+        }
+        let mut buf = vec![0u8; len];
+        self.fram.fram_read(addr, &mut buf).map_err(to_pyerr)?;
+        Ok(buf)
+    }
+}
+
+#[pymethods]
+impl PyFram {
+    /// Open the FRAM at `address` on the bus at `path`, auto-detecting the size if not given
+    #[new]
+    #[pyo3(signature = (path = "/dev/i2c-1", address = 0x50, size = None))]
+    fn new(path: &str, address: u8, size: Option<u32>) -> PyResult<Self> {
+        let i2c = I2cdev::new(path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let mut builder = Builder::new().with_address(address);
+        if let Some(size) = size {
+            builder = builder.with_size(size);
+        }
+        let fram = builder.try_connect_i2c(i2c).map_err(|e| to_pyerr(e.error))?;
+        Ok(Self { fram })
+    }
+
+    /// Size of the device in bytes
+    #[getter]
+    fn size(&self) -> u32 {
+        self.fram.fram_size()
+    }
+
+    /// Read `length` bytes at `addr`
+    fn read<'py>(&mut self, py: Python<'py>, addr: u32, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let buf = self.read_vec(addr, length)?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Write `data` at `addr`
+    fn write(&mut self, addr: u32, data: &[u8]) -> PyResult<()> {
+        let addr = self.fram.address(addr).map_err(to_pyerr)?;
+        self.fram.fram_write(addr, data).map_err(to_pyerr)?;
+        Ok(())
+    }
+
+    /// Read the whole device
+    fn dump<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let size = self.fram.fram_size();
+        self.read(py, 0, size as usize)
+    }
+
+    /// Write an image (usually from `dump`) back to the start of the device
+    fn restore(&mut self, image: &[u8]) -> PyResult<()> {
+        self.write(0, image)
+    }
+
+    /// Format `length` bytes at `addr` (default: the rest of the device) as a hexdump
+    #[pyo3(signature = (addr = 0, length = None))]
+    fn hexdump(&mut self, addr: u32, length: Option<usize>) -> PyResult<String> {
+        let length = length.unwrap_or(self.fram.fram_size().saturating_sub(addr) as usize);
+        let buf = self.read_vec(addr, length)?;
+
+        let mut out = String::new();
+        for (i, line) in buf.chunks(16).enumerate() {
+            let _ = write!(out, "{:08x} ", addr as usize + i * 16);
+            for b in line {
+                let _ = write!(out, " {:02x}", b);
+            }
+            out.push_str(&"   ".repeat(16 - line.len()));
+            out.push_str("  |");
+            out.extend(line.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }));
+            out.push_str("|\n");
+        }
+        Ok(out)
+    }
+}
+
+#[pymodule]
+fn mb85rc(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyFram>()
+}
@@ -0,0 +1,169 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// One queued write: where it goes and where its data sits in the buffer
+#[derive(Debug, Clone, Copy, Default)]
+struct Entry {
+    addr: u32,
+    start: usize,
+    len: usize,
+}
+
+/// Bounded in-RAM queue of writes waiting to go out to the device
+///
+/// [`enqueue`](Self::enqueue) only copies into RAM, so time-critical code can
+/// ask for something to be persisted without touching the bus. A
+/// lower-priority task then calls [`flush_some`](Self::flush_some) with a
+/// byte budget to drain a little at a time. The queue holds up to `E` writes
+/// and `B` bytes of data; share it between tasks the same way as any other
+/// resource (an RTIC resource or a `critical_section::Mutex`, say).
+///
+/// Writes reach the device in the order they were queued. A write that
+/// overlaps or directly follows the most recently queued one is merged into
+/// it, so repeated updates of the same field take no extra space.
+/// [`read_at`](Self::read_at) shows queued data on top of what the device
+/// holds.
+pub struct WriteQueue<const E: usize, const B: usize> {
+    entries: [Entry; E],
+    count: usize,
+    data: [u8; B],
+}
+
+impl<const E: usize, const B: usize> WriteQueue<E, B> {
+    /// An empty queue
+    pub const fn new() -> Self {
+        Self { entries: [Entry { addr: 0, start: 0, len: 0 }; E], count: 0, data: [0u8; B] }
+    }
+
+    /// Number of queued writes
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether nothing is waiting to be written
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Bytes of data waiting to be written
+    pub fn pending_bytes(&self) -> usize {
+        self.entries[..self.count].iter().map(|e| e.len).sum()
+    }
+
+    /// Queue `data` to be written at `addr`
+    ///
+    /// Fails with [`ErrorKind::Full`] if there isn't room for it right now,
+    /// or [`ErrorKind::TooLarge`] if it's bigger than the whole buffer.
+    pub fn enqueue(&mut self, addr: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        if data.len() > B {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+        addr.checked_add(data.len() as u32).ok_or(Mb85rcError::new(ErrorKind::OutOfRange))?;
+
+        // merge into the newest write if this one starts inside it or right after it
+        if let Some(last) = self.count.checked_sub(1).map(|i| self.entries[i]) {
+            let last_end = last.addr + last.len as u32;
+            if last.addr <= addr && addr <= last_end {
+                let offset = (addr - last.addr) as usize;
+                let overlap = (last.len - offset).min(data.len());
+                let extra = data.len() - overlap;
+                if !self.reserve(extra) {
+                    return Err(Mb85rcError::new(ErrorKind::Full));
+                }
+
+                let last = &mut self.entries[self.count - 1];
+                let at = last.start + offset;
+                self.data[at..at + data.len()].copy_from_slice(data);
+                last.len += extra;
+                return Ok(());
+            }
+        }
+
+        if self.count == E || !self.reserve(data.len()) {
+            return Err(Mb85rcError::new(ErrorKind::Full));
+        }
+        let start = self.data_end();
+        self.data[start..start + data.len()].copy_from_slice(data);
+        self.entries[self.count] = Entry { addr, start, len: data.len() };
+        self.count += 1;
+        Ok(())
+    }
+
+    fn data_end(&self) -> usize {
+        match self.count {
+            0 => 0,
+            n => self.entries[n - 1].start + self.entries[n - 1].len,
+        }
+    }
+
+    /// Make room for `n` more bytes after the newest write, returning whether there is
+    fn reserve(&mut self, n: usize) -> bool {
+        if self.data_end() + n <= B {
+            return true;
+        }
+        if self.pending_bytes() + n > B {
+            return false;
+        }
+
+        // slide the queued data back to the start of the buffer
+        let (first, end) = (self.entries[0].start, self.data_end());
+        self.data.copy_within(first..end, 0);
+        for entry in &mut self.entries[..self.count] {
+            entry.start -= first;
+        }
+        true
+    }
+
+    /// Write out up to `budget` bytes, oldest first, returning how many were written
+    ///
+    /// A write bigger than the budget is split, and the rest goes out on a
+    /// later call. If the device fails, the write it failed on stays queued.
+    pub fn flush_some<D: FramDevice + ?Sized>(&mut self, dev: &mut D, budget: usize) -> Result<usize, Mb85rcError> {
+        let mut written = 0;
+        while self.count > 0 && written < budget {
+            let entry = self.entries[0];
+            let n = entry.len.min(budget - written);
+            dev.write_at(entry.addr, &self.data[entry.start..entry.start + n])?;
+            written += n;
+
+            if n == entry.len {
+                self.entries.copy_within(1..self.count, 0);
+                self.count -= 1;
+            } else {
+                let front = &mut self.entries[0];
+                front.addr += n as u32;
+                front.start += n;
+                front.len -= n;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Write out everything that's queued
+    pub fn flush<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        self.flush_some(dev, usize::MAX).map(|_| ())
+    }
+
+    /// Read from `dev` with any queued data for the range laid over the top
+    pub fn read_at<D: FramDevice + ?Sized>(&self, dev: &mut D, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        dev.read_at(addr, buf)?;
+
+        // oldest first, so newer writes end up on top
+        let end = addr as u64 + buf.len() as u64;
+        for entry in &self.entries[..self.count] {
+            let lo = addr.max(entry.addr);
+            let hi = end.min(entry.addr as u64 + entry.len as u64);
+            if (lo as u64) < hi {
+                let n = (hi - lo as u64) as usize;
+                let (dst, src) = ((lo - addr) as usize, entry.start + (lo - entry.addr) as usize);
+                buf[dst..dst + n].copy_from_slice(&self.data[src..src + n]);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const E: usize, const B: usize> Default for WriteQueue<E, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,173 @@
+use embedded_hal::blocking::i2c;
+use core::error::Error;
+
+/// A way of unsticking a wedged I2C bus
+///
+/// Typical implementations clock SCL by hand through a GPIO until the slave
+/// lets go of SDA, or tear down and re-initialise the I2C peripheral. Any
+/// `FnMut(&mut I2C) -> bool` closure works too.
+pub trait BusRecovery<I2C> {
+    /// Try to get the bus working again, returning whether it's worth retrying
+    fn recover(&mut self, i2c: &mut I2C) -> bool;
+}
+
+impl<I2C, F: FnMut(&mut I2C) -> bool> BusRecovery<I2C> for F {
+    fn recover(&mut self, i2c: &mut I2C) -> bool {
+        self(i2c)
+    }
+}
+
+/// Which kind of I2C transaction failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// A plain write
+    Write,
+    /// A write followed by a read
+    WriteRead,
+}
+
+/// Everything a [`RecoveryPolicy`] is told about a failed transaction
+#[derive(Debug, Clone, Copy)]
+pub struct Failure<'a> {
+    /// What was being done
+    pub operation: Operation,
+    /// The slave address it was sent to
+    pub address: u8,
+    /// Which attempt failed, counting from 1
+    pub attempt: u32,
+    /// What the HAL reported
+    pub error: &'a dyn Error,
+}
+
+/// What a [`RecoveryPolicy`] wants done about a failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Try the transaction again
+    Retry,
+    /// Give up and pass the error on
+    Fail,
+}
+
+/// Decides what happens every time a transaction on a [`RecoveringI2c`] fails
+///
+/// The policy gets the bus along with the failure, so it can back off
+/// (sleep for a while that grows with `attempt`), recover the bus, log, or
+/// count errors before deciding. [`Escalate`] is a sensible default.
+pub trait RecoveryPolicy<I2C> {
+    /// Handle a failed transaction
+    fn on_failure(&mut self, i2c: &mut I2C, failure: Failure<'_>) -> Action;
+}
+
+/// Retry a few times, then try a [`BusRecovery`] once, then give up
+pub struct Escalate<R> {
+    recovery: R,
+    attempts: u32,
+    recoveries: u32,
+}
+
+impl<R> Escalate<R> {
+    /// Retry once, then run `recovery` and retry once more if it succeeds
+    pub fn new(recovery: R) -> Self {
+        Self { recovery, attempts: 2, recoveries: 0 }
+    }
+
+    /// Make `attempts` attempts before running the recovery
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// How many times the recovery has been run
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+}
+
+impl<I2C, R: BusRecovery<I2C>> RecoveryPolicy<I2C> for Escalate<R> {
+    fn on_failure(&mut self, i2c: &mut I2C, failure: Failure<'_>) -> Action {
+        if failure.attempt < self.attempts {
+            return Action::Retry;
+        }
+        if failure.attempt > self.attempts {
+            return Action::Fail;
+        }
+
+        self.recoveries = self.recoveries.wrapping_add(1);
+        match self.recovery.recover(i2c) {
+            true => Action::Retry,
+            false => Action::Fail,
+        }
+    }
+}
+
+/// An I2C bus that asks a [`RecoveryPolicy`] what to do whenever a transaction fails
+///
+/// Connect the driver to it like any other bus, e.g.
+/// `Builder::new().connect_i2c(RecoveringI2c::new(i2c, Escalate::new(recovery)))`.
+pub struct RecoveringI2c<I2C, P: RecoveryPolicy<I2C>> {
+    i2c: I2C,
+    policy: P,
+}
+
+impl<I2C, P: RecoveryPolicy<I2C>> RecoveringI2c<I2C, P> {
+    /// Wrap `i2c`, handling failures with `policy`
+    pub fn new(i2c: I2C, policy: P) -> Self {
+        Self { i2c, policy }
+    }
+
+    /// The policy
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// The policy, mutably
+    pub fn policy_mut(&mut self) -> &mut P {
+        &mut self.policy
+    }
+
+    /// Give back the bus and the policy
+    pub fn into_inner(self) -> (I2C, P) {
+        (self.i2c, self.policy)
+    }
+
+    fn run<E: Error>(&mut self, operation: Operation, address: u8, mut op: impl FnMut(&mut I2C) -> Result<(), E>) -> Result<(), E> {
+        let mut attempt = 1;
+        loop {
+            let error = match op(&mut self.i2c) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            let failure = Failure { operation, address, attempt, error: &error };
+            match self.policy.on_failure(&mut self.i2c, failure) {
+                Action::Retry => attempt += 1,
+                Action::Fail => return Err(error),
+            }
+        }
+    }
+}
+
+impl<I2C, P> i2c::Write for RecoveringI2c<I2C, P>
+where
+    I2C: i2c::Write,
+    I2C::Error: Error,
+    P: RecoveryPolicy<I2C>,
+{
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.run(Operation::Write, address, |i2c| i2c.write(address, bytes))
+    }
+}
+
+impl<I2C, P> i2c::WriteRead for RecoveringI2c<I2C, P>
+where
+    I2C: i2c::WriteRead,
+    <I2C as i2c::WriteRead>::Error: Error,
+    P: RecoveryPolicy<I2C>,
+{
+    type Error = <I2C as i2c::WriteRead>::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.run(Operation::WriteRead, address, |i2c| i2c.write_read(address, bytes, buffer))
+    }
+}
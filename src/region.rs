@@ -0,0 +1,253 @@
+use core::ops::Range;
+use crate::{FramDevice, FramSlice, Mb85rcError, ErrorKind};
+use crate::crc::{self, Crc32};
+use crate::snapshot::copy_within;
+
+/// Identifies a region table, "RGN1"
+const MAGIC: u32 = u32::from_le_bytes(*b"RGN1");
+/// Bytes of magic, count, padding and CRC at the start of a table copy
+const HEADER: u32 = 12;
+/// Bytes per table entry
+const ENTRY: u32 = 28;
+/// Longest region name
+pub const REGION_NAME_LEN: usize = 16;
+
+/// One named region from a [`RegionTable`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    name: [u8; REGION_NAME_LEN],
+    /// Start address of the region
+    pub offset: u32,
+    /// Length of the region in bytes
+    pub len: u32,
+    /// Application-defined flags
+    pub flags: u32,
+}
+
+impl Region {
+    /// The region's name
+    pub fn name(&self) -> &str {
+        let end = self.name.iter().position(|&b| b == 0).unwrap_or(REGION_NAME_LEN);
+        core::str::from_utf8(&self.name[..end]).unwrap_or("")
+    }
+
+    /// The addresses the region covers
+    pub fn range(&self) -> Range<u32> {
+        self.offset..self.offset + self.len
+    }
+
+    fn to_bytes(self) -> [u8; ENTRY as usize] {
+        let mut raw = [0u8; ENTRY as usize];
+        raw[..16].copy_from_slice(&self.name);
+        raw[16..20].copy_from_slice(&self.offset.to_le_bytes());
+        raw[20..24].copy_from_slice(&self.len.to_le_bytes());
+        raw[24..28].copy_from_slice(&self.flags.to_le_bytes());
+        raw
+    }
+
+    fn from_bytes(raw: &[u8; ENTRY as usize]) -> Self {
+        Self {
+            name: raw[..16].try_into().unwrap(),
+            offset: u32::from_le_bytes(raw[16..20].try_into().unwrap()),
+            len: u32::from_le_bytes(raw[20..24].try_into().unwrap()),
+            flags: u32::from_le_bytes(raw[24..28].try_into().unwrap()),
+        }
+    }
+}
+
+/// A table of named regions kept on the device itself
+///
+/// Firmware and tooling can look regions up by name instead of hard-coding
+/// addresses. The table is stored twice, back to back at `addr`; edits copy
+/// the primary over the backup before and after touching it, and a primary
+/// that fails its CRC is restored from the backup, so a reset mid-edit loses
+/// at most that edit. The handle only records where the table lives; pass the device
+/// to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionTable {
+    addr: u32,
+    max_entries: u16,
+}
+
+impl RegionTable {
+    /// A table of up to `max_entries` regions stored at `addr`
+    pub const fn new(addr: u32, max_entries: u16) -> Self {
+        Self { addr, max_entries }
+    }
+
+    fn copy_size(&self) -> u32 {
+        HEADER + self.max_entries as u32 * ENTRY
+    }
+
+    /// Bytes of FRAM the table occupies, both copies included
+    pub fn size(&self) -> u32 {
+        self.copy_size() * 2
+    }
+
+    /// The addresses the table itself covers
+    pub fn range(&self) -> Range<u32> {
+        self.addr..self.addr + self.size()
+    }
+
+    fn entry_addr(&self, base: u32, index: u16) -> u32 {
+        base + HEADER + index as u32 * ENTRY
+    }
+
+    /// Write an empty table, forgetting any regions
+    pub fn format<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        if self.addr as u64 + self.size() as u64 > dev.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        self.write_header(dev, self.addr + self.copy_size(), 0)?;
+        self.write_header(dev, self.addr, 0)
+    }
+
+    /// Entry count of the copy at `base`, if it passes its CRC
+    fn check_copy<D: FramDevice + ?Sized>(&self, dev: &mut D, base: u32) -> Result<Option<u16>, Mb85rcError> {
+        let mut header = [0u8; HEADER as usize];
+        dev.read_at(base, &mut header)?;
+        let count = u16::from_le_bytes([header[4], header[5]]);
+        if u32::from_le_bytes(header[0..4].try_into().unwrap()) != MAGIC || count > self.max_entries {
+            return Ok(None);
+        }
+
+        let mut crc = Crc32::new();
+        crc.update(&header[..6]);
+        crc::update_from_device(&mut crc, dev, base + HEADER, count as u32 * ENTRY)?;
+        match crc.finish() == u32::from_le_bytes(header[8..12].try_into().unwrap()) {
+            true => Ok(Some(count)),
+            false => Ok(None),
+        }
+    }
+
+    /// Seal the copy at `base` as holding `count` entries
+    fn write_header<D: FramDevice + ?Sized>(&self, dev: &mut D, base: u32, count: u16) -> Result<(), Mb85rcError> {
+        let mut header = [0u8; HEADER as usize];
+        header[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[4..6].copy_from_slice(&count.to_le_bytes());
+
+        let mut crc = Crc32::new();
+        crc.update(&header[..6]);
+        crc::update_from_device(&mut crc, dev, base + HEADER, count as u32 * ENTRY)?;
+        header[8..12].copy_from_slice(&crc.finish().to_le_bytes());
+        dev.write_at(base, &header)
+    }
+
+    /// Number of regions in a valid primary copy, repairing it from the backup if needed
+    fn load<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<u16, Mb85rcError> {
+        if let Some(count) = self.check_copy(dev, self.addr)? {
+            return Ok(count);
+        }
+        let backup = self.addr + self.copy_size();
+        match self.check_copy(dev, backup)? {
+            Some(count) => {
+                copy_within(dev, backup, self.addr, self.copy_size())?;
+                Ok(count)
+            },
+            None => Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        }
+    }
+
+    /// Number of regions in the table
+    pub fn len<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<u16, Mb85rcError> {
+        self.load(dev)
+    }
+
+    /// The region at `index`, in the order they were added
+    pub fn get<D: FramDevice + ?Sized>(&self, dev: &mut D, index: u16) -> Result<Region, Mb85rcError> {
+        if index >= self.load(dev)? {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        self.read_entry(dev, index)
+    }
+
+    fn read_entry<D: FramDevice + ?Sized>(&self, dev: &mut D, index: u16) -> Result<Region, Mb85rcError> {
+        let mut raw = [0u8; ENTRY as usize];
+        dev.read_at(self.entry_addr(self.addr, index), &mut raw)?;
+        Ok(Region::from_bytes(&raw))
+    }
+
+    fn position<D: FramDevice + ?Sized>(&self, dev: &mut D, count: u16, name: &str) -> Result<Option<u16>, Mb85rcError> {
+        for index in 0..count {
+            if self.read_entry(dev, index)?.name() == name {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Look up a region by name
+    pub fn find<D: FramDevice + ?Sized>(&self, dev: &mut D, name: &str) -> Result<Option<Region>, Mb85rcError> {
+        let count = self.load(dev)?;
+        match self.position(dev, count, name)? {
+            Some(index) => self.read_entry(dev, index).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Add a region called `name` covering `range`
+    ///
+    /// Fails with [`ErrorKind::Overlap`] if the range overlaps the table or
+    /// another region, and [`ErrorKind::InvalidData`] if the name is already
+    /// taken, empty or too long.
+    pub fn add<D: FramDevice + ?Sized>(&self, dev: &mut D, name: &str, range: Range<u32>, flags: u32) -> Result<Region, Mb85rcError> {
+        if name.is_empty() || name.len() > REGION_NAME_LEN || name.as_bytes().contains(&0) {
+            return Err(Mb85rcError::new(ErrorKind::InvalidData));
+        }
+        if range.start > range.end || range.end > dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        let overlaps = |other: Range<u32>| range.start < other.end && other.start < range.end;
+        if overlaps(self.range()) {
+            return Err(Mb85rcError::new(ErrorKind::Overlap));
+        }
+
+        let count = self.load(dev)?;
+        if count == self.max_entries {
+            return Err(Mb85rcError::new(ErrorKind::Full));
+        }
+        for index in 0..count {
+            let other = self.read_entry(dev, index)?;
+            if other.name() == name {
+                return Err(Mb85rcError::new(ErrorKind::InvalidData));
+            }
+            if overlaps(other.range()) {
+                return Err(Mb85rcError::new(ErrorKind::Overlap));
+            }
+        }
+
+        let mut region = Region { name: [0; REGION_NAME_LEN], offset: range.start, len: range.end - range.start, flags };
+        region.name[..name.len()].copy_from_slice(name.as_bytes());
+
+        self.backup(dev)?;
+        dev.write_at(self.entry_addr(self.addr, count), &region.to_bytes())?;
+        self.write_header(dev, self.addr, count + 1)?;
+        self.backup(dev)?;
+        Ok(region)
+    }
+
+    /// Remove the region called `name`, leaving its contents alone
+    pub fn remove<D: FramDevice + ?Sized>(&self, dev: &mut D, name: &str) -> Result<(), Mb85rcError> {
+        let count = self.load(dev)?;
+        let index = self.position(dev, count, name)?.ok_or(Mb85rcError::new(ErrorKind::NotFound))?;
+
+        self.backup(dev)?;
+        // move the last entry into the gap
+        let last = count - 1;
+        if index != last {
+            copy_within(dev, self.entry_addr(self.addr, last), self.entry_addr(self.addr, index), ENTRY)?;
+        }
+        self.write_header(dev, self.addr, last)?;
+        self.backup(dev)
+    }
+
+    fn backup<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        copy_within(dev, self.addr, self.addr + self.copy_size(), self.copy_size())
+    }
+
+    /// Open the region called `name` as a [`FramSlice`]
+    pub fn open<'a, D: FramDevice + ?Sized>(&self, dev: &'a mut D, name: &str) -> Result<FramSlice<'a, D>, Mb85rcError> {
+        let region = self.find(dev, name)?.ok_or(Mb85rcError::new(ErrorKind::NotFound))?;
+        FramSlice::new(dev, region.range())
+    }
+}
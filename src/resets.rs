@@ -0,0 +1,70 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Per-cause reset counts
+///
+/// Keeps `N` saturating 32-bit counters at `addr`, one per reset cause. Call
+/// [`record_flags`](Self::record_flags) early in boot with the MCU's
+/// reset-cause register (bit `i` counts towards cause `i`), or
+/// [`record`](Self::record) with a cause number, and read the counts back
+/// for field diagnostics. The handle only records where the counters live;
+/// pass the device to each call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetCounters<const N: usize> {
+    addr: u32,
+}
+
+impl<const N: usize> ResetCounters<N> {
+    /// Bytes of FRAM the counters take up
+    pub const SIZE: u32 = N as u32 * 4;
+
+    /// Counters stored at `addr`
+    pub const fn new(addr: u32) -> Self {
+        Self { addr }
+    }
+
+    fn counter_addr(&self, cause: usize) -> Result<u32, Mb85rcError> {
+        match cause < N {
+            true => Ok(self.addr + cause as u32 * 4),
+            false => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    /// How many resets have been put down to `cause`
+    pub fn count<D: FramDevice + ?Sized>(&self, dev: &mut D, cause: usize) -> Result<u32, Mb85rcError> {
+        let mut raw = [0u8; 4];
+        dev.read_at(self.counter_addr(cause)?, &mut raw)?;
+        Ok(u32::from_le_bytes(raw))
+    }
+
+    /// Every count, indexed by cause
+    pub fn counts<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<[u32; N], Mb85rcError> {
+        let mut counts = [0u32; N];
+        for (cause, count) in counts.iter_mut().enumerate() {
+            *count = self.count(dev, cause)?;
+        }
+        Ok(counts)
+    }
+
+    /// Count one reset due to `cause`, returning the new count
+    pub fn record<D: FramDevice + ?Sized>(&self, dev: &mut D, cause: usize) -> Result<u32, Mb85rcError> {
+        let count = self.count(dev, cause)?.saturating_add(1);
+        dev.write_at(self.counter_addr(cause)?, &count.to_le_bytes())?;
+        Ok(count)
+    }
+
+    /// Count one reset for every set bit in `flags`, ignoring bits past `N`
+    pub fn record_flags<D: FramDevice + ?Sized>(&self, dev: &mut D, flags: u32) -> Result<(), Mb85rcError> {
+        for cause in (0..N.min(32)).filter(|&bit| flags & (1 << bit) != 0) {
+            self.record(dev, cause)?;
+        }
+        Ok(())
+    }
+
+    /// Set every count back to zero
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        for cause in 0..N {
+            dev.write_at(self.counter_addr(cause)?, &[0u8; 4])?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{FramDevice, FramExt, Mb85rcError, ErrorKind};
+use crate::crc::crc32;
+use crate::mirror::Mirrored;
+
+/// Bytes in an encoded [`RestoreProgress`]
+const RECORD: usize = 16;
+
+/// How far a [`ResumableRestore`] has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreProgress {
+    /// Where the image is being written
+    pub dest: u32,
+    /// Length of the image in bytes
+    pub len: u32,
+    /// CRC-32 of the image
+    pub crc: u32,
+    /// Bytes of the image already written
+    pub done: u32,
+}
+
+impl RestoreProgress {
+    fn to_bytes(self) -> [u8; RECORD] {
+        let mut raw = [0u8; RECORD];
+        raw[0..4].copy_from_slice(&self.dest.to_le_bytes());
+        raw[4..8].copy_from_slice(&self.len.to_le_bytes());
+        raw[8..12].copy_from_slice(&self.crc.to_le_bytes());
+        raw[12..16].copy_from_slice(&self.done.to_le_bytes());
+        raw
+    }
+
+    fn from_bytes(raw: &[u8; RECORD]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(raw[i..i + 4].try_into().unwrap());
+        Self { dest: word(0), len: word(4), crc: word(8), done: word(12) }
+    }
+
+    /// Whether the whole image has been written
+    pub fn is_complete(&self) -> bool {
+        self.done >= self.len
+    }
+}
+
+/// Writes an image to the device, picking up where it left off after an interruption
+///
+/// Progress is checkpointed to a small record at `addr`, kept in two
+/// CRC-checked copies, every [`with_checkpoint`](Self::with_checkpoint)
+/// bytes. Calling [`restore`](Self::restore) again with the same image and
+/// destination continues from the last checkpoint; a different image starts
+/// over. Once everything is written the whole destination is read back and
+/// compared against the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResumableRestore {
+    progress: Mirrored<RECORD>,
+    checkpoint: u32,
+}
+
+impl ResumableRestore {
+    /// Bytes of FRAM the progress record takes up
+    pub const SIZE: u32 = Mirrored::<RECORD>::SIZE;
+
+    /// Keep the progress record at `addr`
+    pub const fn new(addr: u32) -> Self {
+        Self { progress: Mirrored::new(addr), checkpoint: 1024 }
+    }
+
+    /// Record progress every `bytes` bytes (1024 by default)
+    ///
+    /// Smaller checkpoints lose less work to an interruption but spend more
+    /// bus time updating the record.
+    pub fn with_checkpoint(mut self, bytes: u32) -> Self {
+        self.checkpoint = bytes.max(1);
+        self
+    }
+
+    /// The recorded progress, if there is any
+    pub fn progress<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<Option<RestoreProgress>, Mb85rcError> {
+        Ok(self.progress.read(dev)?.map(|(_, raw)| RestoreProgress::from_bytes(&raw)))
+    }
+
+    /// Write `image` to `dev` at `dest`, resuming if an earlier attempt was interrupted
+    ///
+    /// Returns the number of bytes written by this call. If the read-back
+    /// check finds a mismatch, the progress is reset so the next attempt
+    /// starts from scratch, and this fails with [`ErrorKind::InvalidData`].
+    pub fn restore<D: FramDevice + ?Sized>(&self, dev: &mut D, dest: u32, image: &[u8]) -> Result<u32, Mb85rcError> {
+        let len = u32::try_from(image.len()).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+        let end = dest.checked_add(len).filter(|&end| end <= dev.capacity())
+            .ok_or(Mb85rcError::new(ErrorKind::OutOfRange))?;
+        let record = self.progress.addr()..self.progress.addr() + Self::SIZE;
+        if dest < record.end && record.start < end {
+            return Err(Mb85rcError::new(ErrorKind::Overlap));
+        }
+
+        let fresh = RestoreProgress { dest, len, crc: crc32(image), done: 0 };
+        let mut progress = match self.progress(dev)? {
+            Some(p) if (p.dest, p.len, p.crc) == (fresh.dest, fresh.len, fresh.crc) => p,
+            _ => {
+                self.progress.write(dev, &fresh.to_bytes())?;
+                fresh
+            },
+        };
+
+        let start = progress.done.min(len);
+        for chunk_start in (start..len).step_by(self.checkpoint as usize) {
+            let chunk_end = chunk_start.saturating_add(self.checkpoint).min(len);
+            dev.write_at(dest + chunk_start, &image[chunk_start as usize..chunk_end as usize])?;
+            progress.done = chunk_end;
+            self.progress.write(dev, &progress.to_bytes())?;
+        }
+
+        if dev.compare_with(dest..end, image)?.is_some() {
+            self.progress.write(dev, &fresh.to_bytes())?;
+            return Err(Mb85rcError::new(ErrorKind::InvalidData));
+        }
+        Ok(len - start)
+    }
+
+    /// Forget any recorded progress
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        dev.write_at(self.progress.addr(), &[0u8; Self::SIZE as usize])
+    }
+}
@@ -0,0 +1,69 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError};
+
+/// Outcome of [`verify_retention_pattern`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// Bytes compared
+    pub checked: u32,
+    /// Bytes that didn't hold the expected value
+    pub mismatches: u32,
+    /// Address of the first byte that didn't match
+    pub first_mismatch: Option<u32>,
+}
+
+impl RetentionReport {
+    /// Whether every byte matched
+    pub fn passed(&self) -> bool {
+        self.mismatches == 0
+    }
+}
+
+/// The pattern byte for `addr`
+///
+/// Each 32-bit word holds its own address, with every other word inverted,
+/// so every bit is stored as both a 0 and a 1 somewhere nearby and a shifted
+/// or aliased address shows up as a mismatch.
+fn pattern_byte(addr: u32) -> u8 {
+    let word = addr & !3;
+    let value = if word & 4 == 0 { word } else { !word };
+    value.to_le_bytes()[(addr & 3) as usize]
+}
+
+/// Fill `region` with an address-in-address pattern for retention testing
+///
+/// Write the pattern, power-cycle or bake the board, then check it with
+/// [`verify_retention_pattern`].
+pub fn write_retention_pattern<D: FramDevice + ?Sized>(dev: &mut D, region: Range<u32>) -> Result<(), Mb85rcError> {
+    let mut buf = [0u8; 64];
+    let mut addr = region.start;
+    while addr < region.end {
+        let n = (region.end - addr).min(buf.len() as u32) as usize;
+        for (i, byte) in buf[..n].iter_mut().enumerate() {
+            *byte = pattern_byte(addr + i as u32);
+        }
+        dev.write_at(addr, &buf[..n])?;
+        addr += n as u32;
+    }
+    Ok(())
+}
+
+/// Check `region` still holds the pattern from [`write_retention_pattern`]
+pub fn verify_retention_pattern<D: FramDevice + ?Sized>(dev: &mut D, region: Range<u32>) -> Result<RetentionReport, Mb85rcError> {
+    let mut report = RetentionReport::default();
+    let mut buf = [0u8; 64];
+    let mut addr = region.start;
+    while addr < region.end {
+        let n = (region.end - addr).min(buf.len() as u32) as usize;
+        dev.read_at(addr, &mut buf[..n])?;
+        for (i, &byte) in buf[..n].iter().enumerate() {
+            if byte != pattern_byte(addr + i as u32) {
+                report.mismatches += 1;
+                report.first_mismatch.get_or_insert(addr + i as u32);
+            }
+        }
+        report.checked += n as u32;
+        addr += n as u32;
+    }
+    Ok(report)
+}
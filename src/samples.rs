@@ -0,0 +1,199 @@
+use core::ops::Range;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::mirror::Mirrored;
+
+/// Ring buffer of fixed-size `R`-byte samples, numbered from the first one ever appended
+///
+/// Meant for high-rate data like sensor readings, where every record has
+/// the same size: records are stored back to back with no per-record
+/// framing, so the whole region goes to data. Appending writes the sample
+/// and then a small header (two CRC-checked copies at the start of the
+/// region), so a reset mid-append loses at most that sample. Once the
+/// region is full each append overwrites the oldest sample, which a reset
+/// mid-append can leave half overwritten.
+///
+/// Samples keep their number for as long as they're stored, so
+/// [`get`](Self::get) finds any of them with one read. The header records
+/// which slot the oldest sample is in, so numbering can wrap past
+/// `u32::MAX` whatever the capacity. The device is passed to each call that
+/// touches it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleLog<const R: usize> {
+    header: Mirrored<12>,
+    data: u32,
+    capacity: u32,
+    first: u32,
+    next: u32,
+    head: u32,
+}
+
+impl<const R: usize> SampleLog<R> {
+    /// Bytes at the start of the region taken up by the header
+    pub const HEADER_SIZE: u32 = Mirrored::<12>::SIZE;
+
+    /// Find the samples already stored in `region`, or start an empty log there
+    pub fn load<D: FramDevice + ?Sized>(dev: &mut D, region: Range<u32>) -> Result<Self, Mb85rcError> {
+        if region.start > region.end || region.end > dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        let capacity = (region.end - region.start).saturating_sub(Self::HEADER_SIZE) / R.max(1) as u32;
+        if R == 0 || capacity == 0 {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+
+        let header = Mirrored::new(region.start);
+        let (first, next, head) = match header.read(dev)? {
+            Some((_, raw)) => (
+                u32::from_le_bytes(raw[..4].try_into().unwrap()),
+                u32::from_le_bytes(raw[4..8].try_into().unwrap()),
+                u32::from_le_bytes(raw[8..].try_into().unwrap()),
+            ),
+            None => (0, 0, 0),
+        };
+        if next.wrapping_sub(first) > capacity || head >= capacity {
+            return Err(Mb85rcError::new(ErrorKind::InvalidData));
+        }
+        Ok(Self { header, data: region.start + Self::HEADER_SIZE, capacity, first, next, head })
+    }
+
+    /// How many samples fit before the oldest start being overwritten
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// How many samples are stored
+    pub fn len(&self) -> u32 {
+        self.next.wrapping_sub(self.first)
+    }
+
+    /// Whether no samples are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Numbers of the stored samples, oldest first
+    pub fn range(&self) -> Range<u32> {
+        self.first..self.next
+    }
+
+    /// Store `sample`, returning its number
+    pub fn append<D: FramDevice + ?Sized>(&mut self, dev: &mut D, sample: &[u8; R]) -> Result<u32, Mb85rcError> {
+        let n = self.next;
+        dev.write_at(self.slot_addr(n), sample)?;
+
+        let next = n.wrapping_add(1);
+        let (first, head) = match next.wrapping_sub(self.first) > self.capacity {
+            true => (self.first.wrapping_add(1), self.slot(self.first.wrapping_add(1))),
+            false => (self.first, self.head),
+        };
+        self.store_header(dev, first, next, head)?;
+        Ok(n)
+    }
+
+    /// Sample number `n`, or `None` if it was never stored or has been overwritten
+    pub fn get<D: FramDevice + ?Sized>(&self, dev: &mut D, n: u32) -> Result<Option<[u8; R]>, Mb85rcError> {
+        if n.wrapping_sub(self.first) >= self.len() {
+            return Ok(None);
+        }
+        let mut sample = [0u8; R];
+        dev.read_at(self.slot_addr(n), &mut sample)?;
+        Ok(Some(sample))
+    }
+
+    /// Read consecutive samples starting at number `start` into `buf`, returning how many were read
+    ///
+    /// `buf` holds as many whole samples as fit; reading stops at the
+    /// newest. Takes at most two device reads however many samples there are.
+    pub fn read<D: FramDevice + ?Sized>(&self, dev: &mut D, start: u32, buf: &mut [u8]) -> Result<u32, Mb85rcError> {
+        let offset = start.wrapping_sub(self.first);
+        if offset >= self.len() {
+            return Ok(0);
+        }
+        let count = (self.len() - offset).min((buf.len() / R) as u32);
+
+        // the run may wrap around the end of the region
+        let slot = self.slot(start);
+        let before_wrap = count.min(self.capacity - slot);
+        let (head, tail) = buf[..count as usize * R].split_at_mut(before_wrap as usize * R);
+        dev.read_at(self.slot_addr(start), head)?;
+        if !tail.is_empty() {
+            dev.read_at(self.data, tail)?;
+        }
+        Ok(count)
+    }
+
+    /// Read the stored samples numbered in `range` into `buf`, returning which were read
+    ///
+    /// For sync protocols: `last + 1..u32::MAX` asks for everything since
+    /// sample `last`. Samples already overwritten are skipped, and reading
+    /// stops when `buf` is full; ask again from the end of the returned range
+    /// for the rest. Like [`read`](Self::read), takes at most two device reads.
+    pub fn read_range<D: FramDevice + ?Sized>(&self, dev: &mut D, range: Range<u32>, buf: &mut [u8]) -> Result<Range<u32>, Mb85rcError> {
+        let start = range.start.max(self.first).min(self.next);
+        let wanted = range.end.max(start).min(self.next).wrapping_sub(start);
+        let len = (wanted as usize).min(buf.len() / R) * R;
+        let count = self.read(dev, start, &mut buf[..len])?;
+        Ok(start..start.wrapping_add(count))
+    }
+
+    /// Forget every sample; numbering carries on from where it was
+    pub fn clear<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        self.store_header(dev, self.next, self.next, self.slot(self.next))
+    }
+
+    /// Slot sample `n` goes in, counting on from the oldest sample's slot
+    ///
+    /// `n` is at most one past the newest, so this never reaches back into
+    /// samples still stored.
+    fn slot(&self, n: u32) -> u32 {
+        ((self.head as u64 + n.wrapping_sub(self.first) as u64) % self.capacity as u64) as u32
+    }
+
+    pub(crate) fn slot_addr(&self, n: u32) -> u32 {
+        self.data + self.slot(n) * R as u32
+    }
+
+    fn store_header<D: FramDevice + ?Sized>(&mut self, dev: &mut D, first: u32, next: u32, head: u32) -> Result<(), Mb85rcError> {
+        let mut raw = [0u8; 12];
+        raw[..4].copy_from_slice(&first.to_le_bytes());
+        raw[4..8].copy_from_slice(&next.to_le_bytes());
+        raw[8..].copy_from_slice(&head.to_le_bytes());
+        self.header.write(dev, &raw)?;
+        self.first = first;
+        self.next = next;
+        self.head = head;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::SampleLog;
+    use crate::{Builder, SimFram};
+
+    #[test]
+    fn numbering_wraps_with_any_capacity() {
+        let mut fram = Builder::new().with_size(1024).connect_i2c(SimFram::new(1024));
+        let region = 0..SampleLog::<4>::HEADER_SIZE + 3 * 4;
+        let mut log = SampleLog::<4>::load(&mut fram, region.clone()).unwrap();
+        assert_eq!(log.capacity(), 3);
+
+        // 3 doesn't divide 2^32, so `n % capacity` would put samples u32::MAX and 0 in one slot
+        log.store_header(&mut fram, u32::MAX - 3, u32::MAX - 3, 2).unwrap();
+        for n in u32::MAX - 3..=u32::MAX {
+            log.append(&mut fram, &n.to_le_bytes()).unwrap();
+        }
+        log.append(&mut fram, &0u32.to_le_bytes()).unwrap();
+
+        let log = SampleLog::<4>::load(&mut fram, region).unwrap();
+        assert_eq!((log.range().start, log.range().end, log.len()), (u32::MAX - 1, 1, 3));
+        for n in [u32::MAX - 1, u32::MAX, 0] {
+            assert_eq!(log.get(&mut fram, n).unwrap(), Some(n.to_le_bytes()));
+        }
+        let mut buf = [0u8; 12];
+        assert_eq!(log.read(&mut fram, u32::MAX - 1, &mut buf).unwrap(), 3);
+        assert_eq!(buf[..8], [(u32::MAX - 1).to_le_bytes(), u32::MAX.to_le_bytes()].concat());
+        assert_eq!(buf[8..], [0; 4]);
+    }
+}
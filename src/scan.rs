@@ -0,0 +1,61 @@
+use embedded_hal::blocking::i2c;
+
+use crate::DeviceId;
+
+/// The range of slave addresses an MB85RC can be strapped to
+pub const FRAM_ADDRESSES: core::ops::RangeInclusive<u8> = 0x50..=0x57;
+
+/// What [`scan`] found at one address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    /// Nothing answered
+    Empty,
+    /// An FRAM answered with a valid device ID
+    Fram(DeviceId),
+    /// Something answered but failed the device-ID check (an EEPROM, an RTC, ...)
+    Unknown,
+}
+
+/// Result of probing one address in [`scan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanEntry {
+    /// The slave address that was probed
+    pub address: u8,
+    /// What was found there
+    pub probe: Probe,
+}
+
+/// Read the device ID of the FRAM at `address`, if there is one
+pub(crate) fn read_device_id<I2C>(i2c: &mut I2C, address: u8) -> Result<[u8; 3], <I2C as i2c::WriteRead>::Error>
+where
+    I2C: i2c::WriteRead,
+{
+    let mut id = [0u8; 3];
+    i2c.write_read(0xF8 >> 1, &[address << 1], &mut id)?;
+    Ok(id)
+}
+
+/// Probe every address an FRAM can live at (0x50 to 0x57)
+///
+/// The device-ID command is tried first, as it doesn't touch the device at
+/// `address` at all. Addresses that don't answer it are then probed with a
+/// one-byte read, which only moves the register pointer on other devices.
+pub fn scan<I2C>(i2c: &mut I2C) -> [ScanEntry; 8]
+where
+    I2C: i2c::WriteRead,
+{
+    let mut entries = [ScanEntry { address: 0, probe: Probe::Empty }; 8];
+
+    for (entry, address) in entries.iter_mut().zip(FRAM_ADDRESSES) {
+        entry.address = address;
+        entry.probe = match read_device_id(i2c, address) {
+            Ok(id) if DeviceId::from_bytes(id).is_fujitsu() => Probe::Fram(DeviceId::from_bytes(id)),
+            _ => match i2c.write_read(address, &[0], &mut [0u8]) {
+                Ok(_) => Probe::Unknown,
+                Err(_) => Probe::Empty,
+            },
+        };
+    }
+
+    entries
+}
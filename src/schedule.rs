@@ -0,0 +1,76 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError, ErrorKind, MAX_TRANSFER};
+
+/// Decides when to back up a set of regions, and streams them out when it does
+///
+/// Call [`maybe_backup`](Self::maybe_backup) from the main loop with the
+/// current time. It backs up once `interval` ticks have passed since the
+/// last backup, or straight away after [`mark_dirty`](Self::mark_dirty).
+/// Mark it dirty when an [`Autosave`](crate::Autosave) poll saves something
+/// or a [`Watcher`](crate::Watcher) reports a change, so important state
+/// gets copied off soon after it changes without a separate cron job.
+#[derive(Debug, Clone)]
+pub struct BackupSchedule<const N: usize> {
+    regions: [Range<u32>; N],
+    interval: u32,
+    last: u32,
+    dirty: bool,
+}
+
+impl<const N: usize> BackupSchedule<N> {
+    /// Back up `regions` every `interval` ticks, counting `now` as the last backup
+    pub fn new(regions: [Range<u32>; N], interval: u32, now: u32) -> Self {
+        Self { regions, interval, last: now, dirty: false }
+    }
+
+    /// The regions that get backed up
+    pub fn regions(&self) -> &[Range<u32>; N] {
+        &self.regions
+    }
+
+    /// Back up on the next call to [`maybe_backup`](Self::maybe_backup), whatever the time
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether a backup has been asked for with [`mark_dirty`](Self::mark_dirty)
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Whether a backup is due at `now`
+    pub fn is_due(&self, now: u32) -> bool {
+        self.dirty || now.wrapping_sub(self.last) >= self.interval
+    }
+
+    /// Back up if one is due, returning whether it did
+    ///
+    /// Each region is read in [`MAX_TRANSFER`]-byte chunks and `sink` is
+    /// called with the address and contents of each one, in order. If reading
+    /// or `sink` fails the backup stays due, so the next call tries again.
+    pub fn maybe_backup<D, F>(&mut self, dev: &mut D, now: u32, mut sink: F) -> Result<bool, Mb85rcError>
+    where
+        D: FramDevice + ?Sized,
+        F: FnMut(u32, &[u8]) -> Result<(), Mb85rcError>,
+    {
+        if !self.is_due(now) {
+            return Ok(false);
+        }
+
+        let mut buf = [0u8; MAX_TRANSFER];
+        for region in &self.regions {
+            if region.start > region.end || region.end > dev.capacity() {
+                return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+            }
+            for addr in region.clone().step_by(MAX_TRANSFER) {
+                let chunk = &mut buf[..(region.end - addr).min(MAX_TRANSFER as u32) as usize];
+                dev.read_at(addr, chunk)?;
+                sink(addr, chunk)?;
+            }
+        }
+
+        self.last = now;
+        self.dirty = false;
+        Ok(true)
+    }
+}
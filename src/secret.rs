@@ -0,0 +1,85 @@
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce, Tag};
+use chacha20poly1305::aead::AeadInPlace;
+
+use crate::{FramDevice, TlvStore, NonceCounter, Mb85rcError, ErrorKind};
+
+/// Bytes of nonce stored in front of each value
+const NONCE: usize = 12;
+/// Bytes of authentication tag stored after each value
+const TAG: usize = 16;
+
+/// Encrypted values on top of a [`TlvStore`]
+///
+/// Each value is sealed with ChaCha20-Poly1305 under the caller's key and
+/// stored as its own record: a 12-byte nonce, the ciphertext and a 16-byte
+/// tag. The record's tag is bound in as associated data, so a value moved to
+/// a different tag doesn't decrypt. Nonces come from a [`NonceCounter`]
+/// kept outside the store, so clearing the store can't lead to a nonce
+/// being reused under the same key. `N` is the largest value that can be
+/// stored.
+pub struct SecretStore<D: FramDevice, C: FramDevice, const N: usize> {
+    store: TlvStore<D>,
+    nonces: NonceCounter<C>,
+    cipher: ChaCha20Poly1305,
+    buf: [u8; N],
+}
+
+impl<D: FramDevice, C: FramDevice, const N: usize> SecretStore<D, C, N> {
+    /// Use `store` for values encrypted with `key`, taking nonces from `nonces`
+    ///
+    /// Use one nonce counter per key.
+    pub fn new(store: TlvStore<D>, key: &[u8; 32], nonces: NonceCounter<C>) -> Self {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+        Self { store, nonces, cipher, buf: [0u8; N] }
+    }
+
+    /// Give back the record store and the nonce counter
+    pub fn into_inner(self) -> (TlvStore<D>, NonceCounter<C>) {
+        (self.store, self.nonces)
+    }
+
+    fn nonce(counter: u64) -> [u8; NONCE] {
+        let mut nonce = [0u8; NONCE];
+        nonce[..8].copy_from_slice(&counter.to_le_bytes());
+        nonce
+    }
+
+    /// Encrypt and store `value` under `tag`, replacing any earlier value
+    pub fn put_secret(&mut self, tag: u16, value: &[u8]) -> Result<(), Mb85rcError> {
+        let buf = self.buf.get_mut(..value.len()).ok_or(Mb85rcError::new(ErrorKind::TooLarge))?;
+        buf.copy_from_slice(value);
+
+        let nonce = Self::nonce(self.nonces.next_nonce()?);
+        let auth = self.cipher
+            .encrypt_in_place_detached(Nonce::from_slice(&nonce), &tag.to_le_bytes(), buf)
+            .map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+
+        let result = self.store.append_parts(tag, &[&nonce, buf, &auth]).map(|_| ());
+        // don't leave plaintext lying around in the buffer
+        self.buf.fill(0);
+        result
+    }
+
+    /// Decrypt the newest value stored under `tag` into `buf`, returning its length
+    ///
+    /// Fails with [`ErrorKind::AuthFailed`] if the stored value was tampered
+    /// with or was sealed with a different key.
+    pub fn get_secret(&mut self, tag: u16, buf: &mut [u8]) -> Result<Option<usize>, Mb85rcError> {
+        let Some(record) = self.store.find(tag)? else { return Ok(None) };
+        let len = (record.len as usize).checked_sub(NONCE + TAG).ok_or(Mb85rcError::new(ErrorKind::InvalidData))?;
+        let out = buf.get_mut(..len).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+
+        let (mut nonce, mut auth) = ([0u8; NONCE], [0u8; TAG]);
+        self.store.read_part(&record, 0, &mut nonce)?;
+        self.store.read_part(&record, NONCE as u32, out)?;
+        self.store.read_part(&record, (NONCE + len) as u32, &mut auth)?;
+
+        match self.cipher.decrypt_in_place_detached(Nonce::from_slice(&nonce), &tag.to_le_bytes(), out, Tag::from_slice(&auth)) {
+            Ok(()) => Ok(Some(len)),
+            Err(_) => {
+                out.fill(0);
+                Err(Mb85rcError::new(ErrorKind::AuthFailed))
+            },
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use embedded_hal::blocking::i2c;
+use core::error::Error;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{MB85RC, FramAddress, Mb85rcError, NoWriteProtect, SeekFrom, WriteProtect};
+
+/// Cloneable, thread-safe handle to a FRAM interface
+///
+/// Every clone keeps its own cursor, while bus access goes through a shared
+/// mutex so transactions from different threads never interleave.
+pub struct SharedFram<I2C, WP = NoWriteProtect> {
+    inner: Arc<Mutex<MB85RC<I2C, WP>>>,
+    cursor: FramAddress,
+}
+
+impl<I2C, WP> SharedFram<I2C, WP> {
+    /// Share an interface, starting this handle's cursor at the start of the device
+    pub fn new(fram: MB85RC<I2C, WP>) -> Self {
+        let cursor = FramAddress::new(0, fram.fram_size()).unwrap();
+        Self {
+            inner: Arc::new(Mutex::new(fram)),
+            cursor,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MB85RC<I2C, WP>> {
+        // the interface holds no state a panicking thread could leave half-updated
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the size of the shared device
+    pub fn fram_size(&self) -> u32 {
+        self.cursor.capacity()
+    }
+
+    /// Current position of this handle's cursor
+    pub fn position(&self) -> FramAddress {
+        self.cursor
+    }
+
+    /// Move this handle's cursor, returning the new position
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<u32, Mb85rcError> {
+        self.cursor = pos.resolve(self.cursor)?;
+        Ok(self.cursor.get())
+    }
+
+    /// Run `f` with exclusive access to the underlying interface
+    pub fn with_fram<R>(&self, f: impl FnOnce(&mut MB85RC<I2C, WP>) -> R) -> R {
+        f(&mut self.lock())
+    }
+}
+
+impl<I2C, WP: WriteProtect> SharedFram<I2C, WP>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    <I2C as i2c::WriteRead>::Error: Error,
+    <I2C as i2c::Write>::Error: Error,
+{
+    /// Directly read bytes at `addr` into the provided buffer
+    pub fn fram_read(&self, addr: FramAddress, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        self.lock().fram_read(addr, buf)
+    }
+
+    /// Directly write bytes at `addr` from the provided buffer
+    pub fn fram_write(&self, addr: FramAddress, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        self.lock().fram_write(addr, buf)
+    }
+
+    /// Read bytes at this handle's cursor and advance it
+    ///
+    /// Like [`MB85RC::read`], reads stop at the end of device memory
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        let n = self.fram_read(self.cursor, &mut buf[..len])?;
+        self.cursor = self.cursor.checked_add(n as u32).unwrap();
+        Ok(n)
+    }
+
+    /// Write bytes at this handle's cursor and advance it
+    ///
+    /// Like [`MB85RC::write`], writes stop at the end of device memory
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        let n = self.fram_write(self.cursor, &buf[..len])?;
+        self.cursor = self.cursor.checked_add(n as u32).unwrap();
+        Ok(n)
+    }
+}
+
+impl<I2C, WP> Clone for SharedFram<I2C, WP> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            cursor: self.cursor,
+        }
+    }
+}
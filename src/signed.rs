@@ -0,0 +1,55 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+
+use crate::{FramDevice, Mb85rcError, ErrorKind, ResumableRestore, apply_delta};
+
+/// Checks Ed25519 signatures on images before any of them reaches the device
+///
+/// Each write method verifies the whole image (or delta) against its
+/// signature first and fails with [`ErrorKind::AuthFailed`] without
+/// touching the device if it doesn't check out, so provisioning flows only
+/// ever write images signed by the holder of the matching private key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageVerifier {
+    key: VerifyingKey,
+}
+
+impl ImageVerifier {
+    /// Accept images signed by the private key matching `public_key`
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if `public_key` isn't a valid key
+    pub fn new(public_key: &[u8; 32]) -> Result<Self, Mb85rcError> {
+        let key = VerifyingKey::from_bytes(public_key).map_err(|_| Mb85rcError::new(ErrorKind::InvalidData))?;
+        Ok(Self { key })
+    }
+
+    /// Check `signature` over `image`
+    pub fn verify(&self, image: &[u8], signature: &[u8; 64]) -> Result<(), Mb85rcError> {
+        self.key.verify_strict(image, &Signature::from_bytes(signature))
+            .map_err(|_| Mb85rcError::new(ErrorKind::AuthFailed))
+    }
+
+    /// Verify `image` and write it to `dev` at `dest`
+    pub fn restore<D: FramDevice + ?Sized>(&self, dev: &mut D, dest: u32, image: &[u8], signature: &[u8; 64]) -> Result<(), Mb85rcError> {
+        self.verify(image, signature)?;
+        dev.write_at(dest, image)
+    }
+
+    /// Verify `image` and write it with `restore`, resuming an interrupted attempt
+    pub fn restore_resumable<D: FramDevice + ?Sized>(
+        &self,
+        dev: &mut D,
+        restore: &ResumableRestore,
+        dest: u32,
+        image: &[u8],
+        signature: &[u8; 64],
+    ) -> Result<u32, Mb85rcError> {
+        self.verify(image, signature)?;
+        restore.restore(dev, dest, image)
+    }
+
+    /// Verify `delta` and apply it with [`apply_delta`]
+    pub fn apply_delta<D: FramDevice + ?Sized>(&self, dev: &mut D, delta: &[u8], signature: &[u8; 64]) -> Result<u32, Mb85rcError> {
+        self.verify(delta, signature)?;
+        apply_delta(dev, delta)
+    }
+}
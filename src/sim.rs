@@ -0,0 +1,373 @@
+use embedded_hal::blocking::i2c;
+use core::fmt;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::addressing::{Addressing, TwoByteBanked, MAX_ADDRESS_BYTES};
+
+/// Slave address the device-ID command is sent to
+const DEVICE_ID_ADDR: u8 = 0xF8 >> 1;
+
+/// Simulated MB85RC that speaks the chip's I2C protocol on the host
+///
+/// Memory can be kept in RAM or persisted to a file, so integration tests run
+/// against the real driver without hardware. Like the chip, sequential accesses
+/// wrap around from the last byte back to address 0, the device-ID command
+/// reports the density and product of the part that size is, and parts over
+/// 64 KiB take the upper address bits from the low bits of the slave address.
+/// Other framings are chosen with [`with_addressing`](Self::with_addressing).
+///
+/// Failures can be scheduled with [`inject`](Self::inject) and
+/// [`flip_bits`](Self::flip_bits) to exercise error handling.
+pub struct SimFram {
+    mem: Vec<u8>,
+    file: Option<File>,
+    device_addr: u8,
+    addressing: &'static dyn Addressing,
+    has_device_id: bool,
+    latency: Duration,
+    transactions: u64,
+    scheduled: Vec<(u64, Fault)>,
+    flips: Vec<(Range<u32>, u8)>,
+}
+
+/// A one-off failure [`SimFram`] can be told to produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The device doesn't acknowledge; nothing is read or written
+    Nack,
+    /// The bus times out; nothing is read or written
+    Timeout,
+    /// Only the first `n` data bytes of a write land before the transaction fails
+    ///
+    /// Acts like [`Fault::Nack`] on reads
+    TornWrite(usize),
+}
+
+impl SimFram {
+    /// Simulate a device of `size` bytes held only in RAM, initially all zeroes
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0, since no FRAM is that small
+    pub fn new(size: u32) -> Self {
+        assert!(size > 0, "SimFram needs a size of at least one byte");
+        Self {
+            mem: vec![0u8; size as usize],
+            file: None,
+            device_addr: 0x50,
+            addressing: &TwoByteBanked,
+            has_device_id: true,
+            latency: Duration::ZERO,
+            transactions: 0,
+            scheduled: Vec::new(),
+            flips: Vec::new(),
+        }
+    }
+
+    /// Simulate a device of `size` bytes persisted to the file at `path`
+    ///
+    /// An existing file keeps its contents (truncated or zero-extended to
+    /// `size`), so state survives between runs. Fails with
+    /// [`io::ErrorKind::InvalidInput`] if `size` is 0.
+    pub fn open<P: AsRef<Path>>(path: P, size: u32) -> io::Result<Self> {
+        if size == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "SimFram needs a size of at least one byte"));
+        }
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        file.set_len(size as u64)?;
+
+        let mut mem = vec![0u8; size as usize];
+        file.read_exact(&mut mem)?;
+
+        Ok(Self {
+            mem,
+            file: Some(file),
+            device_addr: 0x50,
+            addressing: &TwoByteBanked,
+            has_device_id: true,
+            latency: Duration::ZERO,
+            transactions: 0,
+            scheduled: Vec::new(),
+            flips: Vec::new(),
+        })
+    }
+
+    /// Answer at a different I2C address than the default 0x50
+    pub fn with_address(mut self, address: u8) -> Self {
+        self.device_addr = address;
+        self
+    }
+
+    /// Take memory addresses framed by `addressing` instead of two bytes plus bank bits
+    ///
+    /// Use [`OneByteBanked`](crate::OneByteBanked) to simulate an MB85RC04V
+    /// or MB85RC16, for example. Data bytes in the write half of a
+    /// write-read land in memory, as on the chip, so a probe that sends too
+    /// many address bytes shows up as a changed byte.
+    pub fn with_addressing(mut self, addressing: &'static dyn Addressing) -> Self {
+        self.addressing = addressing;
+        self
+    }
+
+    /// Don't answer the device-ID command, like the MB85RC16 and other older parts
+    pub fn without_device_id(mut self) -> Self {
+        self.has_device_id = false;
+        self
+    }
+
+    /// Sleep for `latency` on every transaction
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Size of the simulated memory in bytes
+    pub fn size(&self) -> u32 {
+        self.mem.len() as u32
+    }
+
+    /// Look at the simulated memory directly
+    pub fn memory(&self) -> &[u8] {
+        &self.mem
+    }
+
+    /// Change the simulated memory directly (not written through to the file)
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        &mut self.mem
+    }
+
+    /// Number of memory transactions so far (device-ID reads are not counted)
+    pub fn transactions(&self) -> u64 {
+        self.transactions
+    }
+
+    /// Make transaction number `n` (counting from 0, see [`transactions`](Self::transactions)) fail with `fault`
+    pub fn inject(&mut self, n: u64, fault: Fault) {
+        self.scheduled.push((n, fault));
+    }
+
+    /// Make the next transaction fail with `fault`
+    pub fn inject_next(&mut self, fault: Fault) {
+        self.inject(self.transactions, fault);
+    }
+
+    /// Flip the bits in `mask` of every byte read from `range`, until the faults are cleared
+    ///
+    /// Stored memory is left alone, so this models a marginal cell or noisy bus
+    /// rather than corruption (use [`memory_mut`](Self::memory_mut) for that).
+    pub fn flip_bits(&mut self, range: Range<u32>, mask: u8) {
+        self.flips.push((range, mask));
+    }
+
+    /// Drop all scheduled faults and bit flips
+    pub fn clear_faults(&mut self) {
+        self.scheduled.clear();
+        self.flips.clear();
+    }
+
+    /// Count a transaction and take any fault scheduled for it
+    fn next_fault(&mut self) -> Option<Fault> {
+        let n = self.transactions;
+        self.transactions += 1;
+        let i = self.scheduled.iter().position(|(at, _)| *at == n)?;
+        Some(self.scheduled.swap_remove(i).1)
+    }
+
+    fn flip_mask(&self, addr: usize) -> u8 {
+        self.flips.iter()
+            .filter(|(r, _)| r.contains(&(addr as u32)))
+            .fold(0, |m, (_, mask)| m | mask)
+    }
+
+    /// Bytes each slave address covers, or `None` if there's only one
+    fn bank_size(&self) -> Option<usize> {
+        match self.addressing.span(0) {
+            u32::MAX => None,
+            span => Some(span as usize),
+        }
+    }
+
+    fn banks(&self) -> u8 {
+        self.bank_size().map_or(1, |bank| self.mem.len().div_ceil(bank).max(1)) as u8
+    }
+
+    /// The three device-ID bytes: Fujitsu manufacturer code, density and product
+    ///
+    /// Sizes that aren't a real part's round down, so anything under 2 KiB
+    /// reports the density of the 512-byte MB85RC04V. The product byte is
+    /// the known part's of that density, or 0 if there isn't one.
+    pub fn device_id(&self) -> [u8; 3] {
+        let density = (self.mem.len() / 1024).max(1).ilog2() as u8 & 0xF;
+        [0x00, 0xA0 | density, crate::id::product_for(density).unwrap_or(0)]
+    }
+
+    /// Work out the start address for a transaction and how many address bytes
+    /// it took, or `None` if nobody answers
+    fn locate(&self, address: u8, bytes: &[u8]) -> Option<(usize, usize)> {
+        let bank = address.checked_sub(self.device_addr).filter(|&b| b < self.banks())?;
+        let n = self.addressing.address_bytes(0, &mut [0u8; MAX_ADDRESS_BYTES]);
+        if bytes.len() < n {
+            return None;
+        }
+        let offset = bytes[..n].iter().fold(0usize, |a, &b| (a << 8) | b as usize);
+        let addr = bank as usize * self.bank_size().unwrap_or(0) + offset;
+        Some((addr % self.mem.len(), n))
+    }
+
+    /// Store `data` from `start` on, wrapping at the end of memory
+    fn store(&mut self, start: usize, data: &[u8]) -> Result<(), SimError> {
+        let size = self.mem.len();
+        for (i, b) in data.iter().enumerate() {
+            self.mem[(start + i) % size] = *b;
+        }
+        self.persist(start, data.len().min(size)).map_err(|e| SimError::Io(e.kind()))
+    }
+
+    fn persist(&mut self, start: usize, len: usize) -> io::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        // a wrapped write lands in two pieces
+        let first = len.min(self.mem.len() - start);
+        file.seek(io::SeekFrom::Start(start as u64))?;
+        file.write_all(&self.mem[start..start + first])?;
+        if first < len {
+            file.seek(io::SeekFrom::Start(0))?;
+            file.write_all(&self.mem[..len - first])?;
+        }
+        file.flush()
+    }
+
+    fn delay(&self) {
+        if !self.latency.is_zero() {
+            thread::sleep(self.latency);
+        }
+    }
+}
+
+impl i2c::Write for SimFram {
+    type Error = SimError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.delay();
+        let (start, n) = self.locate(address, bytes).ok_or(SimError::Nack)?;
+
+        let mut data = &bytes[n..];
+        let fault = self.next_fault();
+        match fault {
+            Some(Fault::Nack) => return Err(SimError::Nack),
+            Some(Fault::Timeout) => return Err(SimError::Timeout),
+            Some(Fault::TornWrite(n)) => data = &data[..n.min(data.len())],
+            None => {},
+        }
+
+        self.store(start, data)?;
+
+        match fault {
+            Some(_) => Err(SimError::Nack),
+            None => Ok(()),
+        }
+    }
+}
+
+impl i2c::WriteRead for SimFram {
+    type Error = SimError;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.delay();
+
+        if address == DEVICE_ID_ADDR {
+            if !self.has_device_id || bytes.first().map(|b| b >> 1) != Some(self.device_addr) {
+                return Err(SimError::Nack);
+            }
+            let id = self.device_id();
+            for (i, b) in buffer.iter_mut().enumerate() {
+                *b = id[i % id.len()];
+            }
+            return Ok(());
+        }
+
+        let (start, n) = self.locate(address, bytes).ok_or(SimError::Nack)?;
+        match self.next_fault() {
+            Some(Fault::Timeout) => return Err(SimError::Timeout),
+            Some(_) => return Err(SimError::Nack),
+            None => {},
+        }
+
+        // bytes after the address are data to the chip, and the read carries on after them
+        let data = &bytes[n..];
+        self.store(start, data)?;
+
+        let size = self.mem.len();
+        let start = start + data.len();
+        for (i, b) in buffer.iter_mut().enumerate() {
+            let addr = (start + i) % size;
+            *b = self.mem[addr] ^ self.flip_mask(addr);
+        }
+        Ok(())
+    }
+}
+
+/// Error reported by [`SimFram`] in place of a bus error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SimError {
+    /// No simulated device answered at that address
+    Nack,
+    /// The simulated bus timed out
+    Timeout,
+    /// The backing file could not be written
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimError::Nack => write!(f, "No acknowledge from simulated device"),
+            SimError::Timeout => write!(f, "Simulated bus timed out"),
+            SimError::Io(kind) => write!(f, "Simulator backing file error: {}", kind),
+        }
+    }
+}
+
+impl Error for SimError {}
+
+#[cfg(feature = "embassy")]
+impl embedded_hal_async::i2c::Error for SimError {
+    fn kind(&self) -> embedded_hal_async::i2c::ErrorKind {
+        use embedded_hal_async::i2c::{ErrorKind, NoAcknowledgeSource};
+        match self {
+            SimError::Nack => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl embedded_hal_async::i2c::ErrorType for SimFram {
+    type Error = SimError;
+}
+
+/// The same device on an async bus, for exercising the async driver
+///
+/// Only a write, or a write followed by a read, is understood; the chip's
+/// current-address reads aren't simulated, so any other sequence of
+/// operations is refused with [`SimError::Nack`].
+#[cfg(feature = "embassy")]
+impl embedded_hal_async::i2c::I2c for SimFram {
+    async fn transaction(&mut self, address: u8, operations: &mut [embedded_hal_async::i2c::Operation<'_>]) -> Result<(), Self::Error> {
+        use embedded_hal_async::i2c::Operation;
+        match operations {
+            [Operation::Write(bytes)] => i2c::Write::write(self, address, bytes),
+            [Operation::Write(bytes), Operation::Read(buffer)] => i2c::WriteRead::write_read(self, address, bytes, buffer),
+            _ => Err(SimError::Nack),
+        }
+    }
+}
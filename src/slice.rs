@@ -0,0 +1,95 @@
+use core::ops::Range;
+
+use crate::{FramDevice, FramAddress, Mb85rcError, ErrorKind, SeekFrom};
+
+/// A window onto part of a device
+///
+/// Offsets are relative to the start of the window and accesses can't reach
+/// outside it, so a component can be handed "just this 1 KB" without being
+/// able to touch anything else. A slice is itself a [`FramDevice`], so slices
+/// can be nested and passed to the storage helpers.
+pub struct FramSlice<'a, D: FramDevice + ?Sized> {
+    dev: &'a mut D,
+    start: u32,
+    cursor: FramAddress,
+}
+
+impl<'a, D: FramDevice + ?Sized> FramSlice<'a, D> {
+    /// Borrow `range` of `dev`, or fail if it doesn't lie inside the device
+    pub fn new(dev: &'a mut D, range: Range<u32>) -> Result<Self, Mb85rcError> {
+        if range.start > range.end || range.end > dev.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+
+        Ok(Self {
+            dev,
+            start: range.start,
+            cursor: FramAddress::new(0, range.end - range.start).unwrap(),
+        })
+    }
+
+    /// Length of the window in bytes
+    pub fn len(&self) -> u32 {
+        self.cursor.capacity()
+    }
+
+    /// Whether the window is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Range of device addresses the window covers
+    pub fn range(&self) -> Range<u32> {
+        self.start..self.start + self.len()
+    }
+
+    fn check_access(&self, offset: u32, len: usize) -> Result<(), Mb85rcError> {
+        match FramAddress::new(offset, self.len()) {
+            Some(a) if a.fits(len) => Ok(()),
+            _ => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    /// Current position of the cursor, relative to the start of the window
+    pub fn position(&self) -> FramAddress {
+        self.cursor
+    }
+
+    /// Move the cursor within the window, returning the new position
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<u32, Mb85rcError> {
+        self.cursor = pos.resolve(self.cursor)?;
+        Ok(self.cursor.get())
+    }
+
+    /// Read bytes at the cursor and advance it, stopping at the end of the window
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        self.read_at(self.cursor.get(), &mut buf[..len])?;
+        self.cursor = self.cursor.checked_add(len as u32).unwrap();
+        Ok(len)
+    }
+
+    /// Write bytes at the cursor and advance it, stopping at the end of the window
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Mb85rcError> {
+        let len = buf.len().min(self.cursor.remaining() as usize);
+        self.write_at(self.cursor.get(), &buf[..len])?;
+        self.cursor = self.cursor.checked_add(len as u32).unwrap();
+        Ok(len)
+    }
+}
+
+impl<D: FramDevice + ?Sized> FramDevice for FramSlice<'_, D> {
+    fn capacity(&self) -> u32 {
+        self.len()
+    }
+
+    fn read_at(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.check_access(addr, buf.len())?;
+        self.dev.read_at(self.start + addr, buf)
+    }
+
+    fn write_at(&mut self, addr: u32, buf: &[u8]) -> Result<(), Mb85rcError> {
+        self.check_access(addr, buf.len())?;
+        self.dev.write_at(self.start + addr, buf)
+    }
+}
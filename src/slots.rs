@@ -0,0 +1,94 @@
+use crate::{FramDevice, FramBitmap, Mb85rcError, ErrorKind};
+
+/// Fixed-size record slots with an occupancy bitmap
+///
+/// The device (usually a [`FramSlice`](crate::FramSlice)) starts with a
+/// [`FramBitmap`] marking which slots are in use, followed by as many
+/// `record_size`-byte slots as fit.
+pub struct SlotAllocator<D: FramDevice> {
+    dev: D,
+    record_size: u32,
+    bitmap: FramBitmap,
+}
+
+impl<D: FramDevice> SlotAllocator<D> {
+    /// Divide `dev` into slots of `record_size` bytes
+    pub fn new(dev: D, record_size: u32) -> Result<Self, Mb85rcError> {
+        if record_size == 0 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        // each slot costs its record plus one bit of bitmap
+        let slots = (dev.capacity() as u64 * 8 / (record_size as u64 * 8 + 1)) as u32;
+        Ok(Self { dev, record_size, bitmap: FramBitmap::new(0, slots) })
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Total number of slots
+    pub fn slots(&self) -> u32 {
+        self.bitmap.bits()
+    }
+
+    /// Number of slots in use
+    pub fn used(&mut self) -> Result<u32, Mb85rcError> {
+        self.bitmap.count_ones(&mut self.dev)
+    }
+
+    fn slot_addr(&self, index: u32) -> u32 {
+        self.bitmap.size() + index * self.record_size
+    }
+
+    fn check_allocated(&mut self, index: u32) -> Result<(), Mb85rcError> {
+        match self.bitmap.test(&mut self.dev, index)? {
+            true => Ok(()),
+            false => Err(Mb85rcError::new(ErrorKind::NotFound)),
+        }
+    }
+
+    /// Claim a free slot, returning its index
+    pub fn alloc(&mut self) -> Result<u32, Mb85rcError> {
+        let index = self.bitmap.find_first_zero(&mut self.dev)?
+            .ok_or(Mb85rcError::new(ErrorKind::Full))?;
+        self.bitmap.set(&mut self.dev, index)?;
+        Ok(index)
+    }
+
+    /// Release slot `index`
+    pub fn free(&mut self, index: u32) -> Result<(), Mb85rcError> {
+        self.check_allocated(index)?;
+        self.bitmap.clear(&mut self.dev, index)
+    }
+
+    /// Whether slot `index` is in use
+    pub fn is_allocated(&mut self, index: u32) -> Result<bool, Mb85rcError> {
+        self.bitmap.test(&mut self.dev, index)
+    }
+
+    /// Read the record in slot `index`, which must be allocated
+    ///
+    /// `buf` may be shorter than the record to read just its start
+    pub fn read(&mut self, index: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        if buf.len() > self.record_size as usize {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        self.check_allocated(index)?;
+        self.dev.read_at(self.slot_addr(index), buf)
+    }
+
+    /// Write the record in slot `index`, which must be allocated
+    pub fn write(&mut self, index: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        if data.len() > self.record_size as usize {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+        self.check_allocated(index)?;
+        self.dev.write_at(self.slot_addr(index), data)
+    }
+
+    /// Free every slot
+    pub fn clear(&mut self) -> Result<(), Mb85rcError> {
+        self.bitmap.clear_all(&mut self.dev)
+    }
+}
@@ -0,0 +1,71 @@
+use core::ops::Range;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Copy `len` bytes from `from` to `to` on the same device, through a small stack buffer
+pub(crate) fn copy_within<D: FramDevice + ?Sized>(dev: &mut D, from: u32, to: u32, len: u32) -> Result<(), Mb85rcError> {
+    let mut buf = [0u8; 64];
+    let mut done = 0;
+    while done < len {
+        let n = (len - done).min(buf.len() as u32);
+        let chunk = &mut buf[..n as usize];
+        dev.read_at(from + done, chunk)?;
+        dev.write_at(to + done, chunk)?;
+        done += n;
+    }
+    Ok(())
+}
+
+/// A saved copy of a region, kept in a scratch area on the same device
+///
+/// Take one before trying out a change, then [`restore`](Self::restore) it if
+/// the change turns out bad or [`discard`](Self::discard) it if not. The copy
+/// lives in FRAM, so it survives a reset while the change is being tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    region: Range<u32>,
+    scratch: u32,
+}
+
+impl Snapshot {
+    /// Copy `region` to the scratch area starting at `scratch`
+    ///
+    /// The scratch area must fit on the device and must not overlap the region
+    pub fn take<D: FramDevice + ?Sized>(dev: &mut D, region: Range<u32>, scratch: u32) -> Result<Self, Mb85rcError> {
+        let len = region.end.checked_sub(region.start).ok_or(Mb85rcError::new(ErrorKind::OutOfRange))?;
+        if region.end > dev.capacity() || scratch as u64 + len as u64 > dev.capacity() as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        if region.start < scratch + len && scratch < region.end {
+            return Err(Mb85rcError::new(ErrorKind::Overlap));
+        }
+
+        copy_within(dev, region.start, scratch, len)?;
+        Ok(Self { region, scratch })
+    }
+
+    /// Rebuild the handle for a snapshot taken earlier (e.g. before a reset)
+    pub fn from_parts(region: Range<u32>, scratch: u32) -> Self {
+        Self { region, scratch }
+    }
+
+    /// The region the snapshot was taken of
+    pub fn region(&self) -> Range<u32> {
+        self.region.clone()
+    }
+
+    /// Where the copy is kept
+    pub fn scratch(&self) -> Range<u32> {
+        self.scratch..self.scratch + self.region.len() as u32
+    }
+
+    /// Put the saved contents back into the region
+    pub fn restore<D: FramDevice + ?Sized>(self, dev: &mut D) -> Result<(), Mb85rcError> {
+        copy_within(dev, self.scratch, self.region.start, self.region.len() as u32)
+    }
+
+    /// Keep the region as it is and forget the snapshot
+    ///
+    /// The scratch area is left untouched and free to reuse
+    pub fn discard(self) {}
+}
@@ -0,0 +1,140 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::crc32;
+
+/// Bytes in one copy of the top pointer: sequence, count, CRC
+const POINTER: u32 = 12;
+/// Records start after both copies of the pointer
+const DATA_START: u32 = 2 * POINTER;
+
+/// Persistent last-in, first-out stack of fixed-size records
+///
+/// The number of records is kept in two alternating, CRC-checked copies at
+/// the start of the device (usually a [`FramSlice`](crate::FramSlice)), so a
+/// reset in the middle of updating it falls back to the previous value. A push
+/// writes the record before moving the pointer and a pop moves the pointer
+/// last, so an interrupted push or pop simply didn't happen.
+pub struct FramStack<D: FramDevice> {
+    dev: D,
+    record_size: u32,
+    seq: u32,
+    count: u32,
+}
+
+impl<D: FramDevice> FramStack<D> {
+    /// Open a stack of `record_size`-byte records on `dev`
+    ///
+    /// A device without a valid pointer opens as an empty stack
+    pub fn new(dev: D, record_size: u32) -> Result<Self, Mb85rcError> {
+        if record_size == 0 || dev.capacity() < DATA_START {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+
+        let mut stack = Self { dev, record_size, seq: 0, count: 0 };
+        let newest = [stack.read_pointer(0)?, stack.read_pointer(1)?]
+            .into_iter()
+            .flatten()
+            .filter(|&(_, count)| count <= stack.capacity())
+            .max_by_key(|&(seq, _)| seq);
+        if let Some((seq, count)) = newest {
+            stack.seq = seq;
+            stack.count = count;
+        }
+        Ok(stack)
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Most records the stack can hold
+    pub fn capacity(&self) -> u32 {
+        (self.dev.capacity() - DATA_START) / self.record_size
+    }
+
+    /// Number of records on the stack
+    pub fn len(&self) -> u32 {
+        self.count
+    }
+
+    /// Whether the stack is empty
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn read_pointer(&mut self, copy: u32) -> Result<Option<(u32, u32)>, Mb85rcError> {
+        let mut raw = [0u8; POINTER as usize];
+        self.dev.read_at(copy * POINTER, &mut raw)?;
+        let crc = u32::from_le_bytes(raw[8..12].try_into().unwrap());
+        if crc32(&raw[..8]) != crc {
+            return Ok(None);
+        }
+        let seq = u32::from_le_bytes(raw[0..4].try_into().unwrap());
+        let count = u32::from_le_bytes(raw[4..8].try_into().unwrap());
+        Ok(Some((seq, count)))
+    }
+
+    fn write_pointer(&mut self, count: u32) -> Result<(), Mb85rcError> {
+        let seq = self.seq.wrapping_add(1);
+        let mut raw = [0u8; POINTER as usize];
+        raw[0..4].copy_from_slice(&seq.to_le_bytes());
+        raw[4..8].copy_from_slice(&count.to_le_bytes());
+        let crc = crc32(&raw[..8]);
+        raw[8..12].copy_from_slice(&crc.to_le_bytes());
+
+        self.dev.write_at((seq % 2) * POINTER, &raw)?;
+        self.seq = seq;
+        self.count = count;
+        Ok(())
+    }
+
+    fn record_addr(&self, index: u32) -> u32 {
+        DATA_START + index * self.record_size
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), Mb85rcError> {
+        match len == self.record_size as usize {
+            true => Ok(()),
+            false => Err(Mb85rcError::new(ErrorKind::BufferTooSmall)),
+        }
+    }
+
+    /// Push a record, which must be exactly `record_size` bytes
+    pub fn push(&mut self, record: &[u8]) -> Result<(), Mb85rcError> {
+        self.check_len(record.len())?;
+        if self.count == self.capacity() {
+            return Err(Mb85rcError::new(ErrorKind::Full));
+        }
+
+        self.dev.write_at(self.record_addr(self.count), record)?;
+        self.write_pointer(self.count + 1)
+    }
+
+    /// Read the top record into `buf` without removing it
+    ///
+    /// Returns `false` if the stack is empty
+    pub fn peek(&mut self, buf: &mut [u8]) -> Result<bool, Mb85rcError> {
+        self.check_len(buf.len())?;
+        if self.count == 0 {
+            return Ok(false);
+        }
+        self.dev.read_at(self.record_addr(self.count - 1), buf)?;
+        Ok(true)
+    }
+
+    /// Remove the top record, reading it into `buf`
+    ///
+    /// Returns `false` if the stack is empty
+    pub fn pop(&mut self, buf: &mut [u8]) -> Result<bool, Mb85rcError> {
+        if !self.peek(buf)? {
+            return Ok(false);
+        }
+        self.write_pointer(self.count - 1)?;
+        Ok(true)
+    }
+
+    /// Remove every record
+    pub fn clear(&mut self) -> Result<(), Mb85rcError> {
+        self.write_pointer(0)
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{Clock, FramDevice, Mb85rcError, ErrorKind};
+use crate::mirror::MirroredBytes;
+
+/// Uptime and usage counters that survive reboots
+///
+/// The values live in RAM and are written to two alternating, CRC-checked
+/// copies at `addr`, so a reset during a flush falls back to the previous
+/// flush. Everything is a `u64` that saturates instead of wrapping. Use the
+/// `N` counters for whatever the application wants to meter, named with
+/// its own index constants. Call [`poll`](Self::poll) regularly to flush
+/// once `interval` ticks have passed, or [`flush`](Self::flush) directly.
+/// The device is passed to each call that touches it.
+#[derive(Debug, Clone)]
+pub struct PersistentStats<const N: usize> {
+    addr: u32,
+    seq: u32,
+    uptime: u64,
+    counters: [u64; N],
+    dirty: bool,
+    last_flush: u32,
+    interval: u32,
+}
+
+impl<const N: usize> PersistentStats<N> {
+    /// The uptime then the counters
+    const RECORD: u32 = 8 + 8 * N as u32;
+    /// Bytes of FRAM the stats take up
+    pub const SIZE: u32 = MirroredBytes::size(Self::RECORD);
+
+    /// Load the stats stored at `addr`, starting from zero if there aren't any
+    pub fn load<D: FramDevice + ?Sized>(dev: &mut D, addr: u32) -> Result<Self, Mb85rcError> {
+        let mut stats = Self { addr, seq: 0, uptime: 0, counters: [0; N], dirty: false, last_flush: 0, interval: 0 };
+        let newest = stats.record().read(dev, (0u64, [0u64; N]), |(uptime, counters), offset, piece| {
+            for (i, b) in piece.iter().enumerate() {
+                let at = offset as usize + i;
+                let word = match at / 8 {
+                    0 => &mut *uptime,
+                    n => &mut counters[n - 1],
+                };
+                *word |= (*b as u64) << (8 * (at % 8));
+            }
+        })?;
+        if let Some((seq, (uptime, counters))) = newest {
+            stats.seq = seq;
+            stats.uptime = uptime;
+            stats.counters = counters;
+        }
+        Ok(stats)
+    }
+
+    fn record(&self) -> MirroredBytes {
+        MirroredBytes::new(self.addr, Self::RECORD)
+    }
+
+    /// Flush at most once every `ticks` from [`poll`](Self::poll)
+    pub fn with_interval(mut self, ticks: u32) -> Self {
+        self.interval = ticks;
+        self
+    }
+
+    /// Accumulated uptime, in whatever unit was passed to [`add_uptime`](Self::add_uptime)
+    pub fn uptime(&self) -> u64 {
+        self.uptime
+    }
+
+    /// Add `ticks` to the uptime
+    pub fn add_uptime(&mut self, ticks: u64) {
+        self.uptime = self.uptime.saturating_add(ticks);
+        self.dirty = true;
+    }
+
+    /// The value of counter `index`
+    ///
+    /// Fails with [`ErrorKind::OutOfRange`] if there's no such counter.
+    pub fn counter(&self, index: usize) -> Result<u64, Mb85rcError> {
+        self.counters.get(index).copied().ok_or(Mb85rcError::new(ErrorKind::OutOfRange))
+    }
+
+    /// Every counter
+    pub fn counters(&self) -> &[u64; N] {
+        &self.counters
+    }
+
+    /// Add `n` to counter `index`
+    pub fn add(&mut self, index: usize, n: u64) -> Result<(), Mb85rcError> {
+        let counter = self.counters.get_mut(index).ok_or(Mb85rcError::new(ErrorKind::OutOfRange))?;
+        *counter = counter.saturating_add(n);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Add one to counter `index`
+    pub fn increment(&mut self, index: usize) -> Result<(), Mb85rcError> {
+        self.add(index, 1)
+    }
+
+    /// Whether anything changed since the last flush
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Flush if anything changed and `interval` ticks have passed since the last flush
+    pub fn poll<D: FramDevice + ?Sized>(&mut self, dev: &mut D, now: u32) -> Result<bool, Mb85rcError> {
+        if !self.dirty || now.wrapping_sub(self.last_flush) < self.interval {
+            return Ok(false);
+        }
+        self.flush(dev)?;
+        self.last_flush = now;
+        Ok(true)
+    }
+
+    /// [`poll`](Self::poll) with the time from `clock`
+    pub fn poll_with<D: FramDevice + ?Sized, K: Clock + ?Sized>(&mut self, dev: &mut D, clock: &mut K) -> Result<bool, Mb85rcError> {
+        self.poll(dev, clock.now())
+    }
+
+    /// Write the stats now
+    pub fn flush<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        let seq = self.seq.wrapping_add(1);
+        self.record().write(dev, seq, |put| {
+            put(&self.uptime.to_le_bytes())?;
+            for counter in &self.counters {
+                put(&counter.to_le_bytes())?;
+            }
+            Ok(())
+        })?;
+
+        self.seq = seq;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Set the uptime and every counter back to zero and flush
+    pub fn reset<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        self.uptime = 0;
+        self.counters = [0; N];
+        self.flush(dev)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// A persistent UTF-8 string of up to `N` bytes
+///
+/// Stored as a little-endian `u16` length followed by `N` bytes of space, so
+/// it always takes [`SIZE`](Self::SIZE) bytes of FRAM. The handle only records
+/// where the string lives; pass the device to each call. Loading checks the
+/// stored length and UTF-8, so a corrupt or never-written string is reported
+/// as [`ErrorKind::InvalidData`] instead of handed back as garbage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FramString<const N: usize> {
+    addr: u32,
+}
+
+impl<const N: usize> FramString<N> {
+    /// Bytes of FRAM the string occupies
+    pub const SIZE: u32 = 2 + N as u32;
+
+    /// A string stored at `addr`
+    pub const fn at(addr: u32) -> Self {
+        Self { addr }
+    }
+
+    /// Where the string is stored
+    pub fn addr(&self) -> u32 {
+        self.addr
+    }
+
+    /// Store `s`, failing with [`ErrorKind::TooLarge`] if it's longer than `N` bytes
+    pub fn set<D: FramDevice + ?Sized>(&self, dev: &mut D, s: &str) -> Result<(), Mb85rcError> {
+        if s.len() > N || N > u16::MAX as usize {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+        dev.write_at(self.addr + 2, s.as_bytes())?;
+        dev.write_at(self.addr, &(s.len() as u16).to_le_bytes())
+    }
+
+    /// Load the string into `buf` and return it
+    pub fn get<'b, D: FramDevice + ?Sized>(&self, dev: &mut D, buf: &'b mut [u8]) -> Result<&'b str, Mb85rcError> {
+        let len = self.len(dev)?;
+        let buf = buf.get_mut(..len).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        dev.read_at(self.addr + 2, buf)?;
+        core::str::from_utf8(buf).map_err(|_| Mb85rcError::new(ErrorKind::InvalidData))
+    }
+
+    /// Length in bytes of the stored string
+    pub fn len<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<usize, Mb85rcError> {
+        let mut raw = [0u8; 2];
+        dev.read_at(self.addr, &mut raw)?;
+        match u16::from_le_bytes(raw) as usize {
+            len if len <= N => Ok(len),
+            _ => Err(Mb85rcError::new(ErrorKind::InvalidData)),
+        }
+    }
+
+    /// Set the string to `""`
+    pub fn clear<D: FramDevice + ?Sized>(&self, dev: &mut D) -> Result<(), Mb85rcError> {
+        dev.write_at(self.addr, &[0, 0])
+    }
+}
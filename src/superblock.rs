@@ -0,0 +1,74 @@
+use crate::{FramDevice, Mb85rcError};
+use crate::crc::crc32;
+
+/// Identifies a superblock, "MB85"
+const MAGIC: u32 = u32::from_le_bytes(*b"MB85");
+/// Version of the superblock layout itself
+const FORMAT_VERSION: u16 = 1;
+/// Bytes a superblock occupies
+pub const SUPERBLOCK_SIZE: u32 = 28;
+
+/// Identity of a formatted device
+///
+/// Stored as a magic number, the superblock format version, the
+/// application's `layout` version, a 16-byte `uuid` and a CRC-32 over the
+/// rest. Nothing else on the device is touched by formatting or mounting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Superblock {
+    /// Application-defined version of whatever layout sits after the superblock
+    pub layout: u16,
+    /// Identifies this particular device, chosen at format time
+    pub uuid: [u8; 16],
+}
+
+/// What [`Superblock::mount`] found on the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountState {
+    /// Every byte was `0x00` or every byte was `0xFF`
+    Blank,
+    /// A valid superblock
+    Formatted(Superblock),
+    /// Something else, either another format or a damaged superblock
+    Unknown,
+}
+
+impl Superblock {
+    /// Write a superblock at `addr`
+    pub fn format<D: FramDevice + ?Sized>(dev: &mut D, addr: u32, layout: u16, uuid: [u8; 16]) -> Result<Self, Mb85rcError> {
+        let sb = Self { layout, uuid };
+        dev.write_at(addr, &sb.to_bytes())?;
+        Ok(sb)
+    }
+
+    /// Check what's at `addr`
+    pub fn mount<D: FramDevice + ?Sized>(dev: &mut D, addr: u32) -> Result<MountState, Mb85rcError> {
+        let mut raw = [0u8; SUPERBLOCK_SIZE as usize];
+        dev.read_at(addr, &mut raw)?;
+
+        if raw.iter().all(|&b| b == 0x00) || raw.iter().all(|&b| b == 0xFF) {
+            return Ok(MountState::Blank);
+        }
+        let magic = u32::from_le_bytes(raw[0..4].try_into().unwrap());
+        let version = u16::from_le_bytes([raw[4], raw[5]]);
+        let crc = u32::from_le_bytes(raw[24..28].try_into().unwrap());
+        if magic != MAGIC || version != FORMAT_VERSION || crc != crc32(&raw[..24]) {
+            return Ok(MountState::Unknown);
+        }
+
+        Ok(MountState::Formatted(Self {
+            layout: u16::from_le_bytes([raw[6], raw[7]]),
+            uuid: raw[8..24].try_into().unwrap(),
+        }))
+    }
+
+    fn to_bytes(self) -> [u8; SUPERBLOCK_SIZE as usize] {
+        let mut raw = [0u8; SUPERBLOCK_SIZE as usize];
+        raw[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        raw[4..6].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        raw[6..8].copy_from_slice(&self.layout.to_le_bytes());
+        raw[8..24].copy_from_slice(&self.uuid);
+        let crc = crc32(&raw[..24]);
+        raw[24..28].copy_from_slice(&crc.to_le_bytes());
+        raw
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const SYSFS_I2C: &str = "/sys/bus/i2c/devices";
+const DT_ALIASES: &str = "/proc/device-tree/aliases";
+const DT_BASE: &str = "/sys/firmware/devicetree/base";
+
+/// An I2C adapter (bus) the Linux kernel knows about
+///
+/// Found through `/sys/bus/i2c`, so boards where the FRAM's bus isn't
+/// `/dev/i2c-1` (other Pi models, BeagleBones, most industrial SBCs) can look
+/// it up by what it is instead of by number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct I2cAdapter {
+    /// The bus number, `N` in `/dev/i2c-N`
+    pub number: u32,
+    /// The name the driver gave the adapter, e.g. `bcm2835 (i2c@7e804000)`
+    pub name: String,
+    /// The adapter's device-tree node path, e.g. `/soc/i2c@7e804000`, on device-tree systems
+    pub of_node: Option<String>,
+}
+
+impl I2cAdapter {
+    /// The character device to open for this adapter
+    pub fn dev_path(&self) -> PathBuf {
+        PathBuf::from(format!("/dev/i2c-{}", self.number))
+    }
+}
+
+/// Every I2C adapter on the system, by bus number
+///
+/// Returns an empty list if the kernel has no I2C support loaded.
+pub fn i2c_adapters() -> io::Result<Vec<I2cAdapter>> {
+    let entries = match fs::read_dir(SYSFS_I2C) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut adapters = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        // devices on the buses show up here as well, as `N-00AA`
+        let Some(number) = path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("i2c-"))
+            .and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+
+        let name = fs::read_to_string(path.join("name"))?.trim().to_string();
+        let of_node = fs::canonicalize(path.join("of_node")).ok()
+            .and_then(|node| node.strip_prefix(DT_BASE).ok().map(dt_path));
+        adapters.push(I2cAdapter { number, name, of_node });
+    }
+    adapters.sort_by_key(|a| a.number);
+    Ok(adapters)
+}
+
+/// The first adapter whose name contains `name`, e.g. `"bcm2835"` or `"i2c@7e804000"`
+pub fn find_i2c_adapter(name: &str) -> io::Result<Option<I2cAdapter>> {
+    Ok(i2c_adapters()?.into_iter().find(|a| a.name.contains(name)))
+}
+
+/// The adapter a device-tree alias like `"i2c1"` points at
+///
+/// Aliases come from the board's device tree, so they stay the same when
+/// the kernel happens to number buses differently, for example because a
+/// HAT added one.
+pub fn find_i2c_alias(alias: &str) -> io::Result<Option<I2cAdapter>> {
+    let target = match fs::read(Path::new(DT_ALIASES).join(alias)) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    // the property is a NUL-terminated string
+    let target = String::from_utf8_lossy(&target).trim_end_matches('\0').to_string();
+    Ok(i2c_adapters()?.into_iter().find(|a| a.of_node.as_deref() == Some(target.as_str())))
+}
+
+/// `/soc/i2c@7e804000` for a path relative to the device-tree base
+fn dt_path(relative: &Path) -> String {
+    format!("/{}", relative.display())
+}
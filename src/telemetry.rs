@@ -0,0 +1,251 @@
+//! Telemetry snapshots: a diagnostics report and chosen regions in one framed blob
+//!
+//! Firmware calls [`encode`] and sends the blob over whatever link it has
+//! (UART, radio, an MQTT payload); the host calls [`decode`] to get the
+//! report and the region contents back. That way the state of an FRAM in the
+//! field can be looked at without anyone going near the board.
+//!
+//! The blob starts with the magic `FRTM` and a version byte, then the
+//! diagnostics, the number of regions, each region as its address, length
+//! and contents, and finally a CRC-32 of everything before it. Numbers are
+//! little-endian.
+
+use core::ops::Range;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind, Diagnostics, DeviceId, Health};
+use crate::crc::crc32;
+
+const MAGIC: &[u8; 4] = b"FRTM";
+
+/// The blob format version [`encode`] writes and [`decode`] accepts
+pub const VERSION: u8 = 1;
+
+const DIAGNOSTICS_SIZE: usize = 30;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + DIAGNOSTICS_SIZE + 2;
+const REGION_HEADER_SIZE: usize = 8;
+const CRC_SIZE: usize = 4;
+
+/// Size of the blob [`encode`] makes for `regions`
+pub fn encoded_len(regions: &[Range<u32>]) -> usize {
+    HEADER_SIZE + CRC_SIZE + regions.iter()
+        .map(|r| REGION_HEADER_SIZE + r.end.saturating_sub(r.start) as usize)
+        .sum::<usize>()
+}
+
+/// Write `diagnostics` and the contents of `regions` of `dev` into `out` as one blob, returning its size
+///
+/// Fails with [`ErrorKind::BufferTooSmall`] if `out` is shorter than
+/// [`encoded_len`], or [`ErrorKind::TooLarge`] for more than 65535 regions.
+pub fn encode<D: FramDevice + ?Sized>(dev: &mut D, diagnostics: &Diagnostics, regions: &[Range<u32>], out: &mut [u8]) -> Result<usize, Mb85rcError> {
+    if regions.iter().any(|r| r.start > r.end || r.end > dev.capacity()) {
+        return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+    }
+    let count = u16::try_from(regions.len()).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+    let len = encoded_len(regions);
+    if out.len() < len {
+        return Err(Mb85rcError::new(ErrorKind::BufferTooSmall));
+    }
+
+    out[..4].copy_from_slice(MAGIC);
+    out[4] = VERSION;
+    encode_diagnostics(diagnostics, &mut out[5..5 + DIAGNOSTICS_SIZE]);
+    out[HEADER_SIZE - 2..HEADER_SIZE].copy_from_slice(&count.to_le_bytes());
+
+    let mut at = HEADER_SIZE;
+    for region in regions {
+        let n = (region.end - region.start) as usize;
+        out[at..at + 4].copy_from_slice(&region.start.to_le_bytes());
+        out[at + 4..at + 8].copy_from_slice(&(n as u32).to_le_bytes());
+        at += REGION_HEADER_SIZE;
+        dev.read_at(region.start, &mut out[at..at + n])?;
+        at += n;
+    }
+
+    let crc = crc32(&out[..at]);
+    out[at..at + CRC_SIZE].copy_from_slice(&crc.to_le_bytes());
+    Ok(len)
+}
+
+/// A decoded telemetry blob, borrowing the region contents from it
+#[derive(Debug, Clone, Copy)]
+pub struct Telemetry<'a> {
+    /// The report the blob was made with
+    pub diagnostics: Diagnostics,
+    count: u16,
+    regions: &'a [u8],
+}
+
+impl<'a> Telemetry<'a> {
+    /// Number of regions in the blob
+    pub fn region_count(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Each region's address and contents, in the order they were encoded
+    pub fn regions(&self) -> Regions<'a> {
+        Regions { rest: self.regions }
+    }
+}
+
+/// Iterator over the regions of a [`Telemetry`] blob
+#[derive(Debug, Clone)]
+pub struct Regions<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Regions<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // decode() has already checked the lengths add up
+        let (header, rest) = self.rest.split_at_checked(REGION_HEADER_SIZE)?;
+        let addr = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        let (data, rest) = rest.split_at(len);
+        self.rest = rest;
+        Some((addr, data))
+    }
+}
+
+/// Check and unpack a blob made by [`encode`]
+///
+/// Fails with [`ErrorKind::InvalidData`] if the blob is truncated, corrupt,
+/// or from a different format version.
+pub fn decode(blob: &[u8]) -> Result<Telemetry<'_>, Mb85rcError> {
+    let invalid = || Mb85rcError::new(ErrorKind::InvalidData);
+    if blob.len() < HEADER_SIZE + CRC_SIZE || &blob[..4] != MAGIC || blob[4] != VERSION {
+        return Err(invalid());
+    }
+    let (body, crc) = blob.split_at(blob.len() - CRC_SIZE);
+    if crc32(body) != u32::from_le_bytes(crc.try_into().unwrap()) {
+        return Err(invalid());
+    }
+
+    let diagnostics = decode_diagnostics(&body[5..5 + DIAGNOSTICS_SIZE]).ok_or_else(invalid)?;
+    let count = u16::from_le_bytes([body[HEADER_SIZE - 2], body[HEADER_SIZE - 1]]);
+    let regions = &body[HEADER_SIZE..];
+
+    // walk the regions once so the iterator can't run off the end
+    let mut rest = regions;
+    for _ in 0..count {
+        let header = rest.get(..REGION_HEADER_SIZE).ok_or_else(invalid)?;
+        let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        rest = rest.get(REGION_HEADER_SIZE..)
+            .and_then(|r| r.get(len..))
+            .ok_or_else(invalid)?;
+    }
+    if !rest.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(Telemetry { diagnostics, count, regions })
+}
+
+fn encode_diagnostics(d: &Diagnostics, out: &mut [u8]) {
+    let id = d.device_id.map(|id| id.bytes());
+    out[0] = id.is_some() as u8;
+    out[1..4].copy_from_slice(&id.unwrap_or_default());
+    out[4] = d.address;
+    out[5..9].copy_from_slice(&d.size.to_le_bytes());
+    out[9..13].copy_from_slice(&(d.max_transfer as u32).to_le_bytes());
+    out[13] = d.arbitration_retries;
+    out[14] = match d.health {
+        None => 0,
+        Some(Health::Healthy) => 1,
+        Some(Health::Degraded) => 2,
+        Some(Health::Failed) => 3,
+    };
+    out[15..19].copy_from_slice(&d.consecutive_failures.to_le_bytes());
+    out[19..23].copy_from_slice(&d.failures.to_le_bytes());
+    out[23..27].copy_from_slice(&d.crc_errors.to_le_bytes());
+    let (code, detail) = d.last_error.map_or((0, 0), encode_kind);
+    out[27] = code;
+    out[28..30].copy_from_slice(&detail.to_le_bytes());
+}
+
+fn decode_diagnostics(b: &[u8]) -> Option<Diagnostics> {
+    let u32_at = |at: usize| u32::from_le_bytes(b[at..at + 4].try_into().unwrap());
+    Some(Diagnostics {
+        device_id: match b[0] {
+            0 => None,
+            1 => Some(DeviceId::from_bytes([b[1], b[2], b[3]])),
+            _ => return None,
+        },
+        address: b[4],
+        size: u32_at(5),
+        max_transfer: u32_at(9) as usize,
+        arbitration_retries: b[13],
+        health: match b[14] {
+            0 => None,
+            1 => Some(Health::Healthy),
+            2 => Some(Health::Degraded),
+            3 => Some(Health::Failed),
+            _ => return None,
+        },
+        consecutive_failures: u32_at(15),
+        failures: u32_at(19),
+        crc_errors: u32_at(23),
+        last_error: match b[27] {
+            0 => None,
+            code => Some(decode_kind(code, u16::from_le_bytes([b[28], b[29]]))?),
+        },
+    })
+}
+
+/// Wire code and detail value for an error kind; 0 means no error
+fn encode_kind(kind: ErrorKind) -> (u8, u16) {
+    match kind {
+        ErrorKind::I2c => (1, 0),
+        ErrorKind::InvalidAddress => (2, 0),
+        ErrorKind::OutOfRange => (3, 0),
+        ErrorKind::InvalidSeek => (4, 0),
+        ErrorKind::UnknownSize => (5, 0),
+        ErrorKind::AddressNotFound { candidates } => (6, candidates as u16),
+        ErrorKind::NoDeviceId => (7, 0),
+        ErrorKind::WrongDevice { found } => (8, found),
+        ErrorKind::BatchFull => (9, 0),
+        ErrorKind::BufferTooSmall => (10, 0),
+        ErrorKind::RolledBack => (11, 0),
+        ErrorKind::RollbackFailed => (12, 0),
+        ErrorKind::Overlap => (13, 0),
+        ErrorKind::NotFound => (14, 0),
+        ErrorKind::TooLarge => (15, 0),
+        ErrorKind::InvalidData => (16, 0),
+        ErrorKind::Full => (17, 0),
+        ErrorKind::WrongState => (18, 0),
+        ErrorKind::AuthFailed => (19, 0),
+        ErrorKind::Locked => (20, 0),
+        ErrorKind::Format => (21, 0),
+        ErrorKind::Failed => (22, 0),
+        ErrorKind::Pin => (23, 0),
+    }
+}
+
+fn decode_kind(code: u8, detail: u16) -> Option<ErrorKind> {
+    Some(match code {
+        1 => ErrorKind::I2c,
+        2 => ErrorKind::InvalidAddress,
+        3 => ErrorKind::OutOfRange,
+        4 => ErrorKind::InvalidSeek,
+        5 => ErrorKind::UnknownSize,
+        6 => ErrorKind::AddressNotFound { candidates: detail as u8 },
+        7 => ErrorKind::NoDeviceId,
+        8 => ErrorKind::WrongDevice { found: detail },
+        9 => ErrorKind::BatchFull,
+        10 => ErrorKind::BufferTooSmall,
+        11 => ErrorKind::RolledBack,
+        12 => ErrorKind::RollbackFailed,
+        13 => ErrorKind::Overlap,
+        14 => ErrorKind::NotFound,
+        15 => ErrorKind::TooLarge,
+        16 => ErrorKind::InvalidData,
+        17 => ErrorKind::Full,
+        18 => ErrorKind::WrongState,
+        19 => ErrorKind::AuthFailed,
+        20 => ErrorKind::Locked,
+        21 => ErrorKind::Format,
+        22 => ErrorKind::Failed,
+        23 => ErrorKind::Pin,
+        _ => return None,
+    })
+}
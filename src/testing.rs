@@ -0,0 +1,154 @@
+//! Helpers for testing code that stores data in FRAM
+//!
+//! Everything here runs against a [`SimFram`], so downstream crates can write
+//! storage tests that go through the real driver without hardware. The
+//! fixtures set up the storage types already holding known contents, which
+//! the matching `fixture_*` functions regenerate for comparison.
+
+use crate::{MB85RC, Builder, SimFram, FramDevice, crc::Crc32};
+use crate::{TlvStore, ConfigHistory, SampleLog, AuditLog, AuditRecord};
+
+/// A driver attached to a fresh, zeroed simulated device of `size` bytes
+pub fn sim_fram(size: u32) -> MB85RC<SimFram> {
+    Builder::new().with_size(size).connect_i2c(SimFram::new(size))
+}
+
+/// A driver attached to a simulated device preloaded with `image`
+pub fn sim_fram_with_image(image: &[u8]) -> MB85RC<SimFram> {
+    let mut sim = SimFram::new(image.len() as u32);
+    sim.memory_mut().copy_from_slice(image);
+    Builder::new().with_size(image.len() as u32).connect_i2c(sim)
+}
+
+/// A deterministic pseudo-random image of `size` bytes
+///
+/// The same `seed` always gives the same bytes, so expected contents can be
+/// regenerated instead of checked in.
+pub fn golden_image(size: usize, seed: u32) -> Vec<u8> {
+    // xorshift32, which never leaves zero, so nudge the seed off it
+    let mut state = seed | 1;
+    (0..size).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    }).collect()
+}
+
+fn read_region<D: FramDevice + ?Sized>(fram: &mut D, addr: u32, len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    fram.read_at(addr, &mut buf).expect("could not read region");
+    buf
+}
+
+/// CRC-32 of `len` bytes starting at `addr`
+pub fn region_crc<D: FramDevice + ?Sized>(fram: &mut D, addr: u32, len: usize) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(&read_region(fram, addr, len));
+    crc.finish()
+}
+
+/// Assert the device holds `expected` starting at `addr`, reporting the first differing byte
+#[track_caller]
+pub fn assert_region_eq<D: FramDevice + ?Sized>(fram: &mut D, addr: u32, expected: &[u8]) {
+    let actual = read_region(fram, addr, expected.len());
+    if let Some(i) = actual.iter().zip(expected).position(|(a, e)| a != e) {
+        panic!(
+            "region mismatch at 0x{:04X}: found 0x{:02X}, expected 0x{:02X}",
+            addr as usize + i, actual[i], expected[i],
+        );
+    }
+}
+
+/// Assert `len` bytes at `addr` have the CRC-32 `expected`
+#[track_caller]
+pub fn assert_region_crc<D: FramDevice + ?Sized>(fram: &mut D, addr: u32, len: usize, expected: u32) {
+    let actual = region_crc(fram, addr, len);
+    assert_eq!(
+        actual, expected,
+        "CRC of 0x{:04X}..0x{:04X} is 0x{:08X}, expected 0x{:08X}",
+        addr, addr as usize + len, actual, expected,
+    );
+}
+
+/// Assert the whole device matches `image`
+#[track_caller]
+pub fn assert_image_eq<D: FramDevice + ?Sized>(fram: &mut D, image: &[u8]) {
+    assert_eq!(fram.capacity() as usize, image.len(), "image size doesn't match the device");
+    assert_region_eq(fram, 0, image);
+}
+
+/// A [`TlvStore`] over a fresh device of `size` bytes, holding `records` in order
+///
+/// # Panics
+///
+/// Panics if the records don't fit
+pub fn tlv_store(size: u32, records: &[(u16, &[u8])]) -> TlvStore<MB85RC<SimFram>> {
+    let mut store = TlvStore::new(sim_fram(size)).expect("could not open TLV store");
+    for (tag, value) in records {
+        store.append(*tag, value).expect("could not append TLV record");
+    }
+    store
+}
+
+/// A [`ConfigHistory`] of `slots` slots over a fresh device of `size` bytes, with `versions` saved in order
+///
+/// Version `n` is saved with timestamp `n`, so it ends up with sequence
+/// number `n` too.
+///
+/// # Panics
+///
+/// Panics if the slots are too small for the versions
+pub fn config_history(size: u32, slots: u32, versions: &[&[u8]]) -> ConfigHistory<MB85RC<SimFram>> {
+    let mut history = ConfigHistory::new(sim_fram(size), slots).expect("could not open config history");
+    for (timestamp, data) in (0..).zip(versions) {
+        history.save(data, timestamp).expect("could not save config version");
+    }
+    history
+}
+
+/// The contents [`sample_log`] gives sample `n`
+pub fn fixture_sample<const R: usize>(n: u32) -> [u8; R] {
+    let seed = n.to_le_bytes();
+    core::array::from_fn(|i| seed[i % 4] ^ i as u8)
+}
+
+/// A fresh device of `size` bytes with a [`SampleLog`] over all of it, `count` samples appended
+///
+/// Sample `n` holds [`fixture_sample`]`(n)`. With more samples than fit, the
+/// oldest have been overwritten, as they would be in the field.
+///
+/// # Panics
+///
+/// Panics if not even one sample fits
+pub fn sample_log<const R: usize>(size: u32, count: u32) -> (MB85RC<SimFram>, SampleLog<R>) {
+    let mut fram = sim_fram(size);
+    let mut log = SampleLog::load(&mut fram, 0..size).expect("could not open sample log");
+    for n in 0..count {
+        log.append(&mut fram, &fixture_sample(n)).expect("could not append sample");
+    }
+    (fram, log)
+}
+
+/// The record [`audit_log`] gives sequence number `seq`
+pub fn fixture_audit_record(seq: u32) -> AuditRecord {
+    AuditRecord { seq, addr: seq.wrapping_mul(16), len: 16, time: seq.wrapping_mul(1000), tag: seq ^ 0xA5A5_A5A5 }
+}
+
+/// A fresh device of `size` bytes with an [`AuditLog`] over all of it, `count` writes recorded
+///
+/// Record `n` is [`fixture_audit_record`]`(n)`. With more records than
+/// fit, the oldest have been overwritten.
+///
+/// # Panics
+///
+/// Panics if not even one record fits
+pub fn audit_log(size: u32, count: u32) -> (MB85RC<SimFram>, AuditLog) {
+    let mut fram = sim_fram(size);
+    let mut log = AuditLog::load(&mut fram, 0..size).expect("could not open audit log");
+    for seq in 0..count {
+        let r = fixture_audit_record(seq);
+        log.record(&mut fram, r.addr, r.len, r.time, r.tag).expect("could not record write");
+    }
+    (fram, log)
+}
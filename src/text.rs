@@ -0,0 +1,140 @@
+//! Hex and base64 text export and import of device contents
+//!
+//! Handy for pasting FRAM contents into bug reports, moving them over a UART
+//! console or embedding them in JSON, where raw binary doesn't survive.
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind, MAX_TRANSFER};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Text encoding for [`export`] and [`import`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Two lowercase hex digits per byte
+    Hex,
+    /// Standard base64 with `=` padding
+    Base64,
+}
+
+/// Write `range` of `dev` to `out` as text, without any line breaks
+pub fn export<D, W>(dev: &mut D, range: Range<u32>, encoding: Encoding, out: &mut W) -> Result<(), Mb85rcError>
+where
+    D: FramDevice + ?Sized,
+    W: fmt::Write + ?Sized,
+{
+    if range.start > range.end || range.end > dev.capacity() {
+        return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+    }
+
+    // a multiple of 3, so base64 groups never straddle two chunks
+    const CHUNK: usize = MAX_TRANSFER / 3 * 3;
+    let mut buf = [0u8; CHUNK];
+    for addr in range.clone().step_by(CHUNK) {
+        let chunk = &mut buf[..(range.end - addr).min(CHUNK as u32) as usize];
+        dev.read_at(addr, chunk)?;
+        match encoding {
+            Encoding::Hex => write_hex(chunk, out),
+            Encoding::Base64 => write_base64(chunk, out),
+        }
+        .map_err(|_| Mb85rcError::new(ErrorKind::Format))?;
+    }
+    Ok(())
+}
+
+fn write_hex<W: fmt::Write + ?Sized>(bytes: &[u8], out: &mut W) -> fmt::Result {
+    for b in bytes {
+        write!(out, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+fn write_base64<W: fmt::Write + ?Sized>(bytes: &[u8], out: &mut W) -> fmt::Result {
+    for group in bytes.chunks(3) {
+        let mut raw = [0u8; 3];
+        raw[..group.len()].copy_from_slice(group);
+        let bits = u32::from_be_bytes([0, raw[0], raw[1], raw[2]]);
+        for i in 0..4 {
+            match i <= group.len() {
+                true => out.write_char(BASE64[(bits >> (18 - 6 * i) & 0x3F) as usize] as char)?,
+                false => out.write_char('=')?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decode `text` and write it to `dev` starting at `addr`, returning the number of bytes written
+///
+/// Whitespace anywhere in the text is ignored, so wrapped or indented text
+/// can be passed straight in. The whole text is checked before anything is
+/// written; malformed text fails with [`ErrorKind::InvalidData`] and leaves
+/// the device untouched.
+pub fn import<D: FramDevice + ?Sized>(dev: &mut D, addr: u32, encoding: Encoding, text: &str) -> Result<u32, Mb85rcError> {
+    let len = decode(encoding, text, |_| Ok(()))?;
+    if addr as u64 + len as u64 > dev.capacity() as u64 {
+        return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+    }
+
+    let mut buf = [0u8; MAX_TRANSFER];
+    let (mut filled, mut pos) = (0, addr);
+    decode(encoding, text, |b| {
+        buf[filled] = b;
+        filled += 1;
+        if filled == MAX_TRANSFER {
+            dev.write_at(pos, &buf)?;
+            pos += filled as u32;
+            filled = 0;
+        }
+        Ok(())
+    })?;
+    dev.write_at(pos, &buf[..filled])?;
+    Ok(len)
+}
+
+/// Pass each decoded byte of `text` to `emit`, returning how many there were
+fn decode(encoding: Encoding, text: &str, mut emit: impl FnMut(u8) -> Result<(), Mb85rcError>) -> Result<u32, Mb85rcError> {
+    let invalid = || Mb85rcError::new(ErrorKind::InvalidData);
+    let (bits_per_char, mut padding) = match encoding {
+        Encoding::Hex => (4, None),
+        Encoding::Base64 => (6, Some(0)),
+    };
+
+    let (mut acc, mut bits, mut chars, mut len) = (0u32, 0, 0u32, 0u32);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' && padding.is_some() {
+            padding = padding.map(|p| p + 1);
+            chars += 1;
+            continue;
+        }
+        if padding.is_some_and(|p| p > 0) {
+            // nothing but padding may follow padding
+            return Err(invalid());
+        }
+
+        let value = match encoding {
+            Encoding::Hex => (c as char).to_digit(16).ok_or_else(invalid)?,
+            Encoding::Base64 => BASE64.iter().position(|&b| b == c).ok_or_else(invalid)? as u32,
+        };
+        acc = (acc << bits_per_char) | value;
+        bits += bits_per_char;
+        chars += 1;
+        if bits >= 8 {
+            bits -= 8;
+            emit((acc >> bits) as u8)?;
+            acc &= (1 << bits) - 1;
+            len += 1;
+        }
+    }
+
+    let complete = match encoding {
+        Encoding::Hex => chars % 2 == 0,
+        Encoding::Base64 => chars % 4 == 0 && padding.unwrap_or(0) <= 2,
+    };
+    match complete {
+        true => Ok(len),
+        false => Err(invalid()),
+    }
+}
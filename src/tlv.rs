@@ -0,0 +1,204 @@
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+use crate::crc::{self, Crc32};
+
+/// Bytes of tag and length in front of each record's value
+const HEADER: u32 = 4;
+/// Bytes of CRC after each record's value
+const TRAILER: u32 = 4;
+
+/// Where one record lives in a [`TlvStore`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlvRecord {
+    /// The record's tag
+    pub tag: u16,
+    /// Length of the value in bytes
+    pub len: u16,
+    /// Offset of the record within the store
+    pub offset: u32,
+}
+
+impl TlvRecord {
+    fn size(&self) -> u32 {
+        HEADER + self.len as u32 + TRAILER
+    }
+}
+
+/// Append-only tag-length-value record area
+///
+/// Each record is a 16-bit tag, a 16-bit length, the value and a CRC-32 over
+/// all three. Records are packed one after another from the start of the
+/// device (usually a [`FramSlice`](crate::FramSlice)), and the first record
+/// that fails its CRC marks the end. Appending the same tag again supersedes
+/// the earlier record for [`find`](Self::find), which makes this a good fit
+/// for manufacturing data such as MAC addresses, serials and calibration.
+pub struct TlvStore<D: FramDevice> {
+    dev: D,
+    end: u32,
+}
+
+impl<D: FramDevice> TlvStore<D> {
+    /// Open the record area on `dev`, scanning to find where the records end
+    pub fn new(dev: D) -> Result<Self, Mb85rcError> {
+        let mut store = Self { dev, end: 0 };
+        while let Some(record) = store.record_at(store.end)? {
+            store.end += record.size();
+        }
+        Ok(store)
+    }
+
+    /// Give back the device
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Bytes taken up by records
+    pub fn used(&self) -> u32 {
+        self.end
+    }
+
+    /// Bytes left for new records, including their overhead
+    pub fn free(&self) -> u32 {
+        self.dev.capacity() - self.end
+    }
+
+    /// Read and check the record at `offset`, `None` if there isn't a valid one
+    fn record_at(&mut self, offset: u32) -> Result<Option<TlvRecord>, Mb85rcError> {
+        if offset as u64 + (HEADER + TRAILER) as u64 > self.dev.capacity() as u64 {
+            return Ok(None);
+        }
+
+        let mut header = [0u8; HEADER as usize];
+        self.dev.read_at(offset, &mut header)?;
+        let tag = u16::from_le_bytes([header[0], header[1]]);
+        let len = u16::from_le_bytes([header[2], header[3]]);
+        let record = TlvRecord { tag, len, offset };
+        if offset as u64 + record.size() as u64 > self.dev.capacity() as u64 {
+            return Ok(None);
+        }
+
+        let mut crc = Crc32::new();
+        crc.update(&header);
+        crc::update_from_device(&mut crc, &mut self.dev, offset + HEADER, len as u32)?;
+        let mut stored = [0u8; TRAILER as usize];
+        self.dev.read_at(offset + HEADER + len as u32, &mut stored)?;
+
+        Ok((crc.finish() == u32::from_le_bytes(stored)).then_some(record))
+    }
+
+    /// Add a record, returning where it was stored
+    pub fn append(&mut self, tag: u16, value: &[u8]) -> Result<TlvRecord, Mb85rcError> {
+        self.append_parts(tag, &[value])
+    }
+
+    /// Add a record whose value is `parts` joined together
+    pub(crate) fn append_parts(&mut self, tag: u16, parts: &[&[u8]]) -> Result<TlvRecord, Mb85rcError> {
+        let len = parts.iter().map(|p| p.len()).sum::<usize>();
+        let len = u16::try_from(len).map_err(|_| Mb85rcError::new(ErrorKind::TooLarge))?;
+        let record = TlvRecord { tag, len, offset: self.end };
+        if record.size() > self.free() {
+            return Err(Mb85rcError::new(ErrorKind::TooLarge));
+        }
+
+        // clear the space after the new record first, so stale records left
+        // over from before a clear() can never follow it
+        let after = self.end + record.size();
+        let blank = (self.dev.capacity() - after).min(HEADER + TRAILER) as usize;
+        self.dev.write_at(after, &[0u8; (HEADER + TRAILER) as usize][..blank])?;
+
+        let mut header = [0u8; HEADER as usize];
+        header[..2].copy_from_slice(&tag.to_le_bytes());
+        header[2..].copy_from_slice(&len.to_le_bytes());
+        let mut crc = Crc32::new();
+        crc.update(&header);
+
+        self.dev.write_at(self.end, &header)?;
+        let mut pos = self.end + HEADER;
+        for part in parts {
+            crc.update(part);
+            self.dev.write_at(pos, part)?;
+            pos += part.len() as u32;
+        }
+        self.dev.write_at(pos, &crc.finish().to_le_bytes())?;
+
+        self.end = after;
+        Ok(record)
+    }
+
+    /// Iterate over every record, oldest first
+    pub fn records(&mut self) -> TlvRecords<'_, D> {
+        TlvRecords { store: self, offset: 0 }
+    }
+
+    /// The newest record with `tag`
+    pub fn find(&mut self, tag: u16) -> Result<Option<TlvRecord>, Mb85rcError> {
+        let mut found = None;
+        for record in self.records() {
+            let record = record?;
+            if record.tag == tag {
+                found = Some(record);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Read a record's value into `buf`, returning its length
+    pub fn read_value(&mut self, record: &TlvRecord, buf: &mut [u8]) -> Result<usize, Mb85rcError> {
+        let buf = buf.get_mut(..record.len as usize).ok_or(Mb85rcError::new(ErrorKind::BufferTooSmall))?;
+        self.read_part(record, 0, buf)?;
+        Ok(buf.len())
+    }
+
+    /// Read `buf.len()` bytes of a record's value, starting `offset` bytes in
+    pub(crate) fn read_part(&mut self, record: &TlvRecord, offset: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        if offset as u64 + buf.len() as u64 > record.len as u64 {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        self.dev.read_at(record.offset + HEADER + offset, buf)
+    }
+
+    /// Find the newest record with `tag` and read its value into `buf`
+    pub fn get(&mut self, tag: u16, buf: &mut [u8]) -> Result<Option<usize>, Mb85rcError> {
+        match self.find(tag)? {
+            Some(record) => self.read_value(&record, buf).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove every record
+    pub fn clear(&mut self) -> Result<(), Mb85rcError> {
+        let blank = self.dev.capacity().min(HEADER + TRAILER) as usize;
+        self.dev.write_at(0, &[0u8; (HEADER + TRAILER) as usize][..blank])?;
+        self.end = 0;
+        Ok(())
+    }
+}
+
+/// Iterator over the records in a [`TlvStore`], from [`TlvStore::records`]
+pub struct TlvRecords<'a, D: FramDevice> {
+    store: &'a mut TlvStore<D>,
+    offset: u32,
+}
+
+impl<D: FramDevice> Iterator for TlvRecords<'_, D> {
+    type Item = Result<TlvRecord, Mb85rcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.store.end {
+            return None;
+        }
+        match self.store.record_at(self.offset) {
+            Ok(Some(record)) => {
+                self.offset += record.size();
+                Some(Ok(record))
+            },
+            Ok(None) => {
+                self.offset = self.store.end;
+                Some(Err(Mb85rcError::new(ErrorKind::InvalidData)))
+            },
+            Err(e) => {
+                self.offset = self.store.end;
+                Some(Err(e))
+            },
+        }
+    }
+}
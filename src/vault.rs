@@ -0,0 +1,113 @@
+use core::ops::Range;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{FramDevice, Mb85rcError, ErrorKind};
+
+/// Bytes of secret verifier at the start of the vault
+const VERIFIER: u32 = 32;
+
+/// A region for credentials that stays shut until unlocked
+///
+/// The region starts with a verifier derived from a secret (an HMAC-SHA256
+/// of the vault's address keyed with the secret, so the secret itself is
+/// never stored). Reads and writes through the handle fail with
+/// [`ErrorKind::Locked`] until [`unlock`](Self::unlock) is given the right
+/// secret, and [`lock`](Self::lock) shuts it again. This keeps credentials
+/// away from code that has no business with them; it doesn't encrypt
+/// anything, so pair it with [`AuthRegion`](crate::AuthRegion) or
+/// encryption if the chip itself is exposed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyVault {
+    region: Range<u32>,
+    unlocked: bool,
+}
+
+impl KeyVault {
+    /// A locked handle to the vault in `region`
+    pub fn new(region: Range<u32>) -> Result<Self, Mb85rcError> {
+        if region.end < region.start || region.end - region.start <= VERIFIER {
+            return Err(Mb85rcError::new(ErrorKind::OutOfRange));
+        }
+        Ok(Self { region, unlocked: false })
+    }
+
+    /// Bytes the vault holds, not counting the verifier
+    pub fn capacity(&self) -> u32 {
+        self.region.end - self.region.start - VERIFIER
+    }
+
+    fn verifier(&self, secret: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(b"mb85rc key vault");
+        mac.update(&self.region.start.to_le_bytes());
+        mac
+    }
+
+    /// Set up the vault with `secret`, clearing its contents, and leave it unlocked
+    pub fn format<D: FramDevice + ?Sized>(&mut self, dev: &mut D, secret: &[u8]) -> Result<(), Mb85rcError> {
+        let start = self.region.start + VERIFIER;
+        let mut done = 0;
+        while done < self.capacity() {
+            let n = (self.capacity() - done).min(64);
+            dev.write_at(start + done, &[0u8; 64][..n as usize])?;
+            done += n;
+        }
+        dev.write_at(self.region.start, &self.verifier(secret).finalize().into_bytes())?;
+        self.unlocked = true;
+        Ok(())
+    }
+
+    /// Unlock the vault, failing with [`ErrorKind::AuthFailed`] if `secret` is wrong
+    pub fn unlock<D: FramDevice + ?Sized>(&mut self, dev: &mut D, secret: &[u8]) -> Result<(), Mb85rcError> {
+        let mut stored = [0u8; VERIFIER as usize];
+        dev.read_at(self.region.start, &mut stored)?;
+
+        // constant-time comparison
+        match self.verifier(secret).verify_slice(&stored) {
+            Ok(()) => {
+                self.unlocked = true;
+                Ok(())
+            },
+            Err(_) => Err(Mb85rcError::new(ErrorKind::AuthFailed)),
+        }
+    }
+
+    /// Lock the vault again
+    pub fn lock(&mut self) {
+        self.unlocked = false;
+    }
+
+    /// Whether the vault is locked
+    pub fn is_locked(&self) -> bool {
+        !self.unlocked
+    }
+
+    /// Replace the secret, keeping the contents; the vault must be unlocked
+    pub fn change_secret<D: FramDevice + ?Sized>(&mut self, dev: &mut D, secret: &[u8]) -> Result<(), Mb85rcError> {
+        self.check_access(0, 0)?;
+        dev.write_at(self.region.start, &self.verifier(secret).finalize().into_bytes())
+    }
+
+    fn check_access(&self, offset: u32, len: usize) -> Result<(), Mb85rcError> {
+        if !self.unlocked {
+            return Err(Mb85rcError::new(ErrorKind::Locked));
+        }
+        match offset as u64 + len as u64 <= self.capacity() as u64 {
+            true => Ok(()),
+            false => Err(Mb85rcError::new(ErrorKind::OutOfRange)),
+        }
+    }
+
+    /// Read `buf.len()` bytes at `offset` into the vault
+    pub fn read<D: FramDevice + ?Sized>(&self, dev: &mut D, offset: u32, buf: &mut [u8]) -> Result<(), Mb85rcError> {
+        self.check_access(offset, buf.len())?;
+        dev.read_at(self.region.start + VERIFIER + offset, buf)
+    }
+
+    /// Write `data` at `offset` into the vault
+    pub fn write<D: FramDevice + ?Sized>(&self, dev: &mut D, offset: u32, data: &[u8]) -> Result<(), Mb85rcError> {
+        self.check_access(offset, data.len())?;
+        dev.write_at(self.region.start + VERIFIER + offset, data)
+    }
+}
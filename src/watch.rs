@@ -0,0 +1,56 @@
+use core::ops::Range;
+use crate::{FramDevice, Mb85rcError};
+use crate::crc::{self, Crc32};
+
+/// Spots changes made to regions by someone else
+///
+/// Keeps a CRC-32 of each of `N` regions and recomputes them on every
+/// [`poll`](Self::poll), reporting which ones differ from last time. Useful
+/// when another MCU or a service tool shares the FRAM. The first poll (or
+/// [`snapshot`](Self::snapshot)) only records a baseline.
+#[derive(Debug, Clone)]
+pub struct Watcher<const N: usize> {
+    regions: [Range<u32>; N],
+    crcs: [Option<u32>; N],
+}
+
+impl<const N: usize> Watcher<N> {
+    /// Watch `regions`
+    pub fn new(regions: [Range<u32>; N]) -> Self {
+        Self { regions, crcs: [None; N] }
+    }
+
+    /// The watched regions
+    pub fn regions(&self) -> &[Range<u32>; N] {
+        &self.regions
+    }
+
+    fn region_crc<D: FramDevice + ?Sized>(dev: &mut D, region: &Range<u32>) -> Result<u32, Mb85rcError> {
+        let mut crc = Crc32::new();
+        crc::update_from_device(&mut crc, dev, region.start, region.end.saturating_sub(region.start))?;
+        Ok(crc.finish())
+    }
+
+    /// Record the current contents as the baseline without reporting changes
+    pub fn snapshot<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<(), Mb85rcError> {
+        for (region, crc) in self.regions.iter().zip(self.crcs.iter_mut()) {
+            *crc = Some(Self::region_crc(dev, region)?);
+        }
+        Ok(())
+    }
+
+    /// Check every region, returning which ones changed since the last poll
+    ///
+    /// Changes the application made itself show up too; call
+    /// [`snapshot`](Self::snapshot) after writing to a watched region to
+    /// skip them.
+    pub fn poll<D: FramDevice + ?Sized>(&mut self, dev: &mut D) -> Result<[bool; N], Mb85rcError> {
+        let mut changed = [false; N];
+        for ((region, old), changed) in self.regions.iter().zip(self.crcs.iter_mut()).zip(changed.iter_mut()) {
+            let crc = Self::region_crc(dev, region)?;
+            *changed = old.is_some_and(|old| old != crc);
+            *old = Some(crc);
+        }
+        Ok(changed)
+    }
+}
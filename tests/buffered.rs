@@ -0,0 +1,54 @@
+//! Write coalescing in `BufferedFram`
+
+#![cfg(feature = "std")]
+
+use mb85rc::{BufferedFram, Builder, FramDevice, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+#[test]
+fn merges_adjacent_writes() {
+    let mut fram = BufferedFram::<_, 32>::new(connect());
+    for (i, field) in [[1u8, 2], [3, 4], [5, 6], [7, 8]].iter().enumerate() {
+        fram.write_at(100 + i as u32 * 2, field).unwrap();
+    }
+    assert_eq!(fram.pending(), 8);
+
+    // pending bytes are visible before they're written
+    let mut back = [0u8; 10];
+    fram.read_at(99, &mut back).unwrap();
+    assert_eq!(back, [0, 1, 2, 3, 4, 5, 6, 7, 8, 0]);
+
+    // a write somewhere else sends the merged one first
+    fram.write_at(200, &[9]).unwrap();
+    assert_eq!(fram.pending(), 1);
+    let mut dev = fram.into_inner().unwrap();
+    dev.with_bus(|sim| {
+        assert_eq!(&sim.memory()[100..108], &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(sim.memory()[200], 9);
+        // the read, the merged write and the last write
+        assert_eq!(sim.transactions(), 3);
+    });
+}
+
+#[test]
+fn flushes_when_full() {
+    let mut fram = BufferedFram::<_, 4>::new(connect());
+    fram.write_at(0, &[1, 2, 3, 4, 5, 6]).unwrap();
+    assert_eq!(fram.pending(), 2);
+    let mut dev = fram.into_inner().unwrap();
+    dev.with_bus(|sim| assert_eq!(&sim.memory()[..6], &[1, 2, 3, 4, 5, 6]));
+}
+
+#[test]
+fn zero_sized_buffer_writes_through() {
+    let mut fram = BufferedFram::<_, 0>::new(connect());
+    fram.write_at(10, &[1, 2, 3]).unwrap();
+    assert_eq!(fram.pending(), 0);
+    let mut dev = fram.into_inner().unwrap();
+    dev.with_bus(|sim| assert_eq!(&sim.memory()[10..13], &[1, 2, 3]));
+}
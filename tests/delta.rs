@@ -0,0 +1,69 @@
+//! Building deltas with `make_delta` and applying them with `apply_delta`
+
+#![cfg(feature = "std")]
+
+use mb85rc::{apply_delta, make_delta, Builder, ErrorKind, FramDevice, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+/// Write `old`, apply the delta to `new`, and check the device ends up holding `new`
+fn round_trip(old: &[u8], new: &[u8]) -> usize {
+    let mut fram = connect();
+    fram.write_at(0, old).unwrap();
+
+    let mut delta = [0u8; 2048];
+    let len = make_delta(old, new, &mut delta).unwrap();
+    let written = apply_delta(&mut fram, &delta[..len]).unwrap();
+
+    let mut back = vec![0u8; new.len()];
+    fram.read_at(0, &mut back).unwrap();
+    assert_eq!(back, new);
+    assert!(written as usize <= new.len());
+    len
+}
+
+#[test]
+fn round_trips() {
+    let old: Vec<u8> = (0..200).map(|i| (i * 7) as u8).collect();
+
+    assert_eq!(round_trip(&old, &old), 0);
+
+    let mut new = old.clone();
+    new[0] ^= 1;
+    new[199] ^= 1;
+    // two runs of one byte each
+    assert_eq!(round_trip(&old, &new), 2 * 7);
+
+    // changes closer together than a run header are merged
+    let mut new = old.clone();
+    new[50] ^= 1;
+    new[53] ^= 1;
+    assert_eq!(round_trip(&old, &new), 6 + 4);
+
+    // growing past the end of the old image
+    let mut new = old.clone();
+    new.extend_from_slice(&[0xEE; 20]);
+    assert_eq!(round_trip(&old, &new), 6 + 20);
+
+    let new: Vec<u8> = old.iter().map(|b| !b).collect();
+    round_trip(&old, &new);
+}
+
+#[test]
+fn rejects_bad_deltas() {
+    let mut fram = connect();
+    let mut delta = [0u8; 16];
+    assert_eq!(make_delta(&[0; 8], &[1; 8], &mut delta[..10]).map_err(|e| e.kind()), Err(ErrorKind::BufferTooSmall));
+
+    let len = make_delta(&[0; 8], &[1; 8], &mut delta).unwrap();
+    // truncated
+    assert_eq!(apply_delta(&mut fram, &delta[..len - 1]).map_err(|e| e.kind()), Err(ErrorKind::InvalidData));
+    // past the end of the device
+    delta[..4].copy_from_slice(&(SIZE - 4).to_le_bytes());
+    assert_eq!(apply_delta(&mut fram, &delta[..len]).map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+    assert_eq!(fram.with_bus(|sim| sim.transactions()), 0);
+}
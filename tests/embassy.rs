@@ -0,0 +1,112 @@
+//! The async driver, on its own and through an Embassy shared bus
+
+#![cfg(all(feature = "embassy", feature = "std"))]
+
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_sync::mutex::Mutex;
+use futures_util::StreamExt;
+use mb85rc::{AuditLog, Builder, ErrorKind, Fault, OneByteBanked, SampleLog, SimFram, TwoByteBanked};
+
+#[test]
+fn detects_size_and_round_trips() {
+    block_on(async {
+        let mut fram = Builder::new().with_max_transfer(16).connect_async(SimFram::new(32 * 1024)).await.unwrap();
+        assert_eq!(fram.fram_size(), 32 * 1024);
+        assert_eq!(fram.device_id().await.unwrap().model(), Some("MB85RC256V"));
+
+        let data: Vec<u8> = (0..100).collect();
+        fram.write_at(1000, &data).await.unwrap();
+        let mut back = [0u8; 100];
+        fram.read_at(1000, &mut back).await.unwrap();
+        assert_eq!(&back[..], &data[..]);
+
+        let sim = fram.release();
+        assert_eq!(&sim.memory()[1000..1100], &data[..]);
+        // 100 bytes in 16-byte transactions, twice
+        assert_eq!(sim.transactions(), 14);
+    });
+}
+
+#[test]
+fn reports_errors() {
+    block_on(async {
+        let mut sim = SimFram::new(128 * 1024);
+        sim.inject(0, Fault::Nack);
+        let mut fram = Builder::new().with_banked_addressing(&TwoByteBanked).with_size(128 * 1024).connect_async(sim).await.unwrap();
+        let err = fram.write_at(0, &[1]).await.unwrap_err();
+        assert_eq!((err.kind(), err.detail()), (ErrorKind::I2c, Some("Nack")));
+        assert_eq!(fram.write_at(128 * 1024 - 1, &[1, 2]).await.map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+
+        // a write across the bank boundary lands in both banks
+        fram.write_at(0xFFFF, &[7, 8]).await.unwrap();
+        let sim = fram.release();
+        assert_eq!(&sim.memory()[0xFFFF..0x10001], &[7, 8]);
+    });
+}
+
+#[test]
+fn shares_the_bus() {
+    block_on(async {
+        let bus = Mutex::<NoopRawMutex, _>::new(SimFram::new(8 * 1024));
+        let mut a = Builder::new().connect_shared(&bus).await.unwrap();
+        let mut b = Builder::new().with_size(8 * 1024).connect_shared(&bus).await.unwrap();
+
+        a.write_at(10, b"shared").await.unwrap();
+        let mut back = [0u8; 6];
+        b.read_at(10, &mut back).await.unwrap();
+        assert_eq!(&back, b"shared");
+    });
+
+    let bus = Mutex::<NoopRawMutex, _>::new(SimFram::new(1024).with_address(0x51));
+    let err = block_on(Builder::new().with_id_check(true).connect_shared(&bus)).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::NoDeviceId);
+}
+
+#[test]
+fn discovery_leaves_one_byte_parts_alone() {
+    block_on(async {
+        let mut sim = SimFram::new(2 * 1024).with_addressing(&OneByteBanked).without_device_id();
+        sim.memory_mut()[..2].copy_from_slice(&[0x5A, 0xA5]);
+        let fram = Builder::new()
+            .with_banked_addressing(&OneByteBanked)
+            .with_size(2 * 1024)
+            .with_address_discovery(true)
+            .connect_async(sim)
+            .await
+            .unwrap();
+        assert_eq!(&fram.release().memory()[..2], &[0x5A, 0xA5]);
+    });
+}
+
+#[test]
+fn streams_log_records() {
+    let mut fram = Builder::new().with_size(8 * 1024).connect_i2c(SimFram::new(8 * 1024));
+    let mut samples = SampleLog::<4>::load(&mut fram, 0..SampleLog::<4>::HEADER_SIZE + 3 * 4).unwrap();
+    for n in 0..5u32 {
+        samples.append(&mut fram, &(n * 10).to_le_bytes()).unwrap();
+    }
+    let mut audit = AuditLog::load(&mut fram, 1024..1024 + 4 * AuditLog::RECORD_SIZE).unwrap();
+    for addr in 0..3 {
+        audit.record(&mut fram, addr, 1, 0, 7).unwrap();
+    }
+
+    block_on(async {
+        let mut fram = Builder::new().with_size(8 * 1024).connect_async(fram.release()).await.unwrap();
+
+        // the first two samples were overwritten
+        let got: Vec<_> = core::pin::pin!(fram.samples(&samples, 0)).map(Result::unwrap).collect().await;
+        assert_eq!(got, [(2, 20u32.to_le_bytes()), (3, 30u32.to_le_bytes()), (4, 40u32.to_le_bytes())]);
+
+        let seqs: Vec<_> = core::pin::pin!(fram.audit_records(&audit, 1)).map(|r| r.unwrap().seq).collect().await;
+        assert_eq!(seqs, [1, 2]);
+
+        // a corrupt record ends the stream with an error
+        fram.write_at(1024 + AuditLog::RECORD_SIZE, &[0xFF]).await.unwrap();
+        let results: Vec<_> = core::pin::pin!(fram.audit_records(&audit, 0))
+            .map(|r| r.map(|r| r.seq).map_err(|e| e.kind()))
+            .collect()
+            .await;
+        assert_eq!(results, [Ok(0), Err(ErrorKind::InvalidData)]);
+    });
+}
@@ -0,0 +1,58 @@
+//! Searching with `FramExt::find` and `find_all`
+
+#![cfg(feature = "std")]
+
+use mb85rc::{Builder, FramDevice, FramExt, SimFram, MAX_TRANSFER, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+#[test]
+fn finds_matches_straddling_chunks() {
+    let chunk = MAX_TRANSFER as u32;
+    let needle = b"needle";
+    // every split of the needle across the first chunk boundary, searching from 0 and from 3
+    for split in 1..needle.len() as u32 {
+        for start in [0, 3] {
+            let mut fram = connect();
+            let at = start + chunk - split;
+            fram.write_at(at, needle).unwrap();
+            assert_eq!(fram.find(start..SIZE, needle).unwrap(), Some(at), "split {} from {}", split, start);
+        }
+    }
+}
+
+#[test]
+fn finds_every_match() {
+    let mut fram = connect();
+    let chunk = MAX_TRANSFER as u32;
+    let spots = [5, chunk - 2, 2 * chunk - 1, 3 * chunk + 7, SIZE - 3];
+    for &at in &spots {
+        fram.write_at(at, b"abc").unwrap();
+    }
+
+    let mut found = [0u32; 8];
+    assert_eq!(fram.find_all(0..SIZE, b"abc", &mut found).unwrap(), spots.len());
+    assert_eq!(&found[..spots.len()], &spots);
+
+    // more matches than room for them
+    let mut few = [0u32; 2];
+    assert_eq!(fram.find_all(0..SIZE, b"abc", &mut few).unwrap(), spots.len());
+    assert_eq!(few, [spots[0], spots[1]]);
+
+    // a match cut off by the end of the range doesn't count
+    assert_eq!(fram.find(SIZE - 4..SIZE - 1, b"abc").unwrap(), None);
+}
+
+#[test]
+fn matches_do_not_overlap() {
+    let mut fram = connect();
+    fram.write_at(10, b"aaaaa").unwrap();
+    let mut found = [0u32; 4];
+    assert_eq!(fram.find_all(0..SIZE, b"aa", &mut found).unwrap(), 2);
+    assert_eq!(&found[..2], &[10, 12]);
+    assert_eq!(fram.find(0..SIZE, b"").unwrap(), None);
+}
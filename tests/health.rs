@@ -0,0 +1,92 @@
+//! `HealthMonitor` state transitions
+
+#![cfg(feature = "std")]
+
+use mb85rc::{Builder, ErrorKind, Fault, FramDevice, Health, HealthMonitor, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+/// A monitored device whose first `failures` transactions time out
+fn connect(failures: u64) -> HealthMonitor<MB85RC<SimFram>> {
+    let mut sim = SimFram::new(SIZE);
+    for n in 0..failures {
+        sim.inject(n, Fault::Timeout);
+    }
+    HealthMonitor::new(Builder::new().with_size(SIZE).connect_i2c(sim))
+}
+
+#[test]
+fn degrades_then_fails() {
+    let mut monitor = connect(10);
+    for n in 1..=10 {
+        assert!(monitor.write_at(0, &[1]).is_err());
+        let expected = match n {
+            1..=2 => Health::Healthy,
+            3..=9 => Health::Degraded,
+            _ => Health::Failed,
+        };
+        assert_eq!(monitor.health(), expected, "after {} failures", n);
+        assert_eq!(monitor.consecutive_failures(), n);
+    }
+    assert_eq!(monitor.last_error().map(|e| e.kind()), Some(ErrorKind::I2c));
+}
+
+#[test]
+fn success_clears_degraded_but_not_failed() {
+    let mut monitor = connect(3).with_thresholds(3, 5);
+    for _ in 0..3 {
+        assert!(monitor.write_at(0, &[1]).is_err());
+    }
+    assert_eq!(monitor.health(), Health::Degraded);
+    monitor.write_at(0, &[1]).unwrap();
+    assert_eq!(monitor.health(), Health::Healthy);
+    assert_eq!((monitor.consecutive_failures(), monitor.failures()), (0, 3));
+
+    let mut monitor = connect(5).with_thresholds(3, 5);
+    for _ in 0..5 {
+        assert!(monitor.write_at(0, &[1]).is_err());
+    }
+    monitor.write_at(0, &[1]).unwrap();
+    assert_eq!(monitor.health(), Health::Failed);
+    monitor.reset();
+    assert_eq!(monitor.health(), Health::Healthy);
+    assert_eq!((monitor.failures(), monitor.last_error()), (0, None));
+}
+
+#[test]
+fn short_circuit_skips_the_bus() {
+    let mut monitor = connect(2).with_thresholds(1, 2).with_short_circuit(true);
+    for _ in 0..2 {
+        assert_eq!(monitor.write_at(0, &[1]).map_err(|e| e.kind()), Err(ErrorKind::I2c));
+    }
+    assert_eq!(monitor.health(), Health::Failed);
+    assert_eq!(monitor.read_at(0, &mut [0]).map_err(|e| e.kind()), Err(ErrorKind::Failed));
+
+    monitor.reset();
+    monitor.write_at(0, &[1]).unwrap();
+    assert_eq!(monitor.into_inner().with_bus(|sim| sim.transactions()), 3);
+}
+
+#[test]
+fn out_of_range_is_not_counted() {
+    let mut monitor = connect(0).with_thresholds(1, 1);
+    assert_eq!(monitor.write_at(SIZE - 1, &[1, 2]).map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+    assert_eq!(monitor.health(), Health::Healthy);
+    assert_eq!(monitor.failures(), 0);
+}
+
+#[test]
+fn crc_errors_degrade() {
+    let mut monitor = connect(0).with_crc_threshold(2);
+    monitor.crc_error();
+    assert_eq!(monitor.health(), Health::Healthy);
+    monitor.crc_error();
+    assert_eq!(monitor.health(), Health::Degraded);
+    assert_eq!(monitor.last_error().map(|e| e.kind()), Some(ErrorKind::InvalidData));
+
+    let mut monitor = connect(0).with_crc_threshold(0);
+    for _ in 0..100 {
+        monitor.crc_error();
+    }
+    assert_eq!(monitor.health(), Health::Healthy);
+}
@@ -0,0 +1,152 @@
+//! Runs the driver against the Linux `i2c-stub` module
+//!
+//! `i2c-stub` only speaks SMBus and keeps 256 byte-wide registers per chip,
+//! so the bus here turns the driver's transactions into SMBus I2C-block
+//! transfers, the FRAM is driven with one-byte addressing, and transfers are
+//! limited to the 32 bytes an SMBus block holds. A second stub chip at
+//! `0x7C` stands in for the reserved device-ID address. Its registers are
+//! shared by every FRAM address (the ID of `0x50` is registers `0xA0..=0xA2`,
+//! of `0x51` `0xA2..=0xA4`), so tests only set up IDs that don't overlap.
+//! Needs root (or
+//! access to the stub's `/dev/i2c-N`):
+//!
+//! ```sh
+//! sudo modprobe i2c-stub chip_addr=0x50,0x7c
+//! cargo test --features i2c-stub --test i2c_stub
+//! ```
+
+use std::io;
+
+use embedded_hal::blocking::i2c;
+use linux_embedded_hal::i2cdev::core::I2CDevice;
+use linux_embedded_hal::i2cdev::linux::{LinuxI2CDevice, LinuxI2CError};
+
+use mb85rc::{find_i2c_adapter, Builder, ErrorKind, FramDevice, FramExt, OneByte, MB85RC};
+
+const FRAM_ADDR: u8 = 0x50;
+const ID_ADDR: u8 = 0xF8 >> 1;
+const SIZE: u32 = 256;
+const SMBUS_BLOCK: usize = 32;
+/// Fujitsu manufacturer code and the smallest density, as an MB85RC04V would answer
+const DEVICE_ID: [u8; 3] = [0x00, 0xA0, 0x10];
+
+/// An I2C bus made of SMBus I2C-block transfers, which is all `i2c-stub` understands
+struct SmbusBus {
+    dev: LinuxI2CDevice,
+    /// NACK the device-ID address, like a part that has no device ID
+    without_id: bool,
+}
+
+impl SmbusBus {
+    fn select(&mut self, address: u8) -> Result<(), LinuxI2CError> {
+        if self.without_id && address == ID_ADDR {
+            // ENXIO, what the kernel reports for an address nobody acknowledges
+            return Err(LinuxI2CError::Io(io::Error::from_raw_os_error(6)));
+        }
+        self.dev.set_slave_address(address as u16)
+    }
+}
+
+impl i2c::Write for SmbusBus {
+    type Error = LinuxI2CError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.select(address)?;
+        self.dev.smbus_write_i2c_block_data(bytes[0], &bytes[1..])
+    }
+}
+
+impl i2c::WriteRead for SmbusBus {
+    type Error = LinuxI2CError;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.select(address)?;
+        let data = self.dev.smbus_read_i2c_block_data(bytes[0], buffer.len() as u8)?;
+        buffer.copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+/// Write the ID the stub answers for the FRAM at `fram_addr`
+fn set_device_id(dev: &mut LinuxI2CDevice, fram_addr: u8, id: [u8; 3]) {
+    // the device-ID command writes the FRAM's address shifted left, then reads three bytes
+    dev.set_slave_address(ID_ADDR as u16).unwrap();
+    dev.smbus_write_i2c_block_data(fram_addr << 1, &id).unwrap();
+}
+
+fn stub_bus() -> SmbusBus {
+    let adapter = find_i2c_adapter("SMBus stub driver").unwrap()
+        .expect("i2c-stub isn't loaded; run `modprobe i2c-stub chip_addr=0x50,0x7c`");
+    let mut dev = LinuxI2CDevice::new(adapter.dev_path(), ID_ADDR as u16).unwrap();
+    set_device_id(&mut dev, FRAM_ADDR, DEVICE_ID);
+    SmbusBus { dev, without_id: false }
+}
+
+fn connect() -> MB85RC<SmbusBus> {
+    Builder::new()
+        .with_address(FRAM_ADDR)
+        .with_size(SIZE)
+        .with_addressing(&OneByte)
+        .with_max_transfer(SMBUS_BLOCK)
+        .with_id_check(true)
+        .try_connect_i2c(stub_bus())
+        .unwrap()
+}
+
+#[test]
+fn probes_device_id() {
+    let mut fram = connect();
+    let id = fram.device_id().unwrap();
+    assert!(id.is_fujitsu());
+    assert_eq!(id.bytes(), DEVICE_ID);
+    assert_eq!(fram.model().unwrap(), Some("MB85RC04V"));
+}
+
+#[test]
+fn rejects_missing_device_id() {
+    let mut bus = stub_bus();
+    bus.without_id = true;
+    let result = Builder::new()
+        .with_address(FRAM_ADDR)
+        .with_size(SIZE)
+        .with_addressing(&OneByte)
+        .with_id_check(true)
+        .try_connect_i2c(bus);
+    assert_eq!(result.err().map(|e| e.error.kind()), Some(ErrorKind::NoDeviceId));
+}
+
+#[test]
+fn rejects_other_manufacturer() {
+    // 0x52's ID registers start past the end of 0x50's
+    let mut bus = stub_bus();
+    set_device_id(&mut bus.dev, 0x52, [0x01, 0x25, 0x00]);
+    let result = Builder::new()
+        .with_address(0x52)
+        .with_size(SIZE)
+        .with_addressing(&OneByte)
+        .with_id_check(true)
+        .try_connect_i2c(bus);
+    assert_eq!(result.err().map(|e| e.error.kind()), Some(ErrorKind::WrongDevice { found: 0x012 }));
+}
+
+#[test]
+fn chunked_round_trip() {
+    let mut fram = connect();
+    let image: Vec<u8> = (0..SIZE).map(|i| (i * 7 + 3) as u8).collect();
+
+    // several SMBus blocks per access, starting off a block boundary
+    fram.write_at(0, &image).unwrap();
+    let mut back = vec![0u8; SIZE as usize];
+    fram.read_at(0, &mut back).unwrap();
+    assert_eq!(back, image);
+
+    fram.write_at(45, &[0xAA; 100]).unwrap();
+    let mut window = [0u8; 102];
+    fram.read_at(44, &mut window).unwrap();
+    assert_eq!(window[0], image[44]);
+    assert!(window[1..101].iter().all(|&b| b == 0xAA));
+    assert_eq!(window[101], image[145]);
+
+    assert!(fram.is_blank(45..145, 0xAA).unwrap());
+    assert_eq!(fram.write_at(SIZE - 4, &[0; 8]).map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+}
@@ -0,0 +1,25 @@
+//! Decoding device IDs
+
+use mb85rc::DeviceId;
+
+#[test]
+fn sizes_of_known_parts() {
+    for (bytes, model, size) in [
+        ([0x00, 0xA0, 0x10], "MB85RC04V", 512),
+        ([0x00, 0xA3, 0x58], "MB85RC64TA", 8 * 1024),
+        ([0x00, 0xA5, 0x10], "MB85RC256V", 32 * 1024),
+        ([0x00, 0xA6, 0x58], "MB85RC512T", 64 * 1024),
+        ([0x00, 0xA7, 0x58], "MB85RC1MT", 128 * 1024),
+    ] {
+        let id = DeviceId::from_bytes(bytes);
+        assert!(id.is_fujitsu());
+        assert_eq!((id.model(), id.size()), (Some(model), size));
+    }
+}
+
+#[test]
+fn other_manufacturers() {
+    let id = DeviceId::from_bytes([0x01, 0x25, 0x10]);
+    assert_eq!((id.manufacturer(), id.density(), id.size()), (0x012, 5, 32 * 1024));
+    assert_eq!(id.model(), None);
+}
@@ -0,0 +1,72 @@
+//! Passphrase blocks and the limits on what they can ask for
+
+#![cfg(all(feature = "crypto", feature = "std"))]
+
+use mb85rc::{Builder, ErrorKind, FramDevice, Kdf, PasswordBlock, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+const SALT: [u8; 16] = *b"0123456789abcdef";
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[test]
+fn unlocks_with_each_kdf() {
+    for kdf in [Kdf::Pbkdf2 { rounds: 100 }, Kdf::Argon2 { memory_kib: 64, iterations: 2 }, Kdf::Hkdf] {
+        let mut fram = connect();
+        let block = PasswordBlock::new(100);
+        let formatted = block.format(&mut fram, b"hunter2", SALT, kdf).unwrap();
+        let unlocked = block.unlock(&mut fram, b"hunter2").unwrap();
+        assert_eq!(formatted.region_key(b"config"), unlocked.region_key(b"config"), "{:?}", kdf);
+        assert_ne!(unlocked.region_key(b"config"), unlocked.region_key(b"calibration"));
+
+        let wrong = block.unlock(&mut fram, b"hunter3").map(|_| ()).map_err(|e| e.kind());
+        assert_eq!(wrong, Err(ErrorKind::AuthFailed));
+    }
+}
+
+#[test]
+fn kdfs_give_different_keys() {
+    let mut fram = connect();
+    let block = PasswordBlock::new(0);
+    let a = block.format(&mut fram, b"pw", SALT, Kdf::Pbkdf2 { rounds: 10 }).unwrap();
+    let b = block.format(&mut fram, b"pw", SALT, Kdf::Argon2 { memory_kib: 8, iterations: 1 }).unwrap();
+    assert_ne!(a.region_key(b"x"), b.region_key(b"x"));
+}
+
+#[test]
+fn refuses_settings_over_the_limits() {
+    let mut fram = connect();
+    let block = PasswordBlock::new(0);
+    for kdf in [
+        Kdf::Pbkdf2 { rounds: Kdf::MAX_PBKDF2_ROUNDS + 1 },
+        Kdf::Argon2 { memory_kib: Kdf::MAX_ARGON2_MEMORY_KIB + 1, iterations: 1 },
+        Kdf::Argon2 { memory_kib: 8, iterations: Kdf::MAX_ARGON2_ITERATIONS + 1 },
+    ] {
+        let result = block.format(&mut fram, b"pw", SALT, kdf).map(|_| ()).map_err(|e| e.kind());
+        assert_eq!(result, Err(ErrorKind::TooLarge), "{:?}", kdf);
+    }
+
+    // a block rewritten to ask for billions of rounds is refused rather than run
+    block.format(&mut fram, b"pw", SALT, Kdf::Pbkdf2 { rounds: 10 }).unwrap();
+    let mut raw = [0u8; PasswordBlock::SIZE as usize];
+    fram.read_at(0, &mut raw).unwrap();
+    raw[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    let crc = crc32(&raw[..60]);
+    raw[60..].copy_from_slice(&crc.to_le_bytes());
+    fram.write_at(0, &raw).unwrap();
+    let result = block.unlock(&mut fram, b"pw").map(|_| ()).map_err(|e| e.kind());
+    assert_eq!(result, Err(ErrorKind::InvalidData));
+}
@@ -0,0 +1,80 @@
+//! Merging and compaction in `WriteQueue`
+
+#![cfg(feature = "std")]
+
+use mb85rc::{Builder, ErrorKind, FramDevice, SimFram, WriteQueue, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+#[test]
+fn merges_into_newest_write() {
+    let mut fram = connect();
+    let mut queue = WriteQueue::<4, 32>::new();
+    queue.enqueue(10, &[1, 2, 3, 4]).unwrap();
+    // overwrites the tail and runs on past it
+    queue.enqueue(12, &[5, 6, 7]).unwrap();
+    // directly follows
+    queue.enqueue(15, &[8]).unwrap();
+    // lands inside without extending it
+    queue.enqueue(10, &[9]).unwrap();
+    assert_eq!((queue.len(), queue.pending_bytes()), (1, 6));
+
+    // only the newest write is merged into
+    queue.enqueue(0, &[0xAA]).unwrap();
+    queue.enqueue(11, &[0xBB]).unwrap();
+    assert_eq!(queue.len(), 3);
+
+    let mut back = [0u8; 6];
+    queue.read_at(&mut fram, 10, &mut back).unwrap();
+    assert_eq!(back, [9, 0xBB, 5, 6, 7, 8]);
+
+    queue.flush(&mut fram).unwrap();
+    assert!(queue.is_empty());
+    assert_eq!(&fram.with_bus(|sim| sim.memory()[10..16].to_vec()), &[9, 0xBB, 5, 6, 7, 8]);
+    assert_eq!(fram.with_bus(|sim| sim.memory()[0]), 0xAA);
+}
+
+#[test]
+fn slides_data_back_to_make_room() {
+    let mut fram = connect();
+    let mut queue = WriteQueue::<4, 8>::new();
+    queue.enqueue(0, &[1; 6]).unwrap();
+    queue.enqueue(100, &[2; 2]).unwrap();
+    assert_eq!(queue.enqueue(200, &[3]).map_err(|e| e.kind()), Err(ErrorKind::Full));
+
+    // draining the front frees space at the start of the buffer, which a
+    // new write can only use once the rest has slid back
+    assert_eq!(queue.flush_some(&mut fram, 6).unwrap(), 6);
+    queue.enqueue(200, &[3; 5]).unwrap();
+    queue.enqueue(205, &[4]).unwrap();
+    assert_eq!((queue.len(), queue.pending_bytes()), (2, 8));
+
+    let mut back = [0u8; 6];
+    queue.read_at(&mut fram, 200, &mut back).unwrap();
+    assert_eq!(back, [3, 3, 3, 3, 3, 4]);
+
+    queue.flush(&mut fram).unwrap();
+    let mem = fram.with_bus(|sim| sim.memory().to_vec());
+    assert_eq!((&mem[100..102], &mem[200..206]), (&[2u8; 2][..], &[3, 3, 3, 3, 3, 4][..]));
+}
+
+#[test]
+fn splits_writes_over_the_budget() {
+    let mut fram = connect();
+    let mut queue = WriteQueue::<2, 16>::new();
+    queue.enqueue(0, &[7; 10]).unwrap();
+    assert_eq!(queue.flush_some(&mut fram, 4).unwrap(), 4);
+    assert_eq!(queue.pending_bytes(), 6);
+
+    let mut back = [0u8; 10];
+    fram.read_at(0, &mut back).unwrap();
+    assert_eq!(back, [7, 7, 7, 7, 0, 0, 0, 0, 0, 0]);
+    queue.flush(&mut fram).unwrap();
+    fram.read_at(0, &mut back).unwrap();
+    assert_eq!(back, [7; 10]);
+    assert_eq!(queue.enqueue(0, &[0; 17]).map_err(|e| e.kind()), Err(ErrorKind::TooLarge));
+}
@@ -0,0 +1,72 @@
+//! How `RecoveringI2c` and `Escalate` retry failed transactions
+
+#![cfg(feature = "std")]
+
+use std::cell::Cell;
+
+use mb85rc::{Builder, ErrorKind, Escalate, Fault, FramDevice, RecoveringI2c, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+/// A device whose first `failures` transactions NACK, behind `Escalate` with
+/// a recovery that counts its runs in `runs` and reports `works`
+fn connect(
+    failures: u64,
+    attempts: u32,
+    runs: &Cell<u32>,
+    works: bool,
+) -> MB85RC<RecoveringI2c<SimFram, Escalate<impl FnMut(&mut SimFram) -> bool + '_>>> {
+    let mut sim = SimFram::new(SIZE);
+    for n in 0..failures {
+        sim.inject(n, Fault::Nack);
+    }
+    let recovery = move |_: &mut SimFram| {
+        runs.set(runs.get() + 1);
+        works
+    };
+    let bus = RecoveringI2c::new(sim, Escalate::new(recovery).with_attempts(attempts));
+    Builder::new().with_size(SIZE).connect_i2c(bus)
+}
+
+#[test]
+fn retries_without_recovering() {
+    let runs = Cell::new(0);
+    let mut fram = connect(1, 2, &runs, true);
+    fram.write_at(0, &[1, 2, 3]).unwrap();
+    assert_eq!(runs.get(), 0);
+    assert_eq!(fram.with_bus(|bus| bus.policy().recoveries()), 0);
+}
+
+#[test]
+fn recovers_once_attempts_run_out() {
+    let runs = Cell::new(0);
+    let mut fram = connect(3, 3, &runs, true);
+    fram.write_at(0, &[1, 2, 3]).unwrap();
+    assert_eq!(runs.get(), 1);
+    assert_eq!(fram.with_bus(|bus| bus.policy().recoveries()), 1);
+
+    let mut back = [0u8; 3];
+    fram.read_at(0, &mut back).unwrap();
+    assert_eq!(back, [1, 2, 3]);
+}
+
+#[test]
+fn gives_up_when_recovery_fails() {
+    let runs = Cell::new(0);
+    let mut fram = connect(2, 2, &runs, false);
+    assert_eq!(fram.write_at(0, &[1]).map_err(|e| e.kind()), Err(ErrorKind::I2c));
+    assert_eq!(runs.get(), 1);
+    assert_eq!(fram.with_bus(|bus| bus.policy().recoveries()), 1);
+
+    // the faults are used up, so the bus works again
+    fram.write_at(0, &[1]).unwrap();
+}
+
+#[test]
+fn one_retry_after_recovering() {
+    let runs = Cell::new(0);
+    let mut fram = connect(3, 2, &runs, true);
+    assert_eq!(fram.write_at(0, &[1]).map_err(|e| e.kind()), Err(ErrorKind::I2c));
+    assert_eq!(runs.get(), 1);
+    assert_eq!(fram.with_bus(|bus| bus.policy().recoveries()), 1);
+}
@@ -0,0 +1,119 @@
+//! The simulated device itself
+
+#![cfg(feature = "std")]
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+use mb85rc::{Builder, DeviceId, ErrorKind, Fault, FramDevice, OneByteBanked, SimFram, TwoByteBanked};
+
+#[test]
+#[should_panic(expected = "at least one byte")]
+fn rejects_zero_size() {
+    SimFram::new(0);
+}
+
+#[test]
+fn open_rejects_zero_size() {
+    let path = std::env::temp_dir().join(format!("mb85rc-sim-zero-{}.bin", std::process::id()));
+    let err = SimFram::open(&path, 0).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!path.exists());
+}
+
+#[test]
+fn wraps_at_end_of_memory() {
+    let mut sim = SimFram::new(256);
+    sim.write(0x50, &[0x00, 0xFE, 1, 2, 3, 4]).unwrap();
+    assert_eq!(&sim.memory()[254..], &[1, 2]);
+    assert_eq!(&sim.memory()[..2], &[3, 4]);
+
+    let mut back = [0u8; 4];
+    sim.write_read(0x50, &[0x00, 0xFE], &mut back).unwrap();
+    assert_eq!(back, [1, 2, 3, 4]);
+}
+
+#[test]
+fn reports_density_and_banks() {
+    let mut fram = Builder::new().with_banked_addressing(&TwoByteBanked).with_size(128 * 1024).connect_i2c(SimFram::new(128 * 1024));
+    assert_eq!(fram.capacity(), 128 * 1024);
+
+    // the upper address bit goes in the slave address
+    fram.write_at(0x1_0005, &[0xAA]).unwrap();
+    fram.with_bus(|sim| {
+        assert_eq!(sim.memory()[0x1_0005], 0xAA);
+        let mut byte = [0u8];
+        sim.write_read(0x51, &[0x00, 0x05], &mut byte).unwrap();
+        assert_eq!(byte, [0xAA]);
+    });
+}
+
+#[test]
+fn reports_the_product_for_the_size() {
+    for (size, model) in [(512, Some("MB85RC04V")), (8 * 1024, Some("MB85RC64TA")), (32 * 1024, Some("MB85RC256V")), (128 * 1024, Some("MB85RC1MT")), (2 * 1024, None)] {
+        assert_eq!(DeviceId::from_bytes(SimFram::new(size).device_id()).model(), model, "{} bytes", size);
+    }
+}
+
+#[test]
+fn one_byte_banked_framing() {
+    let sim = SimFram::new(2 * 1024).with_addressing(&OneByteBanked).without_device_id();
+    let mut fram = Builder::new().with_banked_addressing(&OneByteBanked).with_size(2 * 1024).connect_i2c(sim);
+
+    // bank 3 of 8, one address byte
+    fram.write_at(0x3FE, &[1, 2, 3]).unwrap();
+    fram.with_bus(|sim| {
+        assert_eq!(&sim.memory()[0x3FE..0x401], &[1, 2, 3]);
+        let mut back = [0u8; 2];
+        sim.write_read(0x53, &[0xFE], &mut back).unwrap();
+        assert_eq!(back, [1, 2]);
+        assert!(sim.write_read(0x58, &[0x00], &mut back).is_err());
+    });
+}
+
+#[test]
+fn discovery_leaves_one_byte_parts_alone() {
+    // an MB85RC16: no device ID, so discovery falls back to a plain read
+    let mut sim = SimFram::new(2 * 1024).with_addressing(&OneByteBanked).without_device_id();
+    sim.memory_mut()[..2].copy_from_slice(&[0x5A, 0xA5]);
+    let mut fram = Builder::new()
+        .with_banked_addressing(&OneByteBanked)
+        .with_size(2 * 1024)
+        .with_address_discovery(true)
+        .connect_i2c(sim);
+
+    fram.with_bus(|sim| assert_eq!(&sim.memory()[..2], &[0x5A, 0xA5]));
+}
+
+#[test]
+fn write_half_of_write_read_lands() {
+    let mut sim = SimFram::new(256);
+    let mut back = [0u8; 1];
+    sim.write_read(0x50, &[0x00, 0x10, 0xAB], &mut back).unwrap();
+    assert_eq!(sim.memory()[0x10], 0xAB);
+}
+
+#[test]
+fn bus_errors_keep_their_text() {
+    let mut sim = SimFram::new(1024);
+    sim.inject(0, Fault::Nack);
+    let mut fram = Builder::new().with_size(1024).connect_i2c(sim);
+    let err = fram.write_at(0, &[1]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::I2c);
+    assert_eq!(err.detail(), Some("Nack"));
+    assert_eq!(err.to_string(), "I2C Error: Nack");
+
+    let err = fram.write_at(1024, &[1]).unwrap_err();
+    assert_eq!((err.kind(), err.detail()), (ErrorKind::OutOfRange, None));
+}
+
+#[test]
+fn persists_to_file() {
+    let path = std::env::temp_dir().join(format!("mb85rc-sim-{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let mut fram = Builder::new().with_size(512).connect_i2c(SimFram::open(&path, 512).unwrap());
+        fram.write_at(500, &[9, 8, 7]).unwrap();
+    }
+    let sim = SimFram::open(&path, 512).unwrap();
+    assert_eq!(&sim.memory()[500..503], &[9, 8, 7]);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,37 @@
+//! Persistent counters and the write counters built on them
+
+#![cfg(feature = "std")]
+
+use mb85rc::{Builder, ErrorKind, FramDevice, PersistentStats, SimFram, WriteCounters, MB85RC};
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(1024).connect_i2c(SimFram::new(1024))
+}
+
+#[test]
+fn round_trips_records_longer_than_one_read() {
+    let mut fram = connect();
+    let mut stats = PersistentStats::<6>::load(&mut fram, 100).unwrap();
+    stats.add_uptime(u64::MAX - 1);
+    for i in 0..6 {
+        stats.add(i, (i as u64 + 1) << 40).unwrap();
+    }
+    stats.flush(&mut fram).unwrap();
+
+    let stats = PersistentStats::<6>::load(&mut fram, 100).unwrap();
+    assert_eq!(stats.uptime(), u64::MAX - 1);
+    assert_eq!(stats.counters(), &[1 << 40, 2 << 40, 3 << 40, 4 << 40, 5 << 40, 6 << 40]);
+}
+
+#[test]
+fn out_of_range_counters_are_errors() {
+    let mut fram = connect();
+    let stats = PersistentStats::<2>::load(&mut fram, 0).unwrap();
+    assert_eq!(stats.counter(1).unwrap(), 0);
+    assert_eq!(stats.counter(2).map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+
+    let mut counted = WriteCounters::load(connect(), 0, [100..200, 200..300]).unwrap();
+    counted.write_at(150, &[1]).unwrap();
+    assert_eq!(counted.count(0).unwrap(), 1);
+    assert_eq!(counted.count(2).map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+}
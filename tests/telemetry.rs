@@ -0,0 +1,72 @@
+//! Encoding and decoding telemetry blobs
+
+#![cfg(feature = "std")]
+
+use mb85rc::telemetry::{self, VERSION};
+use mb85rc::{Builder, DeviceId, Diagnostics, ErrorKind, FramDevice, Health, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+fn report() -> Diagnostics {
+    Diagnostics {
+        device_id: Some(DeviceId::from_bytes([0x00, 0xA3, 0x58])),
+        address: 0x52,
+        size: SIZE,
+        max_transfer: 64,
+        arbitration_retries: 3,
+        health: Some(Health::Degraded),
+        consecutive_failures: 4,
+        failures: 17,
+        crc_errors: 2,
+        last_error: Some(ErrorKind::InvalidData),
+    }
+}
+
+#[test]
+fn round_trips() {
+    let mut fram = connect();
+    fram.write_at(0, b"header").unwrap();
+    fram.write_at(500, &[0xAB; 40]).unwrap();
+
+    let regions = [0..6, 500..540, 1000..1000];
+    let mut blob = vec![0u8; telemetry::encoded_len(&regions)];
+    assert_eq!(telemetry::encode(&mut fram, &report(), &regions, &mut blob).unwrap(), blob.len());
+    assert_eq!((&blob[..4], blob[4]), (&b"FRTM"[..], VERSION));
+
+    let decoded = telemetry::decode(&blob).unwrap();
+    assert_eq!(decoded.diagnostics, report());
+    assert_eq!(decoded.region_count(), 3);
+    let regions: Vec<_> = decoded.regions().collect();
+    assert_eq!(regions, [(0, &b"header"[..]), (500, &[0xAB; 40][..]), (1000, &[][..])]);
+
+    // a report with nothing filled in survives too
+    let empty = Diagnostics { device_id: None, health: None, last_error: None, ..report() };
+    let mut blob = vec![0u8; telemetry::encoded_len(&[])];
+    telemetry::encode(&mut fram, &empty, &[], &mut blob).unwrap();
+    let decoded = telemetry::decode(&blob).unwrap();
+    assert_eq!((decoded.diagnostics, decoded.region_count()), (empty, 0));
+}
+
+#[test]
+fn rejects_damaged_blobs() {
+    let mut fram = connect();
+    let regions = [10..30, 40..50];
+    let mut blob = vec![0u8; telemetry::encoded_len(&regions)];
+    telemetry::encode(&mut fram, &report(), &regions, &mut blob).unwrap();
+
+    let invalid = Err(ErrorKind::InvalidData);
+    assert_eq!(telemetry::decode(&blob[..blob.len() - 1]).map(|_| ()).map_err(|e| e.kind()), invalid);
+    for i in [0, 4, 20, blob.len() - 10, blob.len() - 1] {
+        let mut bad = blob.clone();
+        bad[i] ^= 0x10;
+        assert_eq!(telemetry::decode(&bad).map(|_| ()).map_err(|e| e.kind()), invalid, "byte {}", i);
+    }
+
+    let short = &mut blob[..telemetry::encoded_len(&regions) - 1];
+    assert_eq!(telemetry::encode(&mut fram, &report(), &regions, short).map_err(|e| e.kind()), Err(ErrorKind::BufferTooSmall));
+    assert_eq!(telemetry::encode(&mut fram, &report(), &[0..10, 1000..1100], &mut blob).map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+}
@@ -0,0 +1,51 @@
+//! The fixtures in `mb85rc::testing` hold what they say they do
+
+#![cfg(feature = "testing")]
+
+use mb85rc::testing::{audit_log, config_history, fixture_audit_record, fixture_sample, sample_log, tlv_store};
+use mb85rc::AuditRecord;
+
+#[test]
+fn tlv_fixture() {
+    let mut store = tlv_store(256, &[(1, b"serial"), (2, &[0xAA; 6]), (1, b"newer")]);
+    let mut buf = [0u8; 16];
+    assert_eq!(store.get(1, &mut buf).unwrap(), Some(5));
+    assert_eq!(&buf[..5], b"newer");
+    assert_eq!(store.records().count(), 3);
+}
+
+#[test]
+fn config_history_fixture() {
+    let mut history = config_history(512, 4, &[b"v0", b"v1", b"v2", b"v3", b"v4"]);
+    let mut buf = [0u8; 8];
+    let latest = history.load_latest(&mut buf).unwrap().unwrap();
+    assert_eq!((latest.seq, latest.timestamp, &buf[..2]), (4, 4, &b"v4"[..]));
+    // the oldest version has been overwritten
+    assert!(history.load(0, &mut buf).is_err());
+    history.load(1, &mut buf).unwrap();
+    assert_eq!(&buf[..2], b"v1");
+}
+
+#[test]
+fn sample_log_fixture() {
+    let (mut fram, log) = sample_log::<6>(256, 100);
+    let capacity = log.capacity();
+    assert!(capacity < 100);
+    assert_eq!(log.range(), 100 - capacity..100);
+    for n in log.range() {
+        assert_eq!(log.get(&mut fram, n).unwrap(), Some(fixture_sample::<6>(n)));
+    }
+    assert_ne!(fixture_sample::<6>(1), fixture_sample::<6>(2));
+}
+
+#[test]
+fn audit_log_fixture() {
+    let (mut fram, log) = audit_log(24 * 8, 10);
+    assert_eq!(log.range(), 10 - log.capacity()..10);
+    let mut out = [AuditRecord { seq: 0, addr: 0, len: 0, time: 0, tag: 0 }; 16];
+    let got = log.read_range(&mut fram, 0..10, &mut out).unwrap();
+    for (i, seq) in got.enumerate() {
+        assert_eq!(out[i], fixture_audit_record(seq));
+    }
+    assert_eq!(log.latest(&mut fram).unwrap(), Some(fixture_audit_record(9)));
+}
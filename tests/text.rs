@@ -0,0 +1,74 @@
+//! Hex and base64 export and import
+
+#![cfg(feature = "std")]
+
+use mb85rc::text::{self, Encoding};
+use mb85rc::{Builder, ErrorKind, FramDevice, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+#[test]
+fn round_trips() {
+    let data: Vec<u8> = (0..=255).chain(0..45).collect();
+    for encoding in [Encoding::Hex, Encoding::Base64] {
+        // every length of base64 padding
+        for len in [0, 1, 2, 3, data.len()] {
+            let mut fram = connect();
+            fram.write_at(100, &data[..len]).unwrap();
+            let mut out = String::new();
+            text::export(&mut fram, 100..100 + len as u32, encoding, &mut out).unwrap();
+
+            let mut copy = connect();
+            assert_eq!(text::import(&mut copy, 500, encoding, &out).unwrap(), len as u32);
+            let mut back = vec![0u8; len];
+            copy.read_at(500, &mut back).unwrap();
+            assert_eq!(back, &data[..len], "{:?} of {} bytes", encoding, len);
+        }
+    }
+}
+
+#[test]
+fn known_encodings() {
+    let mut fram = connect();
+    fram.write_at(0, b"FRAM!").unwrap();
+    let mut hex = String::new();
+    text::export(&mut fram, 0..5, Encoding::Hex, &mut hex).unwrap();
+    assert_eq!(hex, "4652414d21");
+    let mut base64 = String::new();
+    text::export(&mut fram, 0..5, Encoding::Base64, &mut base64).unwrap();
+    assert_eq!(base64, "RlJBTSE=");
+}
+
+#[test]
+fn ignores_whitespace() {
+    let mut fram = connect();
+    assert_eq!(text::import(&mut fram, 0, Encoding::Hex, " 46 52\n41\t4d ").unwrap(), 4);
+    assert_eq!(text::import(&mut fram, 10, Encoding::Base64, "Rl\r\nJB TSE=\n").unwrap(), 5);
+    let mut back = [0u8; 15];
+    fram.read_at(0, &mut back).unwrap();
+    assert_eq!(&back[..4], b"FRAM");
+    assert_eq!(&back[10..], b"FRAM!");
+}
+
+#[test]
+fn rejects_malformed_text() {
+    let mut fram = connect();
+    for (encoding, bad) in [
+        (Encoding::Hex, "465"),
+        (Encoding::Hex, "4g"),
+        (Encoding::Base64, "RlJBTSE"),
+        (Encoding::Base64, "RlJ*TSE="),
+        (Encoding::Base64, "R==="),
+    ] {
+        let result = text::import(&mut fram, 0, encoding, bad);
+        assert_eq!(result.map_err(|e| e.kind()), Err(ErrorKind::InvalidData), "{:?} {:?}", encoding, bad);
+    }
+    assert!(fram.with_bus(|sim| sim.memory().iter().all(|&b| b == 0)));
+
+    let result = text::import(&mut fram, SIZE - 1, Encoding::Hex, "0102");
+    assert_eq!(result.map_err(|e| e.kind()), Err(ErrorKind::OutOfRange));
+}
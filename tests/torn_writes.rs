@@ -0,0 +1,161 @@
+//! Records kept in two copies survive a write torn at any point
+
+#![cfg(feature = "std")]
+
+use mb85rc::{Block, BootFlags, BootSlot, Builder, Fault, FramDevice, FramHeap, FramSlice, Mb85rcError, Migration, Migrator, PersistentStats, SimFram, MB85RC};
+
+const SIZE: u32 = 1024;
+
+fn connect() -> MB85RC<SimFram> {
+    Builder::new().with_size(SIZE).connect_i2c(SimFram::new(SIZE))
+}
+
+/// Run `update` once for every transaction it makes and every length that
+/// transaction could be cut short at, tearing it there, and hand `check`
+/// the device afterwards along with whether the update reported success
+///
+/// A failed update may still have landed in full, if the tear came after
+/// the last byte that mattered, so checks accept the old or the new state.
+fn sweep(setup: impl Fn(&mut MB85RC<SimFram>), update: impl Fn(&mut MB85RC<SimFram>) -> bool, check: impl Fn(&mut MB85RC<SimFram>, bool)) {
+    let mut clean = connect();
+    setup(&mut clean);
+    let before = clean.with_bus(|sim| sim.transactions());
+    assert!(update(&mut clean));
+    let count = clean.with_bus(|sim| sim.transactions()) - before;
+
+    for at in 0..count {
+        for keep in 0..16 {
+            let mut fram = connect();
+            setup(&mut fram);
+            fram.with_bus(|sim| sim.inject(sim.transactions() + at, Fault::TornWrite(keep)));
+            let done = update(&mut fram);
+            fram.with_bus(|sim| sim.clear_faults());
+            check(&mut fram, done);
+        }
+    }
+}
+
+#[test]
+fn boot_flags_survive_torn_switch() {
+    let flags = BootFlags::new(0);
+    sweep(
+        |fram| flags.switch(fram, BootSlot::A, 3).and_then(|_| flags.confirm(fram)).unwrap(),
+        |fram| flags.switch(fram, BootSlot::B, 3).is_ok(),
+        |fram, done| {
+            let state = flags.state(fram).unwrap();
+            let state = (state.active, state.attempts_left, state.confirmed);
+            let (old, new) = ((BootSlot::A, 3, true), (BootSlot::B, 3, false));
+            assert!(state == new || (!done && state == old), "{:?}", state);
+        },
+    );
+}
+
+#[test]
+fn boot_flags_survive_torn_trial_boot() {
+    let flags = BootFlags::new(0);
+    sweep(
+        |fram| flags.switch(fram, BootSlot::B, 1).unwrap(),
+        |fram| flags.boot(fram).is_ok(),
+        |fram, done| {
+            let state = flags.state(fram).unwrap();
+            assert_eq!(state.active, BootSlot::B);
+            assert!(state.attempts_left == 0 || (!done && state.attempts_left == 1));
+        },
+    );
+}
+
+#[test]
+fn stats_survive_torn_flush() {
+    sweep(
+        |fram| {
+            let mut stats = PersistentStats::<2>::load(fram, 0).unwrap();
+            stats.add_uptime(100);
+            stats.add(1, 7).unwrap();
+            stats.flush(fram).unwrap();
+        },
+        |fram| {
+            let Ok(mut stats) = PersistentStats::<2>::load(fram, 0) else {
+                return false;
+            };
+            stats.add_uptime(50);
+            stats.increment(0).unwrap();
+            stats.flush(fram).is_ok()
+        },
+        |fram, done| {
+            let stats = PersistentStats::<2>::load(fram, 0).unwrap();
+            let state = (stats.uptime(), *stats.counters());
+            assert!(state == (150, [1, 7]) || (!done && state == (100, [0, 7])), "{:?}", state);
+        },
+    );
+}
+
+#[test]
+fn migration_survives_torn_step() {
+    // adding one to every byte shows up if the step ever runs twice
+    fn bump(region: &mut FramSlice<'_, MB85RC<SimFram>>) -> Result<(), Mb85rcError> {
+        let mut data = [0u8; 8];
+        region.read_at(0, &mut data)?;
+        data.iter_mut().for_each(|b| *b += 1);
+        region.write_at(0, &data)
+    }
+    let steps = [Migration { from: 0, run: bump }];
+    let migrator = Migrator::new(&steps, 0, 100..108).with_scratch(200);
+
+    sweep(
+        |fram| fram.write_at(100, &[5; 8]).unwrap(),
+        |fram| migrator.run(fram).is_ok(),
+        |fram, _| {
+            // the next run finishes or recovers whatever was interrupted
+            assert_eq!(migrator.run(fram).unwrap(), 1);
+            let mut data = [0u8; 8];
+            fram.read_at(100, &mut data).unwrap();
+            assert_eq!(data, [6; 8]);
+        },
+    );
+}
+
+#[test]
+fn heap_survives_torn_alloc() {
+    sweep(
+        |fram| {
+            FramHeap::format(&mut *fram).unwrap();
+        },
+        |fram| FramHeap::mount(&mut *fram).and_then(|mut heap| heap.alloc(40)).is_ok(),
+        |fram, done| {
+            let stats = FramHeap::mount(&mut *fram).unwrap().check().unwrap();
+            assert!((stats.blocks, stats.used) == (2, 40) || (!done && (stats.blocks, stats.used) == (1, 0)), "{:?}", stats);
+        },
+    );
+}
+
+#[test]
+fn heap_survives_torn_free() {
+    let setup = |fram: &mut MB85RC<SimFram>| {
+        let mut heap = FramHeap::format(&mut *fram).unwrap();
+        let a = heap.alloc(40).unwrap();
+        let b = heap.alloc(40).unwrap();
+        heap.write(a, 0, b"kept").unwrap();
+        heap.write(b, 0, b"gone").unwrap();
+        assert_eq!((a.into_raw(), b.into_raw()), (keep().into_raw(), drop_block().into_raw()));
+    };
+    fn keep() -> Block {
+        Block::from_raw(FramHeap::<&mut MB85RC<SimFram>>::HEADER_SIZE)
+    }
+    fn drop_block() -> Block {
+        Block::from_raw(2 * FramHeap::<&mut MB85RC<SimFram>>::HEADER_SIZE + 40)
+    }
+
+    sweep(
+        setup,
+        |fram| FramHeap::mount(&mut *fram).and_then(|mut heap| heap.free(drop_block())).is_ok(),
+        |fram, done| {
+            // freeing and merging with the free space after it may stop in between
+            let mut heap = FramHeap::mount(&mut *fram).unwrap();
+            let stats = heap.check().unwrap();
+            assert!(stats.used == 40 || (!done && stats.used == 80), "{:?}", stats);
+            let mut data = [0u8; 4];
+            heap.read(keep(), 0, &mut data).unwrap();
+            assert_eq!(&data, b"kept");
+        },
+    );
+}
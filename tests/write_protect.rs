@@ -0,0 +1,84 @@
+//! A driver with a WP pin, through the wrappers that take the driver itself
+
+#![cfg(feature = "std")]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use mb85rc::{Builder, ChunkedRead, ChunkedWrite, ErrorKind, Mb85rcError, SharedFram, SimFram, WriteProtect, MB85RC};
+
+const SIZE: u32 = 1024;
+
+/// A pin that remembers the level it was last driven to
+#[derive(Clone, Default)]
+struct Pin(Rc<Cell<bool>>);
+
+impl WriteProtect for Pin {
+    fn set_protected(&mut self, protected: bool) -> Result<(), Mb85rcError> {
+        self.0.set(protected);
+        Ok(())
+    }
+}
+
+fn connect(pin: Pin, auto_unprotect: bool) -> MB85RC<SimFram, Pin> {
+    Builder::new()
+        .with_size(SIZE)
+        .with_max_transfer(16)
+        .with_write_protect(pin)
+        .with_auto_unprotect(auto_unprotect)
+        .connect_i2c(SimFram::new(SIZE))
+}
+
+#[test]
+fn chunked_transfers_with_pin() {
+    let pin = Pin::default();
+    let mut fram = connect(pin.clone(), true);
+    assert!(pin.0.get());
+
+    let data: Vec<u8> = (0..100).collect();
+    let mut write = ChunkedWrite::new(fram.address(10).unwrap(), &data);
+    while !write.step(&mut fram).unwrap() {
+        assert!(pin.0.get(), "left unprotected between steps");
+    }
+
+    let mut back = [0u8; 100];
+    let mut read = ChunkedRead::new(fram.address(10).unwrap(), &mut back);
+    while !read.step(&mut fram).unwrap() {}
+    assert_eq!(&back[..], &data[..]);
+}
+
+#[test]
+fn chunked_write_refused_while_protected() {
+    let mut fram = connect(Pin::default(), false);
+    let mut write = ChunkedWrite::new(fram.address(0).unwrap(), &[1, 2, 3]);
+    assert_eq!(write.step(&mut fram).map_err(|e| e.kind()), Err(ErrorKind::Locked));
+    assert_eq!(write.remaining(), 3);
+}
+
+#[test]
+fn shared_with_pin() {
+    let pin = Pin::default();
+    let mut a = SharedFram::new(connect(pin.clone(), true));
+    let mut b = a.clone();
+
+    assert_eq!(a.write(b"hello").unwrap(), 5);
+    let mut back = [0u8; 5];
+    assert_eq!(b.read(&mut back).unwrap(), 5);
+    assert_eq!(&back, b"hello");
+    assert!(pin.0.get());
+    assert!(a.with_fram(|fram| fram.is_protected()));
+}
+
+#[test]
+fn release_hands_back_pin() {
+    let pin = Pin::default();
+    let mut fram = connect(pin.clone(), false);
+    fram.unprotect().unwrap();
+    fram.fram_write(fram.address(3).unwrap(), &[0xAB]).unwrap();
+
+    let (sim, mut released) = fram.release_with_pin();
+    assert_eq!(sim.memory()[3], 0xAB);
+    assert!(!pin.0.get());
+    released.set_protected(true).unwrap();
+    assert!(pin.0.get());
+}